@@ -1,17 +1,30 @@
 use std::io;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use data::repository::{
-    application::ApplicationRepository, connect, error::RepositoryError, grant::GrantRepository,
-    user::UserRepository,
+    application::ApplicationRepository, authorization_code::AuthorizationCodeRepository, connect,
+    consent::ConsentRepository, device_code::DeviceCodeRepository,
+    email_verification_token::EmailVerificationTokenRepository, error::RepositoryError,
+    federation_request::FederationRequestRepository, grant::GrantRepository,
+    group::GroupRepository, impersonation_event::ImpersonationEventRepository,
+    key_promotion::KeyPromotionRepository, login_event::LoginEventRepository,
+    login_throttle::LoginThrottleRepository, mfa_challenge::MfaChallengeRepository,
+    password_reset_token::PasswordResetTokenRepository,
+    personal_access_token::PersonalAccessTokenRepository, recovery_code::RecoveryCodeRepository,
+    refresh_token::RefreshTokenRepository, role::RoleRepository, session::SessionRepository,
+    user::UserRepository, user_identity::UserIdentityRepository, user_totp::UserTotpRepository,
 };
 use libbuildinfo::BuildInfo;
-use poem::{Request, http::StatusCode, web::Data};
+use poem::{
+    Request,
+    http::{Method, StatusCode},
+    web::Data,
+};
 use poem_openapi::{
     OpenApi, SecurityScheme, Tags,
     auth::Bearer,
     param::{Path, Query},
-    payload::{Json, PlainText},
+    payload::{Form, Json, PlainText},
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -19,15 +32,66 @@ use valuable::Valuable;
 
 use crate::{
     Args,
+    models::{login_event::LoginOutcome, oauth::OpenIdConfiguration},
     services::{
         ApiServices,
-        auth::login::{LoginPayload, LoginResponse, login},
-        core::jwt::Claims,
+        auth::{
+            email_verification::{
+                SendEmailVerificationResponse, VerifyEmailPayload, VerifyEmailResponse,
+                send_email_verification, verify_email,
+            },
+            federation::{
+                FederationCallbackPayload,
+                link::{LinkIdentityResponse, link_identity},
+                list::{ListIdentitiesResponse, list_identities},
+                login::{FederatedLoginResponse, federated_login},
+                providers::{ListFederationProvidersResponse, list_providers},
+                start::{StartFederationResponse, start_link, start_login},
+                unlink::{UnlinkIdentityResponse, unlink_identity},
+            },
+            login::{LoginPayload, LoginResponse, login},
+            logout::{LogoutAllResponse, LogoutResponse, logout, logout_all},
+            mfa::{
+                confirm::{ConfirmTotpResponse, TotpCodePayload, confirm_totp},
+                disable::{DisableTotpResponse, disable_totp},
+                enroll::{EnrollTotpResponse, enroll_totp},
+                login::{MfaLoginPayload, login_mfa},
+                recovery_codes::{RegenerateRecoveryCodesResponse, regenerate_recovery_codes},
+                status::{MfaStatusResponse, mfa_status},
+            },
+            password::{ChangePasswordPayload, ChangePasswordResponse, change_password},
+            password_reset::{
+                CompletePasswordResetPayload, CompletePasswordResetResponse, ForgotPasswordPayload,
+                ForgotPasswordResponse, complete_password_reset, forgot_password,
+            },
+            personal_access_token::{
+                authenticate,
+                create::{
+                    CreatePersonalAccessTokenPayload, CreatePersonalAccessTokenResponse,
+                    create_personal_access_token,
+                },
+                is_personal_access_token,
+                list::{ListPersonalAccessTokensResponse, list_personal_access_tokens},
+                revoke::{RevokePersonalAccessTokenResponse, revoke_personal_access_token},
+            },
+            refresh::{RefreshPayload, RefreshResponse, refresh},
+            revalidate::revalidate,
+            session::is_session_revoked,
+        },
+        core::{
+            jwt::{Claims, SubjectType, UserClaims},
+            token,
+        },
         manage::{
             application::{
                 create::{CreateApplicationPayload, CreateApplicationResponse, create_application},
+                delete_secret::{DeleteSecretResponse, delete_secret},
                 get::{GetApplicationResponse, get_application},
                 list::{ListApplicationsResponse, list_applications},
+                modify_grant::{
+                    ModifyClientGrantPayload, ModifyClientGrantResponse, modify_client_grant,
+                },
+                rotate_secret::{RotateSecretResponse, rotate_secret},
                 update::{UpdateApplicationPayload, UpdateApplicationResponse, update_application},
             },
             grant::{
@@ -36,17 +100,78 @@ use crate::{
                     GetGrantByApplicationIdResponse, get_grants_by_application_id,
                 },
                 get_by_id::{GetGrantByIdResponse, get_grant_by_id},
+                list_implications::{ListGrantImplicationsResponse, list_grant_implications},
+                modify_implication::{
+                    ModifyGrantImplicationPayload, ModifyGrantImplicationResponse,
+                    modify_grant_implication,
+                },
+            },
+            group::{
+                create::{CreateGroupPayload, CreateGroupResponse, create_group},
+                delete::{DeleteGroupResponse, delete_group},
+                get::{GetGroupResponse, get_group},
+                list::{ListGroupsResponse, list_groups},
+                modify_grant::{
+                    ModifyGroupGrantPayload, ModifyGroupGrantResponse, modify_group_grant,
+                },
+                modify_member::{
+                    ModifyGroupMemberPayload, ModifyGroupMemberResponse, modify_group_member,
+                },
+                modify_role::{ModifyGroupRolePayload, ModifyGroupRoleResponse, modify_group_role},
+                update::{UpdateGroupPayload, UpdateGroupResponse, update_group},
+            },
+            key::{
+                list::{ListKeysResponse, list_keys},
+                promote::{PromoteKeyPayload, PromoteKeyResponse, promote_key},
+            },
+            role::{
+                create::{CreateRolePayload, CreateRoleResponse, create_role},
+                delete::{DeleteRoleResponse, delete_role},
+                get::{GetRoleResponse, get_role},
+                list::{ListRolesResponse, list_roles},
+                modify_grant::{
+                    ModifyRoleGrantPayload, ModifyRoleGrantResponse, modify_role_grant,
+                },
+                update::{UpdateRolePayload, UpdateRoleResponse, update_role},
             },
             user::{
                 create::{CreateUserPayload, CreateUserResponse, create_user},
                 delete::{DeleteUserResponse, delete_user},
                 get::{GetUserResponse, get_user},
+                impersonate::{ImpersonatePayload, ImpersonateResponse, impersonate},
+                impersonations::{ImpersonationsResponse, impersonations},
                 list::{ListUsersResponse, list_users},
+                login_history::{LoginHistoryResponse, login_history},
                 modify_grant::{ModifyGrantPayload, ModifyGrantResponse, modify_grant},
+                modify_role::{ModifyRolePayload, ModifyRoleResponse, modify_role},
+                reset_password::{ResetPasswordPayload, ResetPasswordResponse, reset_password},
+                revoke_sessions::{RevokeSessionsResponse, revoke_sessions},
+                unlock::{UnlockUserResponse, unlock_user},
+            },
+        },
+        oauth::{
+            authorize::{
+                ApproveAuthorizationPayload, ApproveAuthorizationResponse, AuthorizationRequest,
+                AuthorizeResponse, ConsentPromptResponse, approve_authorization, authorize,
+                consent_prompt,
+            },
+            basic_credentials,
+            device::{
+                DeviceAuthorizationRequest, DeviceAuthorizationResponse, DeviceDecisionPayload,
+                DeviceDecisionResponse, DevicePromptResponse, decide_device, device_authorization,
+                device_prompt,
             },
+            discovery::openid_configuration,
+            introspect::{IntrospectResponse, IntrospectionRequest, introspect},
+            revoke::{RevocationRequest, RevokeResponse, revoke},
+            token::{TokenRequest, TokenResponse, token},
+            userinfo::{UserInfoResponse, userinfo},
         },
     },
-    util::grants::{Grants, HasGrants},
+    util::{
+        grants::{Grants, HasGrants},
+        request::RequestContext,
+    },
 };
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
@@ -62,6 +187,25 @@ pub struct ApiRepositories {
     pub user: UserRepository,
     pub grant: GrantRepository,
     pub application: ApplicationRepository,
+    pub refresh_token: RefreshTokenRepository,
+    pub session: SessionRepository,
+    pub login_event: LoginEventRepository,
+    pub login_throttle: LoginThrottleRepository,
+    pub user_totp: UserTotpRepository,
+    pub recovery_code: RecoveryCodeRepository,
+    pub mfa_challenge: MfaChallengeRepository,
+    pub password_reset_token: PasswordResetTokenRepository,
+    pub email_verification_token: EmailVerificationTokenRepository,
+    pub authorization_code: AuthorizationCodeRepository,
+    pub consent: ConsentRepository,
+    pub personal_access_token: PersonalAccessTokenRepository,
+    pub device_code: DeviceCodeRepository,
+    pub user_identity: UserIdentityRepository,
+    pub federation_request: FederationRequestRepository,
+    pub impersonation_event: ImpersonationEventRepository,
+    pub role: RoleRepository,
+    pub group: GroupRepository,
+    pub key_promotion: KeyPromotionRepository,
 }
 impl ApiRepositories {
     pub async fn new(args: &Args, _build_info: &BuildInfo) -> Result<Self, ApiRepositoriesError> {
@@ -70,14 +214,76 @@ impl ApiRepositories {
             user: UserRepository::new(conn.clone()),
             grant: GrantRepository::new(conn.clone()),
             application: ApplicationRepository::new(conn.clone()),
+            refresh_token: RefreshTokenRepository::new(conn.clone()),
+            session: SessionRepository::new(conn.clone()),
+            login_event: LoginEventRepository::new(conn.clone()),
+            login_throttle: LoginThrottleRepository::new(conn.clone()),
+            user_totp: UserTotpRepository::new(conn.clone()),
+            recovery_code: RecoveryCodeRepository::new(conn.clone()),
+            mfa_challenge: MfaChallengeRepository::new(conn.clone()),
+            password_reset_token: PasswordResetTokenRepository::new(conn.clone()),
+            email_verification_token: EmailVerificationTokenRepository::new(conn.clone()),
+            authorization_code: AuthorizationCodeRepository::new(conn.clone()),
+            consent: ConsentRepository::new(conn.clone()),
+            personal_access_token: PersonalAccessTokenRepository::new(conn.clone()),
+            device_code: DeviceCodeRepository::new(conn.clone()),
+            user_identity: UserIdentityRepository::new(conn.clone()),
+            federation_request: FederationRequestRepository::new(conn.clone()),
+            impersonation_event: ImpersonationEventRepository::new(conn.clone()),
+            role: RoleRepository::new(conn.clone()),
+            group: GroupRepository::new(conn.clone()),
+            key_promotion: KeyPromotionRepository::new(conn.clone()),
         })
     }
 }
 
+/// A token issued to a user
 #[derive(SecurityScheme)]
 #[oai(ty = "bearer", checker = "BearerJwt::extract")]
-pub struct BearerJwt(Claims);
+pub struct BearerJwt(UserClaims);
 impl BearerJwt {
+    async fn extract(req: &&Request, from_request: Bearer) -> poem::Result<UserClaims> {
+        let claims = BearerPrincipal::extract(req, from_request).await?;
+
+        UserClaims::try_from(claims).map_err(|claims| {
+            tracing::error!(
+                "JWT verification failed: Client '{}' used a user endpoint",
+                claims.subject
+            );
+            poem::Error::new(io::Error::other("Unauthorized"), StatusCode::UNAUTHORIZED)
+        })
+    }
+}
+
+/// A token the user holds themselves, see `Claims::is_first_party`. Anything changing how the
+/// account is secured takes one, so a client the user delegated to can't take it over.
+#[derive(SecurityScheme)]
+#[oai(ty = "bearer", checker = "BearerSelf::extract")]
+pub struct BearerSelf(UserClaims);
+impl BearerSelf {
+    async fn extract(req: &&Request, from_request: Bearer) -> poem::Result<UserClaims> {
+        let claims = BearerJwt::extract(req, from_request).await?;
+
+        if !claims.is_first_party() {
+            tracing::error!(
+                "JWT verification failed: A delegated token for user '{}' used an account endpoint",
+                claims.user_id
+            );
+            return Err(poem::Error::new(
+                io::Error::other("Forbidden"),
+                StatusCode::FORBIDDEN,
+            ));
+        }
+
+        Ok(claims)
+    }
+}
+
+/// A token issued to a user, or to an application through client_credentials
+#[derive(SecurityScheme)]
+#[oai(ty = "bearer", checker = "BearerPrincipal::extract")]
+pub struct BearerPrincipal(Claims);
+impl BearerPrincipal {
     async fn extract(req: &&Request, from_request: Bearer) -> poem::Result<Claims> {
         let Some(services) = req.data::<ApiServices>() else {
             return Err(poem::Error::new(
@@ -85,12 +291,73 @@ impl BearerJwt {
                 StatusCode::INTERNAL_SERVER_ERROR,
             ));
         };
+        let Some(repositories) = req.data::<ApiRepositories>() else {
+            return Err(poem::Error::new(
+                io::Error::other("Failed to acquire repositories"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ));
+        };
+
+        let claims = Self::verify(req, services, repositories, from_request).await?;
+
+        // Expanded after any revalidation, which would otherwise drop the implied grants again
+        match services.implications.get(repositories).await {
+            Ok(implications) => Ok(claims.with_implied(&implications)),
+            Err(e) => {
+                tracing::error!("JWT verification failed: Failed to load grant implications: {e}");
+                Err(poem::Error::new(
+                    io::Error::other("Failed to load grant implications"),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                ))
+            }
+        }
+    }
+
+    async fn verify(
+        req: &Request,
+        services: &ApiServices,
+        repositories: &ApiRepositories,
+        from_request: Bearer,
+    ) -> poem::Result<Claims> {
+        if is_personal_access_token(&from_request.token) {
+            return match authenticate(repositories, services, &from_request.token).await {
+                Ok(Some(claims)) => Ok(claims),
+                Ok(None) => {
+                    tracing::error!(
+                        "Personal access token verification failed: Unknown, expired or revoked token"
+                    );
+                    Err(poem::Error::new(
+                        io::Error::other("Unauthorized"),
+                        StatusCode::UNAUTHORIZED,
+                    ))
+                }
+                Err(e) => {
+                    tracing::error!("Personal access token verification failed: {e}");
+                    Err(poem::Error::new(
+                        io::Error::other("Failed to check personal access token"),
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                    ))
+                }
+            };
+        }
 
         let claims = services.jwt.verify(&from_request.token).map_err(|e| {
             tracing::error!("JWT verification failed: {e}");
             poem::Error::new(io::Error::other("Unauthorized"), StatusCode::UNAUTHORIZED)
         })?;
 
+        // Impersonation is for seeing what the user sees, not for acting on their behalf
+        if claims.actor.is_some() && !matches!(*req.method(), Method::GET | Method::HEAD) {
+            tracing::error!(
+                "JWT verification failed: Impersonation token used for a {} request",
+                req.method()
+            );
+            return Err(poem::Error::new(
+                io::Error::other("Forbidden"),
+                StatusCode::FORBIDDEN,
+            ));
+        }
+
         if claims.issuer != crate::PRODUCT_IDENTIFIER {
             tracing::error!(
                 "JWT verification failed: Received invalid issuer '{}', expected '{}'",
@@ -129,10 +396,63 @@ impl BearerJwt {
             ));
         }
 
-        Ok(claims)
+        let revoked = match claims.subject_type {
+            SubjectType::User => {
+                is_session_revoked(repositories, services, &claims.session_id).await
+            }
+            // Client subjects have no session to revoke, their tokens are short lived instead
+            SubjectType::Client => Ok(false),
+        };
+
+        match revoked {
+            Ok(false) => {}
+            Ok(true) => {
+                tracing::error!(
+                    "JWT verification failed: Session '{}' has been revoked",
+                    claims.session_id
+                );
+                return Err(poem::Error::new(
+                    io::Error::other("Unauthorized"),
+                    StatusCode::UNAUTHORIZED,
+                ));
+            }
+            Err(e) => {
+                tracing::error!("JWT verification failed: Failed to check session: {e}");
+                return Err(poem::Error::new(
+                    io::Error::other("Failed to check session"),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                ));
+            }
+        }
+
+        if !services.revalidate_tokens {
+            return Ok(claims);
+        }
+
+        match revalidate(repositories, claims).await {
+            Ok(Some(claims)) => Ok(claims),
+            Ok(None) => {
+                tracing::error!("JWT verification failed: Subject is disabled or no longer exists");
+                Err(poem::Error::new(
+                    io::Error::other("Unauthorized"),
+                    StatusCode::UNAUTHORIZED,
+                ))
+            }
+            Err(e) => {
+                tracing::error!("JWT verification failed: Failed to revalidate user: {e}");
+                Err(poem::Error::new(
+                    io::Error::other("Failed to revalidate user"),
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                ))
+            }
+        }
     }
 }
 
+fn default_login_history_limit() -> u64 {
+    100
+}
+
 pub trait SwaggerApi {
     fn base_uri() -> String;
     fn name() -> String;
@@ -156,6 +476,21 @@ impl Api {
         PlainText("Healthy".into())
     }
 
+    #[oai(path = "/.well-known/jwks.json", method = "get")]
+    async fn jwks(&self, services: Data<&ApiServices>) -> poem::Result<Json<serde_json::Value>> {
+        serde_json::to_value(services.0.jwt.jwks())
+            .map_err(|e| poem::Error::new(e, StatusCode::INTERNAL_SERVER_ERROR))
+            .map(Json)
+    }
+
+    #[oai(path = "/.well-known/openid-configuration", method = "get")]
+    async fn openid_configuration(
+        &self,
+        services: Data<&ApiServices>,
+    ) -> Json<OpenIdConfiguration> {
+        Json(openid_configuration(&services.0))
+    }
+
     #[oai(path = "/me", method = "get")]
     async fn me(&self, repositories: Data<&ApiRepositories>, claims: BearerJwt) -> GetUserResponse {
         let user_id = claims.0.user_id;
@@ -165,11 +500,495 @@ impl Api {
     #[oai(path = "/login", method = "post")]
     async fn login(
         &self,
+        req: &Request,
         repositories: Data<&ApiRepositories>,
         services: Data<&ApiServices>,
         payload: Json<LoginPayload>,
     ) -> LoginResponse {
-        login(repositories.0.clone(), services.0.clone(), payload.0).await
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+
+        login(
+            repositories.0.clone(),
+            services.0.clone(),
+            payload.0,
+            context,
+        )
+        .await
+    }
+
+    /// Second step of logging in for users with TOTP enabled
+    #[oai(path = "/login/mfa", method = "post")]
+    async fn login_mfa(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Json<MfaLoginPayload>,
+    ) -> LoginResponse {
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+
+        login_mfa(
+            repositories.0.clone(),
+            services.0.clone(),
+            payload.0,
+            context,
+        )
+        .await
+    }
+
+    #[oai(path = "/federation/providers", method = "get")]
+    async fn federation_providers(
+        &self,
+        services: Data<&ApiServices>,
+    ) -> ListFederationProvidersResponse {
+        list_providers(services.0.clone()).await
+    }
+
+    /// Starts logging in through an upstream provider, send the user to the returned URL
+    #[oai(path = "/federation/:provider_id/authorize", method = "post")]
+    async fn federation_authorize(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        provider_id: Path<String>,
+    ) -> StartFederationResponse {
+        start_login(repositories.0.clone(), services.0.clone(), &provider_id.0).await
+    }
+
+    /// Called by the callback page with what the provider sent back when logging in
+    #[oai(path = "/federation/callback", method = "post")]
+    async fn federation_callback(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Json<FederationCallbackPayload>,
+    ) -> FederatedLoginResponse {
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+
+        federated_login(
+            repositories.0.clone(),
+            services.0.clone(),
+            payload.0,
+            context,
+        )
+        .await
+    }
+
+    #[oai(path = "/password/forgot", method = "post")]
+    async fn password_forgot(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Json<ForgotPasswordPayload>,
+    ) -> ForgotPasswordResponse {
+        forgot_password(repositories.0.clone(), services.0.clone(), payload.0).await
+    }
+
+    #[oai(path = "/password/reset", method = "post")]
+    async fn password_reset(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Json<CompletePasswordResetPayload>,
+    ) -> CompletePasswordResetResponse {
+        complete_password_reset(repositories.0.clone(), services.0.clone(), payload.0).await
+    }
+
+    #[oai(path = "/me/password", method = "post")]
+    async fn me_password(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        payload: Json<ChangePasswordPayload>,
+    ) -> ChangePasswordResponse {
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+
+        change_password(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            payload.0,
+            context,
+        )
+        .await
+    }
+
+    /// Mails a verification link to the current email
+    #[oai(path = "/me/email/verify", method = "post")]
+    async fn me_email_verify(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+    ) -> SendEmailVerificationResponse {
+        send_email_verification(repositories.0.clone(), services.0.clone(), claims.0.user_id).await
+    }
+
+    #[oai(path = "/email/verify", method = "post")]
+    async fn email_verify(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        payload: Json<VerifyEmailPayload>,
+    ) -> VerifyEmailResponse {
+        verify_email(repositories.0.clone(), payload.0).await
+    }
+
+    #[oai(path = "/me/tokens", method = "get")]
+    async fn me_tokens(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+    ) -> ListPersonalAccessTokensResponse {
+        list_personal_access_tokens(repositories.0.clone(), claims.0.user_id).await
+    }
+
+    #[oai(path = "/me/tokens", method = "post")]
+    async fn me_tokens_create(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerSelf,
+        payload: Json<CreatePersonalAccessTokenPayload>,
+    ) -> CreatePersonalAccessTokenResponse {
+        create_personal_access_token(repositories.0.clone(), claims.0, payload.0).await
+    }
+
+    #[oai(path = "/me/tokens/:personal_access_token_id", method = "delete")]
+    async fn me_tokens_revoke(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerSelf,
+        personal_access_token_id: Path<i32>,
+    ) -> RevokePersonalAccessTokenResponse {
+        let agent = &format!("auth.personal_access_token.revoke:{}", claims.0.user_id);
+
+        revoke_personal_access_token(
+            repositories.0.clone(),
+            claims.0.user_id,
+            personal_access_token_id.0,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/me/identities", method = "get")]
+    async fn me_identities(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+    ) -> ListIdentitiesResponse {
+        list_identities(repositories.0.clone(), claims.0.user_id).await
+    }
+
+    /// Starts linking an upstream account, send the user to the returned URL
+    #[oai(path = "/me/identities/:provider_id/authorize", method = "post")]
+    async fn me_identities_authorize(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        provider_id: Path<String>,
+    ) -> StartFederationResponse {
+        start_link(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            &provider_id.0,
+        )
+        .await
+    }
+
+    /// Called by the callback page with what the provider sent back when linking
+    #[oai(path = "/me/identities/callback", method = "post")]
+    async fn me_identities_callback(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        payload: Json<FederationCallbackPayload>,
+    ) -> LinkIdentityResponse {
+        link_identity(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            payload.0,
+        )
+        .await
+    }
+
+    #[oai(path = "/me/identities/:user_identity_id", method = "delete")]
+    async fn me_identities_unlink(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerSelf,
+        user_identity_id: Path<i32>,
+    ) -> UnlinkIdentityResponse {
+        unlink_identity(repositories.0.clone(), claims.0.user_id, user_identity_id.0).await
+    }
+
+    #[oai(path = "/me/mfa", method = "get")]
+    async fn me_mfa(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+    ) -> MfaStatusResponse {
+        mfa_status(repositories.0.clone(), claims.0.user_id).await
+    }
+
+    #[oai(path = "/me/mfa/totp", method = "post")]
+    async fn me_totp_enroll(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+    ) -> EnrollTotpResponse {
+        let agent = &format!("auth.mfa.enroll:{}", claims.0.user_id);
+
+        enroll_totp(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0.user_id,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/me/mfa/totp/confirm", method = "post")]
+    async fn me_totp_confirm(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        payload: Json<TotpCodePayload>,
+    ) -> ConfirmTotpResponse {
+        let agent = &format!("auth.mfa.confirm:{}", claims.0.user_id);
+
+        confirm_totp(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0.user_id,
+            payload.0,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/me/mfa/totp", method = "delete")]
+    async fn me_totp_disable(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        payload: Json<TotpCodePayload>,
+    ) -> DisableTotpResponse {
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+        let agent = &format!("auth.mfa.disable:{}", claims.0.user_id);
+
+        disable_totp(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            payload.0,
+            context,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/me/mfa/recovery-codes", method = "post")]
+    async fn me_recovery_codes(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+        payload: Json<TotpCodePayload>,
+    ) -> RegenerateRecoveryCodesResponse {
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+        let agent = &format!("auth.mfa.recovery_codes:{}", claims.0.user_id);
+
+        regenerate_recovery_codes(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0.user_id,
+            payload.0,
+            context,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/refresh", method = "post")]
+    async fn refresh(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Json<RefreshPayload>,
+    ) -> RefreshResponse {
+        refresh(repositories.0.clone(), services.0.clone(), payload.0).await
+    }
+
+    #[oai(path = "/logout", method = "post")]
+    async fn logout(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerJwt,
+    ) -> LogoutResponse {
+        logout(repositories.0.clone(), services.0.clone(), claims.0).await
+    }
+
+    #[oai(path = "/logout/all", method = "post")]
+    async fn logout_all(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerSelf,
+    ) -> LogoutAllResponse {
+        logout_all(repositories.0.clone(), services.0.clone(), claims.0).await
+    }
+
+    #[oai(path = "/oauth/authorize", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn oauth_authorize(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        response_type: Query<String>,
+        client_id: Query<String>,
+        redirect_uri: Query<Option<String>>,
+        state: Query<Option<String>>,
+        code_challenge: Query<Option<String>>,
+        code_challenge_method: Query<Option<String>>,
+        scope: Query<Option<String>>,
+        nonce: Query<Option<String>>,
+    ) -> AuthorizeResponse {
+        authorize(
+            repositories.0.clone(),
+            services.0.clone(),
+            AuthorizationRequest {
+                response_type: response_type.0,
+                client_id: client_id.0,
+                redirect_uri: redirect_uri.0,
+                state: state.0,
+                code_challenge: code_challenge.0,
+                code_challenge_method: code_challenge_method.0,
+                scope: scope.0,
+                nonce: nonce.0,
+            },
+        )
+        .await
+    }
+
+    #[oai(path = "/oauth/authorize", method = "post")]
+    async fn oauth_approve(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerJwt,
+        payload: Json<ApproveAuthorizationPayload>,
+    ) -> ApproveAuthorizationResponse {
+        approve_authorization(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            payload.0,
+        )
+        .await
+    }
+
+    #[oai(path = "/oauth/authorize/client", method = "get")]
+    async fn oauth_consent_prompt(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+        client_id: Query<String>,
+    ) -> ConsentPromptResponse {
+        consent_prompt(repositories.0.clone(), claims.0, client_id.0).await
+    }
+
+    #[oai(path = "/oauth/token", method = "post")]
+    async fn oauth_token(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Form<TokenRequest>,
+    ) -> TokenResponse {
+        let basic = req.header("authorization").and_then(basic_credentials);
+
+        token(repositories.0.clone(), services.0.clone(), payload.0, basic).await
+    }
+
+    #[oai(path = "/oauth/device_authorization", method = "post")]
+    async fn oauth_device_authorization(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Form<DeviceAuthorizationRequest>,
+    ) -> DeviceAuthorizationResponse {
+        let basic = req.header("authorization").and_then(basic_credentials);
+
+        device_authorization(repositories.0.clone(), services.0.clone(), payload.0, basic).await
+    }
+
+    #[oai(path = "/oauth/device", method = "get")]
+    async fn oauth_device_prompt(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+        user_code: Query<String>,
+    ) -> DevicePromptResponse {
+        device_prompt(repositories.0.clone(), claims.0, user_code.0).await
+    }
+
+    #[oai(path = "/oauth/device", method = "post")]
+    async fn oauth_device_decide(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+        payload: Json<DeviceDecisionPayload>,
+    ) -> DeviceDecisionResponse {
+        decide_device(repositories.0.clone(), claims.0, payload.0).await
+    }
+
+    #[oai(path = "/oauth/introspect", method = "post")]
+    async fn oauth_introspect(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Form<IntrospectionRequest>,
+    ) -> IntrospectResponse {
+        let basic = req.header("authorization").and_then(basic_credentials);
+
+        introspect(repositories.0.clone(), services.0.clone(), payload.0, basic).await
+    }
+
+    #[oai(path = "/oauth/revoke", method = "post")]
+    async fn oauth_revoke(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        payload: Form<RevocationRequest>,
+    ) -> RevokeResponse {
+        let basic = req.header("authorization").and_then(basic_credentials);
+
+        revoke(repositories.0.clone(), services.0.clone(), payload.0, basic).await
+    }
+
+    #[oai(path = "/userinfo", method = "get")]
+    async fn userinfo(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerJwt,
+    ) -> UserInfoResponse {
+        userinfo(repositories.0.clone(), claims.0).await
     }
 }
 
@@ -189,6 +1008,9 @@ pub enum ManageTags {
     User,
     Application,
     Grant,
+    Role,
+    Group,
+    Key,
 }
 
 #[OpenApi]
@@ -198,14 +1020,14 @@ impl ManageApi {
         &self,
         repositories: Data<&ApiRepositories>,
         services: Data<&ApiServices>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         payload: Json<CreateUserPayload>,
     ) -> CreateUserResponse {
         if !claims.0.has_grants(&[Grants::UserCreate]) {
             return CreateUserResponse::Unauthorized;
         }
 
-        let agent = &format!("user.create:{}", claims.0.user_id);
+        let agent = &format!("user.create:{}", claims.0.subject);
 
         create_user(repositories.0.clone(), services.0.clone(), payload.0, agent).await
     }
@@ -214,7 +1036,7 @@ impl ManageApi {
     async fn user_delete(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         user_id: Path<i32>,
     ) -> DeleteUserResponse {
         if !claims.0.has_grants(&[Grants::UserDelete]) {
@@ -228,7 +1050,7 @@ impl ManageApi {
     async fn user_list(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         enabled: Query<Option<bool>>,
     ) -> ListUsersResponse {
         if !claims.0.has_grants(&[Grants::UserList]) {
@@ -242,7 +1064,7 @@ impl ManageApi {
     async fn user_get(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         user_id: Path<i32>,
     ) -> GetUserResponse {
         if !claims.0.has_grants(&[Grants::UserGet]) {
@@ -256,7 +1078,7 @@ impl ManageApi {
     async fn user_update_grant(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         payload: Json<ModifyGrantPayload>,
     ) -> ModifyGrantResponse {
         if !claims.0.has_grants(&[Grants::UserGrantUpdate]) {
@@ -265,24 +1087,178 @@ impl ManageApi {
 
         let agent = &format!(
             "user.modify_grant:{}:{}",
-            claims.0.user_id, payload.0.grant_id
+            claims.0.subject, payload.0.grant_id
         );
 
         modify_grant(repositories.0.clone(), payload.0, agent).await
     }
 
+    #[oai(path = "/user/roles", method = "put", tag = ManageTags::User, tag = ManageTags::Role)]
+    async fn user_update_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyRolePayload>,
+    ) -> ModifyRoleResponse {
+        if !claims.0.has_grants(&[Grants::UserRoleUpdate]) {
+            return ModifyRoleResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "user.modify_role:{}:{}",
+            claims.0.subject, payload.0.role_id
+        );
+
+        modify_role(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/user/:user_id/logins", method = "get", tag = ManageTags::User)]
+    async fn user_login_history(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+        outcome: Query<Option<LoginOutcome>>,
+        from: Query<Option<DateTime<Utc>>>,
+        until: Query<Option<DateTime<Utc>>>,
+        #[oai(
+            default = "default_login_history_limit",
+            validator(maximum(value = "1000"))
+        )]
+        limit: Query<u64>,
+    ) -> LoginHistoryResponse {
+        if !claims.0.has_grants(&[Grants::UserLoginList]) {
+            return LoginHistoryResponse::Unauthorized;
+        }
+
+        login_history(
+            repositories.0.clone(),
+            user_id.0,
+            outcome.0,
+            from.0,
+            until.0,
+            limit.0,
+        )
+        .await
+    }
+
+    /// Mints a short lived, read only token for the user, recorded in their impersonation history
+    #[oai(path = "/user/:user_id/impersonate", method = "post", tag = ManageTags::User)]
+    async fn user_impersonate(
+        &self,
+        req: &Request,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+        payload: Json<ImpersonatePayload>,
+    ) -> ImpersonateResponse {
+        if !claims.0.has_grants(&[Grants::UserImpersonate]) {
+            return ImpersonateResponse::Unauthorized;
+        }
+
+        let context = RequestContext::from_request(req, services.trust_forwarded_for);
+
+        impersonate(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            user_id.0,
+            payload.0,
+            context,
+        )
+        .await
+    }
+
+    #[oai(path = "/user/:user_id/impersonations", method = "get", tag = ManageTags::User)]
+    async fn user_impersonations(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+        #[oai(
+            default = "default_login_history_limit",
+            validator(maximum(value = "1000"))
+        )]
+        limit: Query<u64>,
+    ) -> ImpersonationsResponse {
+        if !claims.0.has_grants(&[Grants::UserImpersonationList]) {
+            return ImpersonationsResponse::Unauthorized;
+        }
+
+        impersonations(repositories.0.clone(), user_id.0, limit.0).await
+    }
+
+    #[oai(path = "/user/:user_id/sessions", method = "delete", tag = ManageTags::User)]
+    async fn user_revoke_sessions(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+    ) -> RevokeSessionsResponse {
+        if !claims.0.has_grants(&[Grants::UserSessionRevoke]) {
+            return RevokeSessionsResponse::Unauthorized;
+        }
+
+        let agent = &format!("user.revoke_sessions:{}", claims.0.subject);
+
+        revoke_sessions(repositories.0.clone(), services.0.clone(), user_id.0, agent).await
+    }
+
+    #[oai(path = "/user/:user_id/password", method = "post", tag = ManageTags::User)]
+    async fn user_reset_password(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+        payload: Json<ResetPasswordPayload>,
+    ) -> ResetPasswordResponse {
+        if !claims.0.has_grants(&[Grants::UserPasswordReset]) {
+            return ResetPasswordResponse::Unauthorized;
+        }
+
+        let agent = &format!("user.reset_password:{}", claims.0.subject);
+
+        reset_password(
+            repositories.0.clone(),
+            services.0.clone(),
+            claims.0,
+            user_id.0,
+            payload.0,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/user/:user_id/lockout", method = "delete", tag = ManageTags::User)]
+    async fn user_unlock(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        user_id: Path<i32>,
+    ) -> UnlockUserResponse {
+        if !claims.0.has_grants(&[Grants::UserUnlock]) {
+            return UnlockUserResponse::Unauthorized;
+        }
+
+        unlock_user(repositories.0.clone(), services.0.clone(), user_id.0).await
+    }
+
     #[oai(path = "/application", method = "post", tag = ManageTags::Application)]
     async fn create_application(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         payload: Json<CreateApplicationPayload>,
     ) -> CreateApplicationResponse {
         if !claims.0.has_grants(&[Grants::ApplicationCreate]) {
             return CreateApplicationResponse::Unauthorized;
         }
 
-        let agent = &format!("application.create:{}", claims.0.user_id);
+        let agent = &format!("application.create:{}", claims.0.subject);
 
         create_application(repositories.0.clone(), payload.0, &agent).await
     }
@@ -291,7 +1267,7 @@ impl ManageApi {
     async fn get_application(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         application_id: Path<String>,
     ) -> GetApplicationResponse {
         if !claims.0.has_grants(&[Grants::ApplicationGet]) {
@@ -305,7 +1281,7 @@ impl ManageApi {
     async fn list_applications(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
     ) -> ListApplicationsResponse {
         if !claims.0.has_grants(&[Grants::ApplicationList]) {
             return ListApplicationsResponse::Unauthorized;
@@ -318,22 +1294,78 @@ impl ManageApi {
     async fn update_application(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         payload: Json<UpdateApplicationPayload>,
     ) -> UpdateApplicationResponse {
         if !claims.0.has_grants(&[Grants::ApplicationUpdate]) {
             return UpdateApplicationResponse::Unauthorized;
         }
-        let agent = &format!("application.update:{}", claims.0.user_id);
+        let agent = &format!("application.update:{}", claims.0.subject);
 
         update_application(repositories.0.clone(), payload.0.clone(), &agent).await
     }
 
+    #[oai(path = "/application/:application_id/secret", method = "post", tag = ManageTags::Application)]
+    async fn application_rotate_secret(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        application_id: Path<String>,
+    ) -> RotateSecretResponse {
+        if !claims.0.has_grants(&[Grants::ApplicationSecretRotate]) {
+            return RotateSecretResponse::Unauthorized;
+        }
+        let agent = &format!("application.rotate_secret:{}", claims.0.subject);
+
+        rotate_secret(
+            repositories.0.clone(),
+            services.0.clone(),
+            application_id.0,
+            agent,
+        )
+        .await
+    }
+
+    #[oai(path = "/application/:application_id/secret", method = "delete", tag = ManageTags::Application)]
+    async fn application_delete_secret(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        application_id: Path<String>,
+    ) -> DeleteSecretResponse {
+        if !claims.0.has_grants(&[Grants::ApplicationSecretRotate]) {
+            return DeleteSecretResponse::Unauthorized;
+        }
+        let agent = &format!("application.delete_secret:{}", claims.0.subject);
+
+        delete_secret(repositories.0.clone(), application_id.0, agent).await
+    }
+
+    #[oai(path = "/application/grants", method = "put", tag = ManageTags::Application, tag = ManageTags::Grant)]
+    async fn application_update_grant(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyClientGrantPayload>,
+    ) -> ModifyClientGrantResponse {
+        if !claims.0.has_grants(&[Grants::ApplicationGrantUpdate]) {
+            return ModifyClientGrantResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "application.modify_grant:{}:{}",
+            claims.0.subject, payload.0.grant_id
+        );
+
+        modify_client_grant(repositories.0.clone(), payload.0, agent).await
+    }
+
     #[oai(path = "/application/:application_id/grants", method = "get", tag = ManageTags::Application, tag = ManageTags::Grant)]
     async fn get_grants_by_application_id(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         application_id: Path<String>,
     ) -> GetGrantByApplicationIdResponse {
         if !claims.0.has_grants(&[Grants::ApplicationGetGrants]) {
@@ -347,14 +1379,14 @@ impl ManageApi {
     async fn create_grant(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         payload: Json<CreateGrantPayload>,
     ) -> CreateGrantResponse {
         if !claims.0.has_grants(&[Grants::GrantCreate]) {
             return CreateGrantResponse::Unauthorized;
         }
 
-        let agent = &format!("grant.create:{}", claims.0.user_id);
+        let agent = &format!("grant.create:{}", claims.0.subject);
 
         create_grant(repositories.0.clone(), payload.0, &agent).await
     }
@@ -363,7 +1395,7 @@ impl ManageApi {
     async fn get_grant_by_id(
         &self,
         repositories: Data<&ApiRepositories>,
-        claims: BearerJwt,
+        claims: BearerPrincipal,
         grant_id: Path<String>,
     ) -> GetGrantByIdResponse {
         if !claims.0.has_grants(&[Grants::GrantGet]) {
@@ -372,6 +1404,293 @@ impl ManageApi {
 
         get_grant_by_id(repositories.0.clone(), &grant_id).await
     }
+
+    #[oai(path = "/grant/:grant_id/implications", method = "get", tag = ManageTags::Grant)]
+    async fn list_grant_implications(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        grant_id: Path<String>,
+    ) -> ListGrantImplicationsResponse {
+        if !claims.0.has_grants(&[Grants::GrantGet]) {
+            return ListGrantImplicationsResponse::Unauthorized;
+        }
+
+        list_grant_implications(repositories.0.clone(), &grant_id).await
+    }
+
+    #[oai(path = "/grant/implications", method = "put", tag = ManageTags::Grant)]
+    async fn grant_update_implication(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyGrantImplicationPayload>,
+    ) -> ModifyGrantImplicationResponse {
+        if !claims.0.has_grants(&[Grants::GrantImplicationUpdate]) {
+            return ModifyGrantImplicationResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "grant.modify_implication:{}:{}",
+            claims.0.subject, payload.0.implied_grant_id
+        );
+
+        modify_grant_implication(repositories.0.clone(), services.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/role", method = "post", tag = ManageTags::Role)]
+    async fn create_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<CreateRolePayload>,
+    ) -> CreateRoleResponse {
+        if !claims.0.has_grants(&[Grants::RoleCreate]) {
+            return CreateRoleResponse::Unauthorized;
+        }
+
+        let agent = &format!("role.create:{}", claims.0.subject);
+
+        create_role(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/role/:role_id", method = "get", tag = ManageTags::Role)]
+    async fn get_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        role_id: Path<String>,
+    ) -> GetRoleResponse {
+        if !claims.0.has_grants(&[Grants::RoleGet]) {
+            return GetRoleResponse::Unauthorized;
+        }
+
+        get_role(repositories.0.clone(), &role_id).await
+    }
+
+    #[oai(path = "/application/:application_id/roles", method = "get", tag = ManageTags::Application, tag = ManageTags::Role)]
+    async fn list_roles(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        application_id: Path<String>,
+    ) -> ListRolesResponse {
+        if !claims.0.has_grants(&[Grants::RoleList]) {
+            return ListRolesResponse::Unauthorized;
+        }
+
+        list_roles(repositories.0.clone(), &application_id).await
+    }
+
+    #[oai(path = "/role", method = "put", tag = ManageTags::Role)]
+    async fn update_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<UpdateRolePayload>,
+    ) -> UpdateRoleResponse {
+        if !claims.0.has_grants(&[Grants::RoleUpdate]) {
+            return UpdateRoleResponse::Unauthorized;
+        }
+
+        let agent = &format!("role.update:{}", claims.0.subject);
+
+        update_role(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/role/:role_id", method = "delete", tag = ManageTags::Role)]
+    async fn delete_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        role_id: Path<String>,
+    ) -> DeleteRoleResponse {
+        if !claims.0.has_grants(&[Grants::RoleDelete]) {
+            return DeleteRoleResponse::Unauthorized;
+        }
+
+        delete_role(repositories.0.clone(), &role_id).await
+    }
+
+    #[oai(path = "/role/grants", method = "put", tag = ManageTags::Role, tag = ManageTags::Grant)]
+    async fn role_update_grant(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyRoleGrantPayload>,
+    ) -> ModifyRoleGrantResponse {
+        if !claims.0.has_grants(&[Grants::RoleGrantUpdate]) {
+            return ModifyRoleGrantResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "role.modify_grant:{}:{}",
+            claims.0.subject, payload.0.grant_id
+        );
+
+        modify_role_grant(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/group", method = "post", tag = ManageTags::Group)]
+    async fn create_group(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<CreateGroupPayload>,
+    ) -> CreateGroupResponse {
+        if !claims.0.has_grants(&[Grants::GroupCreate]) {
+            return CreateGroupResponse::Unauthorized;
+        }
+
+        let agent = &format!("group.create:{}", claims.0.subject);
+
+        create_group(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/group", method = "get", tag = ManageTags::Group)]
+    async fn list_groups(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+    ) -> ListGroupsResponse {
+        if !claims.0.has_grants(&[Grants::GroupList]) {
+            return ListGroupsResponse::Unauthorized;
+        }
+
+        list_groups(repositories.0.clone()).await
+    }
+
+    #[oai(path = "/group/:group_id", method = "get", tag = ManageTags::Group)]
+    async fn get_group(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        group_id: Path<String>,
+    ) -> GetGroupResponse {
+        if !claims.0.has_grants(&[Grants::GroupGet]) {
+            return GetGroupResponse::Unauthorized;
+        }
+
+        get_group(repositories.0.clone(), &group_id).await
+    }
+
+    #[oai(path = "/group", method = "put", tag = ManageTags::Group)]
+    async fn update_group(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<UpdateGroupPayload>,
+    ) -> UpdateGroupResponse {
+        if !claims.0.has_grants(&[Grants::GroupUpdate]) {
+            return UpdateGroupResponse::Unauthorized;
+        }
+
+        let agent = &format!("group.update:{}", claims.0.subject);
+
+        update_group(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/group/:group_id", method = "delete", tag = ManageTags::Group)]
+    async fn delete_group(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        group_id: Path<String>,
+    ) -> DeleteGroupResponse {
+        if !claims.0.has_grants(&[Grants::GroupDelete]) {
+            return DeleteGroupResponse::Unauthorized;
+        }
+
+        delete_group(repositories.0.clone(), &group_id).await
+    }
+
+    #[oai(path = "/group/grants", method = "put", tag = ManageTags::Group, tag = ManageTags::Grant)]
+    async fn group_update_grant(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyGroupGrantPayload>,
+    ) -> ModifyGroupGrantResponse {
+        if !claims.0.has_grants(&[Grants::GroupGrantUpdate]) {
+            return ModifyGroupGrantResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "group.modify_grant:{}:{}",
+            claims.0.subject, payload.0.grant_id
+        );
+
+        modify_group_grant(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/group/roles", method = "put", tag = ManageTags::Group, tag = ManageTags::Role)]
+    async fn group_update_role(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyGroupRolePayload>,
+    ) -> ModifyGroupRoleResponse {
+        if !claims.0.has_grants(&[Grants::GroupRoleUpdate]) {
+            return ModifyGroupRoleResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "group.modify_role:{}:{}",
+            claims.0.subject, payload.0.role_id
+        );
+
+        modify_group_role(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/group/members", method = "put", tag = ManageTags::Group, tag = ManageTags::User)]
+    async fn group_update_member(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        claims: BearerPrincipal,
+        payload: Json<ModifyGroupMemberPayload>,
+    ) -> ModifyGroupMemberResponse {
+        if !claims.0.has_grants(&[Grants::GroupMemberUpdate]) {
+            return ModifyGroupMemberResponse::Unauthorized;
+        }
+
+        let agent = &format!(
+            "group.modify_member:{}:{}",
+            claims.0.subject, payload.0.group_id
+        );
+
+        modify_group_member(repositories.0.clone(), payload.0, agent).await
+    }
+
+    #[oai(path = "/key", method = "get", tag = ManageTags::Key)]
+    async fn list_keys(
+        &self,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+    ) -> ListKeysResponse {
+        if !claims.0.has_grants(&[Grants::KeyList]) {
+            return ListKeysResponse::Unauthorized;
+        }
+
+        list_keys(services.0.clone()).await
+    }
+
+    #[oai(path = "/key/promote", method = "post", tag = ManageTags::Key)]
+    async fn promote_key(
+        &self,
+        repositories: Data<&ApiRepositories>,
+        services: Data<&ApiServices>,
+        claims: BearerPrincipal,
+        payload: Json<PromoteKeyPayload>,
+    ) -> PromoteKeyResponse {
+        if !claims.0.has_grants(&[Grants::KeyPromote]) {
+            return PromoteKeyResponse::Unauthorized;
+        }
+
+        let agent = &format!("key.promote:{}", claims.0.subject);
+
+        promote_key(repositories.0.clone(), services.0.clone(), payload.0, agent).await
+    }
 }
 
 #[derive(Clone)]
@@ -392,7 +1711,8 @@ impl DebugApi {
             .0
             .jwt
             .sign(&Claims {
-                user_id: 1,
+                subject: "1".into(),
+                subject_type: SubjectType::User,
                 issuer: "ME!".into(),
                 grants: vec![
                     "dev.thmsn.auth.user.create".to_string(),
@@ -411,6 +1731,14 @@ impl DebugApi {
                 apps: vec![],
                 issued_at: Utc::now().timestamp() as u64,
                 expires: (Utc::now() + chrono::Duration::minutes(30)).timestamp() as u64,
+                token_id: token::generate(),
+                session_id: "debug".into(),
+                amr: vec![],
+                email_verified: false,
+                client_id: None,
+                scope: None,
+                personal_access_token_id: None,
+                actor: None,
             })
             .map_err(|e| poem::Error::new(e, StatusCode::INTERNAL_SERVER_ERROR))
             .map(|jwt| PlainText(jwt))
@@ -418,6 +1746,6 @@ impl DebugApi {
 
     #[oai(path = "/jwt", method = "get")]
     async fn claims(&self, jwt: BearerJwt) -> Json<Claims> {
-        Json(jwt.0)
+        Json(jwt.0.claims)
     }
 }
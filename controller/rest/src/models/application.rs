@@ -1,8 +1,20 @@
 use chrono::{DateTime, Utc};
 use data::dto::application::{ApplicationDetailDto, ApplicationDto};
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
+use strum::{Display, EnumString};
 
-use crate::models::application_grant::ApplicationGrant;
+use crate::models::{application_grant::ApplicationGrant, client_grant::ClientGrant};
+
+/// How the application authenticates as an OAuth client
+#[derive(Enum, Display, EnumString, Debug, Clone, Copy, PartialEq, Eq)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ClientType {
+    /// Runs somewhere it can't keep a secret, such as a browser or a desktop app
+    Public,
+    /// Runs on a server and can keep a secret
+    Confidential,
+}
 
 #[derive(Object, Debug)]
 pub struct Application {
@@ -13,8 +25,13 @@ pub struct Application {
     pub updated_by: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub client_type: String,
+    /// Whether a client secret is set, the secret itself is only shown when generated
+    pub has_client_secret: bool,
 
     pub grants: Vec<ApplicationGrant>,
+    pub redirect_uris: Vec<String>,
+    pub client_grants: Vec<ClientGrant>,
 }
 impl From<ApplicationDetailDto> for Application {
     fn from(application: ApplicationDetailDto) -> Self {
@@ -32,6 +49,24 @@ impl From<ApplicationDetailDto> for Application {
                 updated_at: grant.updated_at,
             })
             .collect();
+        this.redirect_uris = application.redirect_uris;
+        this.client_grants = application
+            .client_grants
+            .into_iter()
+            .map(|cg| ClientGrant {
+                grant_id: cg.grant.grant_id,
+                application_id: cg.grant.application_id,
+                display_name: cg.grant.display_name,
+                description: cg.grant.description,
+                enabled: cg.client_grant.enabled,
+                enabled_at: cg.client_grant.enabled_at,
+                disabled_at: cg.client_grant.disabled_at,
+                created_by: cg.client_grant.created_by,
+                updated_by: cg.client_grant.updated_by,
+                created_at: cg.client_grant.created_at,
+                updated_at: cg.client_grant.updated_at,
+            })
+            .collect();
 
         this
     }
@@ -46,7 +81,11 @@ impl From<ApplicationDto> for Application {
             updated_by: application.updated_by,
             created_at: application.created_at,
             updated_at: application.updated_at,
+            client_type: application.client_type,
+            has_client_secret: application.client_secret.is_some(),
             grants: vec![],
+            redirect_uris: vec![],
+            client_grants: vec![],
         }
     }
 }
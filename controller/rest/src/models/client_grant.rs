@@ -0,0 +1,21 @@
+use chrono::Utc;
+use poem_openapi::Object;
+
+/// A grant held by an application's service principal
+#[derive(Object, Debug)]
+pub struct ClientGrant {
+    pub grant_id: String,
+    /// The application the grant belongs to, not necessarily the one holding it
+    pub application_id: String,
+    pub display_name: String,
+    pub description: String,
+
+    pub enabled: bool,
+    pub enabled_at: Option<chrono::DateTime<Utc>>,
+    pub disabled_at: Option<chrono::DateTime<Utc>>,
+    // NOTE: these fields refer to the application <-> grant relation, NOT the grant itself
+    pub created_by: String,
+    pub updated_by: String,
+    pub created_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
@@ -0,0 +1,51 @@
+use chrono::Utc;
+use data::dto::user_identity::UserIdentityDto;
+use poem_openapi::Object;
+
+use crate::services::core::upstream::UpstreamProvider;
+
+/// An upstream OpenID Connect provider users may log in through
+#[derive(Object, Debug)]
+pub struct FederationProvider {
+    pub provider_id: String,
+    pub display_name: String,
+}
+impl From<&UpstreamProvider> for FederationProvider {
+    fn from(provider: &UpstreamProvider) -> Self {
+        Self {
+            provider_id: provider.provider_id.clone(),
+            display_name: provider.display_name.clone(),
+        }
+    }
+}
+
+/// Where to send the user to log in at the provider, it redirects back to the callback page
+#[derive(Object, Debug)]
+pub struct FederationRedirect {
+    pub authorization_url: String,
+    pub expires_at: chrono::DateTime<Utc>,
+}
+
+/// An upstream account linked to the user
+#[derive(Object, Debug)]
+pub struct UserIdentity {
+    pub user_identity_id: i32,
+    pub provider_id: String,
+    /// The provider's `sub`
+    pub subject: String,
+    pub email: Option<String>,
+    pub last_login_at: Option<chrono::DateTime<Utc>>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+impl From<UserIdentityDto> for UserIdentity {
+    fn from(identity: UserIdentityDto) -> Self {
+        Self {
+            user_identity_id: identity.user_identity_id,
+            provider_id: identity.provider_id,
+            subject: identity.subject,
+            email: identity.email,
+            last_login_at: identity.last_login_at,
+            created_at: identity.created_at,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::Utc;
+use data::dto::grant_implication::GrantImplicationDetailDto;
+use poem_openapi::Object;
+
+use crate::models::grant::Grant;
+
+#[derive(Object, Debug)]
+pub struct GrantImplication {
+    pub grant_id: String,
+    /// The grant anyone holding `grant_id` holds as well
+    pub implied: Grant,
+
+    // NOTE: these fields refer to the implication, NOT the implied grant
+    pub created_by: String,
+    pub created_at: chrono::DateTime<Utc>,
+}
+impl From<GrantImplicationDetailDto> for GrantImplication {
+    fn from(value: GrantImplicationDetailDto) -> Self {
+        Self {
+            grant_id: value.implication.grant_id,
+            implied: Grant::from(value.implied),
+            created_by: value.implication.created_by,
+            created_at: value.implication.created_at,
+        }
+    }
+}
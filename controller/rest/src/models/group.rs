@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use data::dto::{
+    group::{GroupDetailDto, GroupDto},
+    group_member::GroupMemberDto,
+};
+use poem_openapi::Object;
+
+use crate::models::{grant::Grant, role::Role};
+
+/// A set of users and nested groups sharing grants and roles
+#[derive(Object, Debug)]
+pub struct Group {
+    pub group_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub created_by: String,
+    pub updated_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub grants: Vec<Grant>,
+    pub roles: Vec<Role>,
+    /// Only listed when the group itself is requested
+    pub members: Vec<GroupMember>,
+}
+impl From<GroupDetailDto> for Group {
+    fn from(value: GroupDetailDto) -> Self {
+        let mut this = Self::from(value.group);
+        this.grants = value.grants.into_iter().map(Grant::from).collect();
+        this.roles = value.roles.into_iter().map(Role::from).collect();
+
+        this
+    }
+}
+impl From<GroupDto> for Group {
+    fn from(value: GroupDto) -> Self {
+        Self {
+            group_id: value.group_id,
+            display_name: value.display_name,
+            description: value.description,
+            created_by: value.created_by,
+            updated_by: value.updated_by,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            grants: vec![],
+            roles: vec![],
+            members: vec![],
+        }
+    }
+}
+
+/// A direct member of a group, either a user or a nested group
+#[derive(Object, Debug)]
+pub struct GroupMember {
+    pub user_id: Option<i32>,
+    pub member_group_id: Option<String>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+}
+impl From<GroupMemberDto> for GroupMember {
+    fn from(value: GroupMemberDto) -> Self {
+        Self {
+            user_id: value.user_id,
+            member_group_id: value.member_group_id,
+            created_by: value.created_by,
+            created_at: value.created_at,
+        }
+    }
+}
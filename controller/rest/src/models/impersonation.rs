@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use data::dto::impersonation_event::ImpersonationEventDto;
+use poem_openapi::Object;
+
+use crate::services::core::jwt::Claims;
+
+/// A read only access token for the impersonated user, there is no refresh token
+#[derive(Object, Debug)]
+pub struct ImpersonationToken {
+    pub token: String,
+    pub claims: Claims,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Object, Debug)]
+pub struct ImpersonationEvent {
+    pub impersonation_event_id: i32,
+    pub user_id: i32,
+    /// `None` once the actor has been deleted
+    pub actor_user_id: Option<i32>,
+    pub actor_username: String,
+    /// End it with `DELETE /manage/user/{user_id}/sessions`, along with the user's own sessions
+    pub session_id: String,
+    pub reason: String,
+    pub expires_at: DateTime<Utc>,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<ImpersonationEventDto> for ImpersonationEvent {
+    fn from(value: ImpersonationEventDto) -> Self {
+        Self {
+            impersonation_event_id: value.impersonation_event_id,
+            user_id: value.user_id,
+            actor_user_id: value.actor_user_id,
+            actor_username: value.actor_username,
+            session_id: value.session_id,
+            reason: value.reason,
+            expires_at: value.expires_at,
+            source_ip: value.source_ip,
+            user_agent: value.user_agent,
+            created_at: value.created_at,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use data::dto::login_event::LoginEventDto;
+use poem_openapi::{Enum, Object};
+use strum::{Display, EnumString};
+
+#[derive(Enum, Display, EnumString, Debug, Clone, Copy, PartialEq, Eq)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LoginOutcome {
+    Success,
+    InvalidCredentials,
+    Disabled,
+    Locked,
+    MfaRequired,
+    PasswordChangeRequired,
+    InvalidMfaCode,
+}
+
+#[derive(Object, Debug)]
+pub struct LoginEvent {
+    pub login_event_id: i32,
+    pub user_id: Option<i32>,
+    pub username: String,
+    pub outcome: String,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+impl From<LoginEventDto> for LoginEvent {
+    fn from(value: LoginEventDto) -> Self {
+        Self {
+            login_event_id: value.login_event_id,
+            user_id: value.user_id,
+            username: value.username,
+            outcome: value.outcome,
+            source_ip: value.source_ip,
+            user_agent: value.user_agent,
+            created_at: value.created_at,
+        }
+    }
+}
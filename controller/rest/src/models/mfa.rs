@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use poem_openapi::Object;
+
+/// Returned instead of tokens when the password was correct but a second factor is required
+#[derive(Object, Debug)]
+pub struct MfaChallenge {
+    /// Exchanged along with a code at `/login/mfa`
+    pub challenge_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Object, Debug)]
+pub struct TotpEnrollment {
+    /// Base32 encoded, for authenticator apps that can't scan `otpauth_uri`
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Only ever shown once, each code can be used in place of a TOTP code a single time
+#[derive(Object, Debug)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Object, Debug)]
+pub struct MfaStatus {
+    pub totp_enabled: bool,
+    pub recovery_codes_remaining: u64,
+}
@@ -1,6 +1,18 @@
 pub mod application;
 pub mod application_grant;
+pub mod client_grant;
+pub mod federation;
 pub mod grant;
 pub mod grant_application;
+pub mod grant_implication;
+pub mod group;
+pub mod impersonation;
+pub mod login_event;
+pub mod mfa;
+pub mod oauth;
+pub mod personal_access_token;
+pub mod role;
+pub mod signing_key;
 pub mod user;
 pub mod user_grant;
+pub mod user_role;
@@ -0,0 +1,164 @@
+use poem_openapi::{Enum, Object};
+use serde::Serialize;
+use strum::{Display, EnumString};
+
+use crate::{models::user::User, services::core::jwt::Claims};
+
+/// Error codes from RFC 6749 sections 4.1.2.1 and 5.2, RFC 7009 section 2.2.1 and RFC 8628
+/// section 3.5
+#[derive(Enum, Display, Debug, Clone, Copy, PartialEq, Eq)]
+#[oai(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum OAuthErrorCode {
+    InvalidRequest,
+    InvalidClient,
+    InvalidGrant,
+    UnauthorizedClient,
+    UnsupportedGrantType,
+    UnsupportedResponseType,
+    AccessDenied,
+    ServerError,
+    UnsupportedTokenType,
+    AuthorizationPending,
+    SlowDown,
+    ExpiredToken,
+}
+
+#[derive(Object, Debug)]
+pub struct OAuthError {
+    pub error: OAuthErrorCode,
+    #[oai(skip_serializing_if_is_none)]
+    pub error_description: Option<String>,
+}
+impl OAuthError {
+    pub fn new(error: OAuthErrorCode, error_description: &str) -> Self {
+        Self {
+            error,
+            error_description: Some(error_description.to_string()),
+        }
+    }
+}
+
+/// Where the user should be sent next, carrying either a `code` or an `error` for the client
+#[derive(Object, Debug)]
+pub struct AuthorizationRedirect {
+    pub redirect_to: String,
+}
+
+/// What the consent page needs to ask the user whether to approve a client
+#[derive(Object, Debug)]
+pub struct ConsentPrompt {
+    pub client_id: String,
+    pub display_name: String,
+    pub description: String,
+    /// The user's grants the client's tokens will carry
+    pub grants: Vec<String>,
+    /// The user already approved this client, the page may skip asking again
+    pub consented: bool,
+}
+
+/// RFC 7662 section 2.2, `claims` is only present for active tokens
+#[derive(Object, Debug)]
+pub struct Introspection {
+    pub active: bool,
+    #[oai(skip_serializing_if_is_none)]
+    pub claims: Option<Claims>,
+}
+
+/// RFC 8628 section 3.2, what a device shows the user while it polls `/oauth/token`
+#[derive(Object, Debug)]
+pub struct DeviceAuthorization {
+    /// Kept by the device and exchanged at `/oauth/token`
+    pub device_code: String,
+    /// Shown to the user, who enters it at `verification_uri`
+    pub user_code: String,
+    pub verification_uri: String,
+    /// `verification_uri` with the code filled in, e.g. for a QR code
+    pub verification_uri_complete: String,
+    /// Seconds until both codes expire
+    pub expires_in: u64,
+    /// Seconds the device must wait between polls
+    pub interval: u64,
+}
+
+/// What the verification page needs to ask the user whether to approve a device
+#[derive(Object, Debug)]
+pub struct DevicePrompt {
+    pub client_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub scope: Vec<String>,
+}
+
+/// Scopes clients can ask for, anything else requested is dropped
+#[derive(Display, EnumString, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum Scope {
+    /// Asks for an ID token and access to `/userinfo`
+    Openid,
+    /// `name`, `preferred_username` and `picture`
+    Profile,
+    /// `email` and `email_verified`
+    Email,
+}
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::Openid, Scope::Profile, Scope::Email];
+}
+
+/// OpenID Connect standard claims, limited to what the granted scopes cover
+#[derive(Object, Serialize, Debug, Clone)]
+pub struct UserInfo {
+    pub sub: String,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email_verified: Option<bool>,
+}
+impl UserInfo {
+    pub fn new(user: &User, scope: &[String]) -> Self {
+        let has = |it: Scope| scope.iter().any(|scope| *scope == it.to_string());
+        let profile = has(Scope::Profile);
+        let email = has(Scope::Email) && user.email.is_some();
+
+        Self {
+            sub: user.user_id.to_string(),
+            name: profile.then(|| user.display_name.clone()),
+            preferred_username: profile.then(|| user.username.clone()),
+            picture: profile.then(|| user.image_url.clone()).flatten(),
+            email: email.then(|| user.email.clone()).flatten(),
+            email_verified: email.then(|| user.email_verified_at.is_some()),
+        }
+    }
+}
+
+/// OpenID Connect discovery metadata
+#[derive(Object, Debug)]
+pub struct OpenIdConfiguration {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub introspection_endpoint: String,
+    pub revocation_endpoint: String,
+    pub device_authorization_endpoint: String,
+    pub scopes_supported: Vec<String>,
+    pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
+    pub subject_types_supported: Vec<String>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    pub code_challenge_methods_supported: Vec<String>,
+    pub claims_supported: Vec<String>,
+}
@@ -0,0 +1,36 @@
+use chrono::Utc;
+use data::dto::personal_access_token::PersonalAccessTokenDto;
+use poem_openapi::Object;
+
+#[derive(Object, Debug)]
+pub struct PersonalAccessToken {
+    pub personal_access_token_id: i32,
+    pub name: String,
+    /// The most the token carries, grants the user has since lost are dropped on use
+    pub grants: Vec<String>,
+    /// `None` for tokens that never expire
+    pub expires_at: Option<chrono::DateTime<Utc>>,
+    pub last_used_at: Option<chrono::DateTime<Utc>>,
+    pub revoked_at: Option<chrono::DateTime<Utc>>,
+    pub created_at: chrono::DateTime<Utc>,
+}
+impl From<PersonalAccessTokenDto> for PersonalAccessToken {
+    fn from(token: PersonalAccessTokenDto) -> Self {
+        Self {
+            personal_access_token_id: token.personal_access_token_id,
+            name: token.name,
+            grants: token.grants,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            revoked_at: token.revoked_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+/// Only ever shown once, only its digest is stored
+#[derive(Object, Debug)]
+pub struct CreatedPersonalAccessToken {
+    pub token: String,
+    pub personal_access_token: PersonalAccessToken,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use data::dto::role::RoleDetailDto;
+use poem_openapi::Object;
+
+use crate::models::grant::Grant;
+
+/// A named bundle of grants, all belonging to the role's application
+#[derive(Object, Debug)]
+pub struct Role {
+    pub role_id: String,
+    pub application_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub created_by: String,
+    pub updated_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+
+    pub grants: Vec<Grant>,
+}
+impl From<RoleDetailDto> for Role {
+    fn from(value: RoleDetailDto) -> Self {
+        Self {
+            role_id: value.role.role_id,
+            application_id: value.role.application_id,
+            display_name: value.role.display_name,
+            description: value.role.description,
+            created_by: value.role.created_by,
+            updated_by: value.role.updated_by,
+            created_at: value.role.created_at,
+            updated_at: value.role.updated_at,
+            grants: value.grants.into_iter().map(Grant::from).collect(),
+        }
+    }
+}
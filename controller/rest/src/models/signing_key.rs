@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use poem_openapi::{Enum, Object};
+
+use crate::services::core::jwt::{KeyInfo, KeyStatus};
+
+#[derive(Enum, Debug)]
+#[oai(rename_all = "snake_case")]
+pub enum SigningKeyStatus {
+    Active,
+    Standby,
+    Retiring,
+}
+impl From<KeyStatus> for SigningKeyStatus {
+    fn from(value: KeyStatus) -> Self {
+        match value {
+            KeyStatus::Active => Self::Active,
+            KeyStatus::Standby => Self::Standby,
+            KeyStatus::Retiring => Self::Retiring,
+        }
+    }
+}
+
+#[derive(Object, Debug)]
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: String,
+    pub status: SigningKeyStatus,
+    pub retire_at: Option<DateTime<Utc>>,
+}
+impl From<KeyInfo> for SigningKey {
+    fn from(value: KeyInfo) -> Self {
+        Self {
+            kid: value.kid,
+            algorithm: value.algorithm,
+            status: SigningKeyStatus::from(value.status),
+            retire_at: value.retire_at,
+        }
+    }
+}
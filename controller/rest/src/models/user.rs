@@ -4,7 +4,7 @@ use chrono::Utc;
 use data::dto::user::{UserDetailDto, UserDto};
 use poem_openapi::Object;
 
-use crate::models::user_grant::UserGrant;
+use crate::models::{group::Group, user_grant::UserGrant, user_role::UserRole};
 
 #[derive(Object, Debug)]
 pub struct User {
@@ -19,8 +19,13 @@ pub struct User {
     pub updated_by: String,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
+    pub password_change_required: bool,
+    pub email_verified_at: Option<chrono::DateTime<Utc>>,
 
     pub grants: HashMap<String, UserGrant>,
+    pub roles: Vec<UserRole>,
+    /// Every group the user belongs to, directly or through a nested group
+    pub groups: Vec<Group>,
 }
 impl From<UserDetailDto> for User {
     fn from(user: UserDetailDto) -> Self {
@@ -43,10 +48,14 @@ impl From<UserDetailDto> for User {
                         updated_by: ug.user_grant.updated_by,
                         created_at: ug.user_grant.created_at,
                         updated_at: ug.user_grant.updated_at,
+                        valid_from: ug.user_grant.valid_from,
+                        valid_until: ug.user_grant.valid_until,
                     },
                 )
             })
             .collect();
+        this.roles = user.roles.into_iter().map(UserRole::from).collect();
+        this.groups = user.groups.into_iter().map(Group::from).collect();
 
         this
     }
@@ -66,7 +75,11 @@ impl From<UserDto> for User {
             updated_by: user.updated_by,
             created_at: user.created_at,
             updated_at: user.updated_at,
+            password_change_required: user.password_change_required,
+            email_verified_at: user.email_verified_at,
             grants: HashMap::new(),
+            roles: vec![],
+            groups: vec![],
         }
     }
 }
@@ -16,4 +16,8 @@ pub struct UserGrant {
     pub updated_by: String,
     pub created_at: chrono::DateTime<Utc>,
     pub updated_at: chrono::DateTime<Utc>,
+    /// The grant only counts from then on, if set
+    pub valid_from: Option<chrono::DateTime<Utc>>,
+    /// The grant stops counting then, and is disabled soon after, if set
+    pub valid_until: Option<chrono::DateTime<Utc>>,
 }
@@ -0,0 +1,35 @@
+use chrono::Utc;
+use data::dto::user_role::UserRoleDetailDto;
+use poem_openapi::Object;
+
+#[derive(Object, Debug)]
+pub struct UserRole {
+    pub role_id: String,
+    pub application_id: String,
+    pub display_name: String,
+    pub description: String,
+    /// The ids of the grants the role gives the user
+    pub grants: Vec<String>,
+
+    // NOTE: these fields refer to the user <-> role relation, NOT the role itself
+    pub created_by: String,
+    pub created_at: chrono::DateTime<Utc>,
+}
+impl From<UserRoleDetailDto> for UserRole {
+    fn from(value: UserRoleDetailDto) -> Self {
+        Self {
+            role_id: value.role.role.role_id,
+            application_id: value.role.role.application_id,
+            display_name: value.role.role.display_name,
+            description: value.role.role.description,
+            grants: value
+                .role
+                .grants
+                .into_iter()
+                .map(|grant| grant.grant_id)
+                .collect(),
+            created_by: value.user_role.created_by,
+            created_at: value.user_role.created_at,
+        }
+    }
+}
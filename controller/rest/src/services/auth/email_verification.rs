@@ -0,0 +1,155 @@
+use chrono::Utc;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        core::{mailer::Mail, token},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct VerifyEmailPayload {
+    pub token: String,
+}
+
+#[derive(ApiResponse)]
+pub enum SendEmailVerificationResponse {
+    /// A verification link was mailed to the user's current email
+    #[oai(status = 202)]
+    Sent,
+    #[oai(status = 400)]
+    NoEmail,
+    #[oai(status = 409)]
+    AlreadyVerified,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum VerifyEmailResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// Unknown, expired or already used, or the email has changed since it was issued
+    #[oai(status = 400)]
+    InvalidToken,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+fn verification_mail(services: &ApiServices, to: &str, verification_token: &str) -> Mail {
+    let hours = services.lifetimes.email_verification.num_hours();
+    let instructions = match &services.email_verification_url {
+        Some(url) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!(
+                "Follow this link to verify your email:\n\n{url}{separator}token={verification_token}"
+            )
+        }
+        None => format!("Use this token to verify your email:\n\n{verification_token}"),
+    };
+
+    Mail {
+        to: to.to_string(),
+        subject: "Verify your email".into(),
+        body: format!(
+            "{instructions}\n\nIt expires in {hours} hours. If you didn't add this email to an account you can ignore this message."
+        ),
+    }
+}
+
+/// Mails a verification link to the user's current email, invalidating any earlier link
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.send_email_verification", skip(repositories, services))]
+pub async fn send_email_verification(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+) -> SendEmailVerificationResponse {
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) => user.user,
+        Ok(None) => return SendEmailVerificationResponse::NoEmail,
+        Err(e) => return SendEmailVerificationResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    if user.is_email_verified() {
+        return SendEmailVerificationResponse::AlreadyVerified;
+    }
+    let Some(email) = &user.email else {
+        return SendEmailVerificationResponse::NoEmail;
+    };
+
+    let agent = &format!("auth.email_verification:{user_id}");
+    if let Err(e) = repositories
+        .email_verification_token
+        .invalidate_user(agent, user_id)
+        .await
+    {
+        return SendEmailVerificationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    let verification_token = token::generate();
+    if let Err(e) = repositories
+        .email_verification_token
+        .create(
+            agent,
+            user_id,
+            email,
+            &token::digest(&verification_token),
+            Utc::now() + services.lifetimes.email_verification,
+        )
+        .await
+    {
+        return SendEmailVerificationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    if let Err(e) = services
+        .mailer
+        .send(verification_mail(&services, email, &verification_token))
+        .await
+    {
+        tracing::error!("Failed to send email verification mail: {:?}", e);
+        return SendEmailVerificationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    SendEmailVerificationResponse::Sent
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.verify_email", skip(repositories, payload))]
+pub async fn verify_email(
+    repositories: ApiRepositories,
+    payload: VerifyEmailPayload,
+) -> VerifyEmailResponse {
+    let verification = match repositories
+        .email_verification_token
+        .by_hash(&token::digest(&payload.token))
+        .await
+    {
+        Ok(Some(verification)) if verification.is_usable() => verification,
+        Ok(_) => return VerifyEmailResponse::InvalidToken,
+        Err(e) => return VerifyEmailResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let agent = &format!("auth.verify_email:{}", verification.user_id);
+    match repositories
+        .email_verification_token
+        .consume(agent, verification.email_verification_token_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return VerifyEmailResponse::InvalidToken,
+        Err(e) => return VerifyEmailResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    // Only verifies the address the link was sent to, a link for an old email is worthless
+    match repositories
+        .user
+        .set_email_verified(agent, verification.user_id, &verification.email)
+        .await
+    {
+        Ok(true) => VerifyEmailResponse::Ok,
+        Ok(false) => VerifyEmailResponse::InvalidToken,
+        Err(e) => VerifyEmailResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,96 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::federation::UserIdentity,
+    services::{
+        ApiServices,
+        auth::federation::{CallbackError, FederationCallbackPayload, finish},
+        core::jwt::UserClaims,
+    },
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum LinkIdentityResponse {
+    #[oai(status = 201)]
+    Linked(Json<UserIdentity>),
+    /// Unknown, expired or used state, one started by another user, or a code the provider
+    /// rejected
+    #[oai(status = 400)]
+    Invalid,
+    /// Only tokens from a first party login can link identities
+    #[oai(status = 403)]
+    Forbidden,
+    /// The upstream account is already linked, to this user or another
+    #[oai(status = 409)]
+    Conflict,
+    /// The provider couldn't be reached or sent back a token we don't trust
+    #[oai(status = 502)]
+    Upstream(Json<ApiError>),
+    /// No callback page is configured
+    #[oai(status = 503)]
+    Unavailable,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+impl From<CallbackError> for LinkIdentityResponse {
+    fn from(error: CallbackError) -> Self {
+        match error {
+            CallbackError::Invalid => Self::Invalid,
+            CallbackError::Unavailable => Self::Unavailable,
+            CallbackError::Upstream(e) => Self::Upstream(Json(e)),
+            CallbackError::Failed(e) => Self::Failed(Json(e)),
+        }
+    }
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.federation.link", skip(repositories, services, claims, payload), fields(user_id = claims.user_id))]
+pub async fn link_identity(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+    payload: FederationCallbackPayload,
+) -> LinkIdentityResponse {
+    if !claims.is_first_party() {
+        return LinkIdentityResponse::Forbidden;
+    }
+
+    let (provider, upstream) =
+        match finish(&repositories, &services, &payload, Some(claims.user_id)).await {
+            Ok(it) => it,
+            Err(e) => return LinkIdentityResponse::from(e),
+        };
+
+    match repositories
+        .user_identity
+        .by_subject(&provider.provider_id, &upstream.sub)
+        .await
+    {
+        Ok(None) => {}
+        Ok(Some(_)) => return LinkIdentityResponse::Conflict,
+        Err(e) => {
+            tracing::error!("Failed to look up user identity: {:?}", e);
+            return LinkIdentityResponse::Failed(Json(ApiError::from(e)));
+        }
+    }
+
+    let agent = &format!("auth.federation.link:{}", claims.user_id);
+    match repositories
+        .user_identity
+        .create(
+            agent,
+            claims.user_id,
+            &provider.provider_id,
+            &upstream.sub,
+            upstream.email.as_deref(),
+        )
+        .await
+    {
+        Ok(identity) => LinkIdentityResponse::Linked(Json(UserIdentity::from(identity))),
+        Err(e) => {
+            tracing::error!("Failed to link user identity: {:?}", e);
+            LinkIdentityResponse::Failed(Json(ApiError::from(e)))
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, models::federation::UserIdentity, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum ListIdentitiesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<UserIdentity>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+pub async fn list_identities(
+    repositories: ApiRepositories,
+    user_id: i32,
+) -> ListIdentitiesResponse {
+    match repositories.user_identity.by_user(user_id).await {
+        Ok(identities) => ListIdentitiesResponse::Ok(Json(
+            identities.into_iter().map(UserIdentity::from).collect(),
+        )),
+        Err(e) => ListIdentitiesResponse::Failed(Json(ApiError::from(e))),
+    }
+}
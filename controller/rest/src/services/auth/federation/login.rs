@@ -0,0 +1,148 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::{login_event::LoginOutcome, mfa::MfaChallenge},
+    services::{
+        ApiServices,
+        auth::{
+            federation::{CallbackError, FederationCallbackPayload, finish, provision},
+            login::{
+                LoginResponse, LoginResponsePayload, complete_first_factor, record_login_event,
+            },
+        },
+        core::jwt::AuthMethod,
+    },
+    util::{error::ApiError, request::RequestContext},
+};
+
+#[derive(ApiResponse)]
+pub enum FederatedLoginResponse {
+    #[oai(status = 200)]
+    Ok(Json<LoginResponsePayload>),
+    /// The user has TOTP enabled, finish logging in at `/login/mfa`
+    #[oai(status = 202)]
+    MfaRequired(Json<MfaChallenge>),
+    /// Unknown, expired or used state, or a code the provider rejected
+    #[oai(status = 400)]
+    Invalid,
+    #[oai(status = 403)]
+    Disabled,
+    /// The provider couldn't be reached or sent back a token we don't trust
+    #[oai(status = 502)]
+    Upstream(Json<ApiError>),
+    /// No callback page is configured
+    #[oai(status = 503)]
+    Unavailable,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+impl From<CallbackError> for FederatedLoginResponse {
+    fn from(error: CallbackError) -> Self {
+        match error {
+            CallbackError::Invalid => Self::Invalid,
+            CallbackError::Unavailable => Self::Unavailable,
+            CallbackError::Upstream(e) => Self::Upstream(Json(e)),
+            CallbackError::Failed(e) => Self::Failed(Json(e)),
+        }
+    }
+}
+impl From<LoginResponse> for FederatedLoginResponse {
+    fn from(response: LoginResponse) -> Self {
+        match response {
+            LoginResponse::Ok(payload) => Self::Ok(payload),
+            LoginResponse::MfaRequired(challenge) => Self::MfaRequired(challenge),
+            LoginResponse::Disabled => Self::Disabled,
+            LoginResponse::Failed(e) => Self::Failed(e),
+            // Only password logins end up here
+            LoginResponse::InvalidCredentials
+            | LoginResponse::PasswordChangeRequired
+            | LoginResponse::Locked(_) => Self::Invalid,
+        }
+    }
+}
+
+/// Logs in the user linked to the upstream identity, provisioning one on its first login
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.federation.login", skip(repositories, services, payload, context))]
+pub async fn federated_login(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: FederationCallbackPayload,
+    context: RequestContext,
+) -> FederatedLoginResponse {
+    let (provider, claims) = match finish(&repositories, &services, &payload, None).await {
+        Ok(it) => it,
+        Err(e) => return FederatedLoginResponse::from(e),
+    };
+    let agent = &format!("auth.federation.login:{}", provider.provider_id);
+
+    let identity = match repositories
+        .user_identity
+        .by_subject(&provider.provider_id, &claims.sub)
+        .await
+    {
+        Ok(Some(identity)) => identity,
+        Ok(None) => {
+            tracing::info!(
+                "Provisioning a user for '{}' from provider '{}'",
+                claims.sub,
+                provider.provider_id
+            );
+            match provision(&repositories, &services, &provider, &claims, agent).await {
+                Ok(identity) => identity,
+                Err(e) => {
+                    tracing::error!("Failed to provision user: {:?}", e);
+                    return FederatedLoginResponse::Failed(Json(ApiError::from(e)));
+                }
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to look up user identity: {:?}", e);
+            return FederatedLoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+
+    if let Err(e) = repositories
+        .user_identity
+        .record_login(agent, identity.user_identity_id, claims.email.as_deref())
+        .await
+    {
+        tracing::error!("Failed to record identity login: {:?}", e);
+    }
+
+    let user = match repositories.user.by_id(identity.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return FederatedLoginResponse::Invalid,
+        Err(e) => {
+            tracing::error!("Database error during login: {:?}", e);
+            return FederatedLoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+    let username = user.user.username.clone();
+
+    if !user.user.enabled {
+        tracing::warn!("Federated login for disabled user: {username}");
+        record_login_event(
+            &repositories,
+            Some(user.user.user_id),
+            &username,
+            LoginOutcome::Disabled,
+            &context,
+        )
+        .await;
+        return FederatedLoginResponse::Disabled;
+    }
+
+    // The provider stands in for the password, a second factor set up here is still required
+    complete_first_factor(
+        &repositories,
+        &services,
+        user,
+        &username,
+        None,
+        &AuthMethod::amr(&[AuthMethod::Federated]),
+        &context,
+    )
+    .await
+    .into()
+}
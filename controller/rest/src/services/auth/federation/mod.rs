@@ -0,0 +1,226 @@
+use data::{
+    dto::user_identity::UserIdentityDto,
+    repository::{grant::GrantError, user::UserError, user_identity::UserIdentityError},
+};
+use poem_openapi::Object;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        core::{
+            hasher::HasherError,
+            token,
+            upstream::{UpstreamClaims, UpstreamError, UpstreamProvider},
+        },
+    },
+    util::error::ApiError,
+};
+
+pub mod link;
+pub mod list;
+pub mod login;
+pub mod providers;
+pub mod start;
+pub mod unlink;
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum FederationServiceError {
+    #[error(transparent)]
+    User {
+        #[from]
+        inner_error: UserError,
+    },
+    #[error(transparent)]
+    UserIdentity {
+        #[from]
+        inner_error: UserIdentityError,
+    },
+    #[error(transparent)]
+    Hasher {
+        #[from]
+        inner_error: HasherError,
+    },
+    #[error(transparent)]
+    Grant {
+        #[from]
+        inner_error: GrantError,
+    },
+    #[error("The username '{username}' was taken while provisioning its user")]
+    UsernameTaken { username: String },
+}
+
+/// What the provider sent back to the callback page
+#[derive(Object, Debug)]
+pub struct FederationCallbackPayload {
+    pub code: String,
+    pub state: String,
+}
+
+pub enum CallbackError {
+    /// Unknown, expired or used state, one started for someone else, or a code the provider
+    /// rejected
+    Invalid,
+    /// No callback page is configured
+    Unavailable,
+    Upstream(ApiError),
+    Failed(ApiError),
+}
+
+/// Matches a callback up with the request that started it and exchanges its code for the
+/// upstream identity. `user_id` must be the one the request was started for, `None` for logins.
+pub async fn finish(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    payload: &FederationCallbackPayload,
+    user_id: Option<i32>,
+) -> Result<(UpstreamProvider, UpstreamClaims), CallbackError> {
+    let Some(redirect_uri) = &services.federation_callback_url else {
+        return Err(CallbackError::Unavailable);
+    };
+
+    let request = match repositories
+        .federation_request
+        .by_state_hash(&token::digest(&payload.state))
+        .await
+    {
+        Ok(Some(request)) if request.is_usable() && request.user_id == user_id => request,
+        Ok(_) => return Err(CallbackError::Invalid),
+        Err(e) => {
+            tracing::error!("Failed to look up federation request: {:?}", e);
+            return Err(CallbackError::Failed(ApiError::from(e)));
+        }
+    };
+
+    let agent = &format!("auth.federation.callback:{}", request.provider_id);
+    match repositories
+        .federation_request
+        .consume(agent, request.federation_request_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return Err(CallbackError::Invalid),
+        Err(e) => {
+            tracing::error!("Failed to consume federation request: {:?}", e);
+            return Err(CallbackError::Failed(ApiError::from(e)));
+        }
+    }
+
+    // The provider may have been removed from the configuration since
+    let Some(provider) = services.upstream.provider(&request.provider_id) else {
+        return Err(CallbackError::Invalid);
+    };
+
+    match services
+        .upstream
+        .exchange(
+            provider,
+            redirect_uri,
+            &payload.code,
+            &request.code_verifier,
+            &request.nonce,
+        )
+        .await
+    {
+        Ok(claims) => Ok((provider.clone(), claims)),
+        Err(e @ UpstreamError::Exchange { .. }) => {
+            tracing::warn!("Upstream code exchange failed: {e}");
+            Err(CallbackError::Invalid)
+        }
+        Err(e) => {
+            tracing::error!("Upstream login failed: {e}");
+            Err(CallbackError::Upstream(ApiError::from(e)))
+        }
+    }
+}
+
+/// Creates a user for someone logging in through `provider` for the first time. Never linked
+/// to an existing user by email, that would hand the account to whoever controls the address
+/// at the provider.
+///
+/// Everything is created in one transaction, and if a concurrent login through the same
+/// identity got there first its user is the one logged in.
+pub async fn provision(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    provider: &UpstreamProvider,
+    claims: &UpstreamClaims,
+    agent: &str,
+) -> Result<UserIdentityDto, FederationServiceError> {
+    let username = available_username(repositories, provider, claims).await?;
+    // Nobody knows it, the user logs in through the provider or resets it by email
+    let password = services.hasher.hash(&token::generate())?;
+    // Left out if another user already verified it, logging in by email must stay unambiguous
+    let email = match claims.verified_email() {
+        Some(email) if repositories.user.by_verified_email(email).await?.is_none() => Some(email),
+        _ => None,
+    };
+
+    // A misconfigured grant shouldn't keep the user from logging in
+    let mut grant_ids = Vec::new();
+    for grant_id in &provider.default_grants {
+        if repositories.grant.by_id(grant_id).await?.is_some() {
+            grant_ids.push(grant_id.clone());
+        } else {
+            tracing::error!(
+                "Default grant '{grant_id}' of provider '{}' doesn't exist",
+                provider.provider_id
+            );
+        }
+    }
+
+    let provisioned = repositories
+        .user_identity
+        .provision(
+            agent,
+            &username,
+            &password,
+            claims.name.as_deref(),
+            email,
+            claims.picture.as_deref(),
+            &grant_ids,
+            &provider.provider_id,
+            &claims.sub,
+            claims.email.as_deref(),
+        )
+        .await?;
+    if let Some(identity) = provisioned {
+        return Ok(identity);
+    }
+
+    repositories
+        .user_identity
+        .by_subject(&provider.provider_id, &claims.sub)
+        .await?
+        .ok_or(FederationServiceError::UsernameTaken { username })
+}
+
+/// The upstream `preferred_username` when it's free, otherwise one made from the provider and
+/// subject
+async fn available_username(
+    repositories: &ApiRepositories,
+    provider: &UpstreamProvider,
+    claims: &UpstreamClaims,
+) -> Result<String, UserError> {
+    let candidates = claims
+        .preferred_username
+        .iter()
+        .filter(|username| username.len() >= 3)
+        .cloned()
+        .chain([format!("{}-{}", provider.provider_id, claims.sub)]);
+
+    for candidate in candidates {
+        if repositories.user.by_username(&candidate).await?.is_none() {
+            return Ok(candidate);
+        }
+    }
+
+    Ok(format!(
+        "{}-{}",
+        provider.provider_id,
+        &token::generate()[..8]
+    ))
+}
@@ -0,0 +1,25 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{models::federation::FederationProvider, services::ApiServices};
+
+#[derive(ApiResponse)]
+pub enum ListFederationProvidersResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<FederationProvider>>),
+}
+
+/// Empty when no callback page is configured, as none of them could be logged in through
+pub async fn list_providers(services: ApiServices) -> ListFederationProvidersResponse {
+    if services.federation_callback_url.is_none() {
+        return ListFederationProvidersResponse::Ok(Json(vec![]));
+    }
+
+    ListFederationProvidersResponse::Ok(Json(
+        services
+            .upstream
+            .providers()
+            .iter()
+            .map(FederationProvider::from)
+            .collect(),
+    ))
+}
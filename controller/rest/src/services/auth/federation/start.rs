@@ -0,0 +1,128 @@
+use chrono::Utc;
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::federation::FederationRedirect,
+    services::{
+        ApiServices,
+        core::{jwt::UserClaims, pkce, token},
+    },
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum StartFederationResponse {
+    #[oai(status = 200)]
+    Ok(Json<FederationRedirect>),
+    /// Only tokens from a first party login can link identities
+    #[oai(status = 403)]
+    Forbidden,
+    /// No such provider is configured
+    #[oai(status = 404)]
+    NotFound,
+    /// The provider's discovery document couldn't be fetched
+    #[oai(status = 502)]
+    Upstream(Json<ApiError>),
+    /// No callback page is configured
+    #[oai(status = 503)]
+    Unavailable,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.federation.start_login", skip(repositories, services))]
+pub async fn start_login(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    provider_id: &str,
+) -> StartFederationResponse {
+    let agent = &format!("auth.federation.login:{provider_id}");
+
+    start(&repositories, &services, provider_id, None, agent).await
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.federation.start_link", skip(repositories, services, claims), fields(user_id = claims.user_id))]
+pub async fn start_link(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+    provider_id: &str,
+) -> StartFederationResponse {
+    // Otherwise a leaked token could be used to add a way into the account
+    if !claims.is_first_party() {
+        return StartFederationResponse::Forbidden;
+    }
+
+    let agent = &format!("auth.federation.link:{}", claims.user_id);
+
+    start(
+        &repositories,
+        &services,
+        provider_id,
+        Some(claims.user_id),
+        agent,
+    )
+    .await
+}
+
+async fn start(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    provider_id: &str,
+    user_id: Option<i32>,
+    agent: &str,
+) -> StartFederationResponse {
+    let Some(redirect_uri) = &services.federation_callback_url else {
+        return StartFederationResponse::Unavailable;
+    };
+    let Some(provider) = services.upstream.provider(provider_id) else {
+        return StartFederationResponse::NotFound;
+    };
+
+    let state = token::generate();
+    let nonce = token::generate();
+    let code_verifier = token::generate();
+    let expires_at = Utc::now() + services.lifetimes.federation_request;
+
+    // Built before anything is stored, so an unreachable provider leaves nothing behind
+    let authorization_url = match services
+        .upstream
+        .authorization_url(
+            provider,
+            redirect_uri,
+            &state,
+            &nonce,
+            &pkce::challenge(&code_verifier),
+        )
+        .await
+    {
+        Ok(authorization_url) => authorization_url,
+        Err(e) => {
+            tracing::error!("Failed to discover upstream provider: {e}");
+            return StartFederationResponse::Upstream(Json(ApiError::from(e)));
+        }
+    };
+
+    if let Err(e) = repositories
+        .federation_request
+        .create(
+            agent,
+            &token::digest(&state),
+            provider_id,
+            &nonce,
+            &code_verifier,
+            user_id,
+            expires_at,
+        )
+        .await
+    {
+        tracing::error!("Failed to store federation request: {:?}", e);
+        return StartFederationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    StartFederationResponse::Ok(Json(FederationRedirect {
+        authorization_url,
+        expires_at,
+    }))
+}
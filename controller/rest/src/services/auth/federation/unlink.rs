@@ -0,0 +1,31 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum UnlinkIdentityResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// No such identity, or it's linked to someone else
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// Logging in through the provider afterwards provisions a new user rather than relinking
+pub async fn unlink_identity(
+    repositories: ApiRepositories,
+    user_id: i32,
+    user_identity_id: i32,
+) -> UnlinkIdentityResponse {
+    match repositories
+        .user_identity
+        .delete(user_id, user_identity_id)
+        .await
+    {
+        Ok(true) => UnlinkIdentityResponse::Ok,
+        Ok(false) => UnlinkIdentityResponse::NotFound,
+        Err(e) => UnlinkIdentityResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -1,16 +1,33 @@
+use chrono::Utc;
+use data::{
+    dto::user::{UserDetailDto, UserDto},
+    repository::user::UserError,
+};
 use poem_openapi::{ApiResponse, Object, payload::Json};
 
 use crate::{
     api::ApiRepositories,
-    models::user::User,
-    services::{ApiServices, core::jwt::Claims},
-    util::error::ApiError,
+    models::{login_event::LoginOutcome, mfa::MfaChallenge, user::User},
+    services::{
+        ApiServices,
+        auth::session::{IssuedTokens, start_session},
+        core::{
+            jwt::{AuthMethod, Claims},
+            throttle::{ThrottleAccount, retry_after_seconds},
+            token,
+        },
+    },
+    util::{error::ApiError, request::RequestContext},
 };
 
 #[derive(Object, Debug)]
 pub struct LoginPayload {
+    /// Or a verified email, when the server allows logging in by email
     pub username: String,
     pub password: String,
+    /// Replaces a temporary password set by an admin, ignored otherwise
+    #[oai(validator(min_length = 8))]
+    pub new_password: Option<String>,
 }
 
 #[derive(Object, Debug)]
@@ -18,34 +35,209 @@ pub struct LoginResponsePayload {
     pub user: User,
     pub claims: Claims,
     pub token: String,
+    pub refresh_token: String,
 }
 
 #[derive(ApiResponse)]
 pub enum LoginResponse {
     #[oai(status = 200)]
     Ok(Json<LoginResponsePayload>),
+    /// The password was correct, finish logging in at `/login/mfa`
+    #[oai(status = 202)]
+    MfaRequired(Json<MfaChallenge>),
     #[oai(status = 400)]
     InvalidCredentials,
+    #[oai(status = 403)]
+    Disabled,
+    /// The password was reset by an admin, log in again with a `new_password`
+    #[oai(status = 428)]
+    PasswordChangeRequired,
+    /// Too many failed attempts for the username or source address, retry after the given seconds
+    #[oai(status = 429)]
+    Locked(#[oai(header = "Retry-After")] u64),
     #[oai(status = 500)]
     Failed(Json<ApiError>),
 }
 
-#[tracing::instrument(level = tracing::Level::INFO, "services.auth.login", skip(repositories, services, payload), fields(username = %payload.username))]
+/// Rehashes the password if its stored hash uses outdated parameters or predates every hash
+/// getting its own salt. Failures are only logged, the login itself already succeeded.
+async fn upgrade_password_hash(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: &UserDto,
+    password: &str,
+) {
+    let outdated = match services.hasher.needs_rehash(&user.password) {
+        Ok(outdated) => outdated,
+        Err(e) => {
+            tracing::error!("Failed to inspect password hash: {:?}", e);
+            return;
+        }
+    };
+
+    let shared_salt = !user.password_salt_unique;
+
+    if !outdated && !shared_salt {
+        return;
+    }
+
+    tracing::info!("Upgrading password hash (outdated={outdated}, shared_salt={shared_salt})");
+
+    let hash = match services.hasher.hash(password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::error!("Failed to rehash password: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = repositories
+        .user
+        .update(
+            &format!("auth.login.rehash:{}", user.user_id),
+            user.user_id,
+            None,
+            None,
+            Some(&hash),
+            None,
+            None,
+        )
+        .await
+    {
+        tracing::error!("Failed to store upgraded password hash: {:?}", e);
+    }
+}
+
+fn hash_new_password(services: &ApiServices, new_password: &str) -> Result<String, LoginResponse> {
+    services.hasher.hash(new_password).map_err(|e| {
+        tracing::error!("Failed to hash new password: {:?}", e);
+        LoginResponse::Failed(Json(ApiError::from(e)))
+    })
+}
+
+/// Stores the hash of the password replacing a temporary one, once every factor has passed
+pub async fn replace_temporary_password(
+    repositories: &ApiRepositories,
+    user_id: i32,
+    hash: &str,
+) -> Result<UserDetailDto, LoginResponse> {
+    repositories
+        .user
+        .set_password(
+            &format!("auth.login.change_password:{}", user_id),
+            user_id,
+            hash,
+            false,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to store new password: {:?}", e);
+            LoginResponse::Failed(Json(ApiError::from(e)))
+        })
+}
+
+/// Looks `identifier` up as a username, falling back to a verified email when enabled
+async fn find_user(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    identifier: &str,
+) -> Result<Option<UserDetailDto>, UserError> {
+    if let Some(user) = repositories.user.by_username(identifier).await? {
+        return Ok(Some(user));
+    }
+
+    if services.login_by_email && identifier.contains('@') {
+        return repositories.user.by_verified_email(identifier).await;
+    }
+
+    Ok(None)
+}
+
+/// Failures are only logged, a broken counter shouldn't turn into a failed login
+pub async fn record_login_failure(
+    services: &ApiServices,
+    account: ThrottleAccount<'_>,
+    context: &RequestContext,
+) {
+    if let Err(e) = services
+        .throttle
+        .record_failure(account, context.source_ip.as_deref())
+        .await
+    {
+        tracing::error!("Failed to record login failure: {:?}", e);
+    }
+}
+
+/// Failures are only logged, a missing audit row shouldn't lock everyone out
+pub async fn record_login_event(
+    repositories: &ApiRepositories,
+    user_id: Option<i32>,
+    username: &str,
+    outcome: LoginOutcome,
+    context: &RequestContext,
+) {
+    if let Err(e) = repositories
+        .login_event
+        .create(
+            "auth.login",
+            user_id,
+            username,
+            &outcome.to_string(),
+            context.source_ip.as_deref(),
+            context.user_agent.as_deref(),
+        )
+        .await
+    {
+        tracing::error!("Failed to record login event: {:?}", e);
+    }
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.login", skip(repositories, services, payload, context), fields(username = %payload.username))]
 pub async fn login(
     repositories: ApiRepositories,
     services: ApiServices,
     payload: LoginPayload,
+    context: RequestContext,
 ) -> LoginResponse {
     tracing::info!("Login attempt for user: {}", payload.username);
 
-    let user = match repositories.user.by_username(&payload.username).await {
-        Ok(Some(user)) => Some(user),
-        Ok(None) => None,
+    let user = match find_user(&repositories, &services, &payload.username).await {
+        Ok(user) => user,
         Err(e) => {
             tracing::error!("Database error during login: {:?}", e);
             return LoginResponse::Failed(Json(ApiError::from(e)));
         }
     };
+    // Every way of naming a user shares their budget
+    let account = match &user {
+        Some(u) => ThrottleAccount::User(u.user.user_id),
+        None => ThrottleAccount::Unknown(&payload.username),
+    };
+
+    // Checked before the password so a locked out account can't be used to keep guessing
+    match services
+        .throttle
+        .retry_after(account, context.source_ip.as_deref())
+        .await
+    {
+        Ok(Some(retry_after)) => {
+            tracing::warn!("Login attempt for locked out user: {}", payload.username);
+            record_login_event(
+                &repositories,
+                user.as_ref().map(|u| u.user.user_id),
+                &payload.username,
+                LoginOutcome::Locked,
+                &context,
+            )
+            .await;
+            return LoginResponse::Locked(retry_after_seconds(retry_after));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::error!("Failed to check login throttle: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    }
 
     // Always verify to prevent timing attacks
     let verification_result = match &user {
@@ -53,6 +245,15 @@ pub async fn login(
         None => {
             // Perform dummy verification with dummy hash to normalize timing
             services.hasher.dummy_verification(&payload.password);
+            record_login_failure(&services, account, &context).await;
+            record_login_event(
+                &repositories,
+                None,
+                &payload.username,
+                LoginOutcome::InvalidCredentials,
+                &context,
+            )
+            .await;
             return LoginResponse::InvalidCredentials;
         }
     };
@@ -63,24 +264,207 @@ pub async fn login(
         }
         _ => {
             tracing::warn!("Failed login attempt for user: {}", payload.username);
+            record_login_failure(&services, account, &context).await;
+            record_login_event(
+                &repositories,
+                user.as_ref().map(|u| u.user.user_id),
+                &payload.username,
+                LoginOutcome::InvalidCredentials,
+                &context,
+            )
+            .await;
             return LoginResponse::InvalidCredentials;
         }
     };
 
-    let user = User::from(user.unwrap());
-    let claims = Claims::r#for(&user);
+    let user = user.unwrap();
 
-    let token = match services.jwt.sign(&claims) {
-        Ok(token) => token,
+    // Only reveal the account is disabled to someone who knows its password
+    if !user.user.enabled {
+        tracing::warn!("Login attempt for disabled user: {}", payload.username);
+        record_login_event(
+            &repositories,
+            Some(user.user.user_id),
+            &payload.username,
+            LoginOutcome::Disabled,
+            &context,
+        )
+        .await;
+        return LoginResponse::Disabled;
+    }
+
+    let new_password = if user.user.password_change_required {
+        // The temporary password can't be kept by passing it back as the new one
+        let Some(new_password) = payload
+            .new_password
+            .as_deref()
+            .filter(|new_password| *new_password != payload.password)
+        else {
+            record_login_event(
+                &repositories,
+                Some(user.user.user_id),
+                &payload.username,
+                LoginOutcome::PasswordChangeRequired,
+                &context,
+            )
+            .await;
+            return LoginResponse::PasswordChangeRequired;
+        };
+
+        Some(new_password)
+    } else {
+        upgrade_password_hash(&repositories, &services, &user.user, &payload.password).await;
+        None
+    };
+
+    complete_first_factor(
+        &repositories,
+        &services,
+        user,
+        &payload.username,
+        new_password,
+        &AuthMethod::amr(&[AuthMethod::Password]),
+        &context,
+    )
+    .await
+}
+
+/// Challenges users with TOTP enabled for their second factor, logs everyone else in.
+/// A `new_password` replacing a temporary one is only stored once the second factor passed.
+pub async fn complete_first_factor(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: UserDetailDto,
+    username: &str,
+    new_password: Option<&str>,
+    amr: &[String],
+    context: &RequestContext,
+) -> LoginResponse {
+    let new_password_hash = match new_password {
+        Some(new_password) => match hash_new_password(services, new_password) {
+            Ok(hash) => Some(hash),
+            Err(response) => return response,
+        },
+        None => None,
+    };
+
+    match repositories.user_totp.by_user(user.user.user_id).await {
+        Ok(Some(totp)) if totp.is_confirmed() => {
+            return challenge(
+                repositories,
+                services,
+                &user.user,
+                username,
+                new_password_hash.as_deref(),
+                context,
+            )
+            .await;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!("Failed to look up TOTP enrollment: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    }
+
+    let user = match new_password_hash {
+        Some(hash) => {
+            match replace_temporary_password(repositories, user.user.user_id, &hash).await {
+                Ok(user) => user,
+                Err(response) => return response,
+            }
+        }
+        None => user,
+    };
+
+    complete_login(repositories, services, user, username, amr, context).await
+}
+
+/// Hands out a challenge token to be exchanged, along with a second factor, at `/login/mfa`
+async fn challenge(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: &UserDto,
+    username: &str,
+    new_password_hash: Option<&str>,
+    context: &RequestContext,
+) -> LoginResponse {
+    let challenge_token = token::generate();
+    let expires_at = Utc::now() + services.lifetimes.mfa_challenge;
+
+    if let Err(e) = repositories
+        .mfa_challenge
+        .create(
+            &format!("auth.login:{}", user.user_id),
+            user.user_id,
+            &token::digest(&challenge_token),
+            expires_at,
+            new_password_hash,
+        )
+        .await
+    {
+        tracing::error!("Failed to create MFA challenge: {:?}", e);
+        return LoginResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    record_login_event(
+        repositories,
+        Some(user.user_id),
+        username,
+        LoginOutcome::MfaRequired,
+        context,
+    )
+    .await;
+
+    LoginResponse::MfaRequired(Json(MfaChallenge {
+        challenge_token,
+        expires_at,
+    }))
+}
+
+/// Opens a session for a user who has passed every required factor
+pub async fn complete_login(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: UserDetailDto,
+    username: &str,
+    amr: &[String],
+    context: &RequestContext,
+) -> LoginResponse {
+    if let Err(e) = services.throttle.record_success(user.user.user_id).await {
+        tracing::error!("Failed to reset login failures: {:?}", e);
+    }
+
+    let agent = &format!("auth.login:{}", user.user.user_id);
+    let IssuedTokens {
+        claims,
+        token,
+        refresh_token,
+    } = match start_session(repositories, services, &user, amr, None, &[], agent).await {
+        Ok(issued) => issued,
         Err(e) => {
-            tracing::error!("JWT signing error: {:?}", e);
+            tracing::error!("Failed to start session: {:?}", e);
             return LoginResponse::Failed(Json(ApiError::from(e)));
         }
     };
+    let user = User::from(user);
+
+    record_login_event(
+        repositories,
+        Some(user.user_id),
+        username,
+        LoginOutcome::Success,
+        context,
+    )
+    .await;
+    if let Err(e) = repositories.user.set_last_login(user.user_id).await {
+        tracing::error!("Failed to set last login: {:?}", e);
+    }
 
     LoginResponse::Ok(Json(LoginResponsePayload {
         user,
         claims,
         token,
+        refresh_token,
     }))
 }
@@ -0,0 +1,75 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::session::{end_session, end_user_sessions},
+        core::jwt::UserClaims,
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct LogoutAllResponsePayload {
+    pub revoked_sessions: usize,
+}
+
+#[derive(ApiResponse)]
+pub enum LogoutResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// Personal access tokens aren't sessions, revoke them with `DELETE /me/tokens/{id}`
+    #[oai(status = 400)]
+    PersonalAccessToken,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum LogoutAllResponse {
+    #[oai(status = 200)]
+    Ok(Json<LogoutAllResponsePayload>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.logout", skip(repositories, services, claims), fields(user_id = claims.user_id))]
+pub async fn logout(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+) -> LogoutResponse {
+    if claims.personal_access_token_id.is_some() {
+        return LogoutResponse::PersonalAccessToken;
+    }
+
+    let agent = &format!("auth.logout:{}", claims.user_id);
+
+    match end_session(&repositories, &services, &claims.session_id, agent).await {
+        Ok(_) => LogoutResponse::Ok,
+        Err(e) => {
+            tracing::error!("Failed to end session: {:?}", e);
+            LogoutResponse::Failed(Json(ApiError::from(e)))
+        }
+    }
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.logout_all", skip(repositories, services, claims), fields(user_id = claims.user_id))]
+pub async fn logout_all(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+) -> LogoutAllResponse {
+    let agent = &format!("auth.logout_all:{}", claims.user_id);
+
+    match end_user_sessions(&repositories, &services, claims.user_id, None, agent).await {
+        Ok(revoked_sessions) => {
+            LogoutAllResponse::Ok(Json(LogoutAllResponsePayload { revoked_sessions }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to end sessions: {:?}", e);
+            LogoutAllResponse::Failed(Json(ApiError::from(e)))
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::Utc;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::mfa::RecoveryCodes,
+    services::{ApiServices, auth::mfa::issue_recovery_codes},
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct TotpCodePayload {
+    pub code: String,
+}
+
+#[derive(ApiResponse)]
+pub enum ConfirmTotpResponse {
+    #[oai(status = 200)]
+    Ok(Json<RecoveryCodes>),
+    #[oai(status = 400)]
+    InvalidCode,
+    /// No enrollment is waiting to be confirmed
+    #[oai(status = 404)]
+    NotEnrolled,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// Turns TOTP on once the user proves their authenticator produces the right codes
+pub async fn confirm_totp(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+    payload: TotpCodePayload,
+    agent: &str,
+) -> ConfirmTotpResponse {
+    let totp = match repositories.user_totp.by_user(user_id).await {
+        Ok(Some(totp)) if !totp.is_confirmed() => totp,
+        Ok(_) => return ConfirmTotpResponse::NotEnrolled,
+        Err(e) => return ConfirmTotpResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let step = match services
+        .totp
+        .verify(&totp.secret, &payload.code, Utc::now())
+    {
+        Ok(Some(step)) => step,
+        Ok(None) => return ConfirmTotpResponse::InvalidCode,
+        Err(e) => return ConfirmTotpResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match repositories.user_totp.confirm(agent, user_id, step).await {
+        Ok(true) => {}
+        Ok(false) => return ConfirmTotpResponse::NotEnrolled,
+        Err(e) => return ConfirmTotpResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    match issue_recovery_codes(&repositories, user_id, agent).await {
+        Ok(recovery_codes) => ConfirmTotpResponse::Ok(Json(RecoveryCodes { recovery_codes })),
+        Err(e) => ConfirmTotpResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,78 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::mfa::{CodeOutcome, check_code, confirm::TotpCodePayload},
+        core::{jwt::UserClaims, throttle::retry_after_seconds},
+    },
+    util::{error::ApiError, request::RequestContext},
+};
+
+#[derive(ApiResponse)]
+pub enum DisableTotpResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 400)]
+    InvalidCode,
+    /// The session wasn't opened with a second factor, log in again with one first
+    #[oai(status = 403)]
+    StepUpRequired,
+    #[oai(status = 404)]
+    NotEnrolled,
+    #[oai(status = 429)]
+    Locked(#[oai(header = "Retry-After")] u64),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// Turns TOTP off and discards the recovery codes. Only sessions opened with a second factor
+/// may do so, and a current code is required on top so a stolen access token alone can't
+/// strip it
+pub async fn disable_totp(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+    payload: TotpCodePayload,
+    context: RequestContext,
+    agent: &str,
+) -> DisableTotpResponse {
+    if !claims.has_mfa() {
+        return DisableTotpResponse::StepUpRequired;
+    }
+
+    let user_id = claims.user_id;
+    let totp = match repositories.user_totp.by_user(user_id).await {
+        Ok(Some(totp)) if totp.is_confirmed() => totp,
+        Ok(_) => return DisableTotpResponse::NotEnrolled,
+        Err(e) => return DisableTotpResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match check_code(
+        &repositories,
+        &services,
+        &totp,
+        &payload.code,
+        &context,
+        agent,
+    )
+    .await
+    {
+        Ok(CodeOutcome::Accepted(_)) => {}
+        Ok(CodeOutcome::Rejected) => return DisableTotpResponse::InvalidCode,
+        Ok(CodeOutcome::Locked(retry_after)) => {
+            return DisableTotpResponse::Locked(retry_after_seconds(retry_after));
+        }
+        Err(e) => return DisableTotpResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    if let Err(e) = repositories.user_totp.delete(user_id).await {
+        return DisableTotpResponse::Failed(Json(ApiError::from(e)));
+    }
+    if let Err(e) = repositories.recovery_code.delete_user(user_id).await {
+        return DisableTotpResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    DisableTotpResponse::Ok
+}
@@ -0,0 +1,48 @@
+use data::repository::user_totp::UserTotpError;
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::mfa::TotpEnrollment,
+    services::{ApiServices, core::totp::Totp},
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum EnrollTotpResponse {
+    #[oai(status = 200)]
+    Ok(Json<TotpEnrollment>),
+    /// Disable the existing enrollment first
+    #[oai(status = 409)]
+    AlreadyEnrolled,
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// Starts enrollment with a new secret, which only takes effect once confirmed with a code
+pub async fn enroll_totp(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+    agent: &str,
+) -> EnrollTotpResponse {
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return EnrollTotpResponse::NotFound,
+        Err(e) => return EnrollTotpResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let secret = Totp::generate_secret();
+    match repositories.user_totp.enroll(agent, user_id, &secret).await {
+        Ok(_) => {}
+        Err(UserTotpError::AlreadyEnrolled { .. }) => return EnrollTotpResponse::AlreadyEnrolled,
+        Err(e) => return EnrollTotpResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    EnrollTotpResponse::Ok(Json(TotpEnrollment {
+        otpauth_uri: services.totp.uri(&secret, &user.user.username),
+        secret,
+    }))
+}
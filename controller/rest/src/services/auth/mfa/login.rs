@@ -0,0 +1,153 @@
+use poem_openapi::{Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::login_event::LoginOutcome,
+    services::{
+        ApiServices,
+        auth::{
+            login::{
+                LoginResponse, complete_login, record_login_event, replace_temporary_password,
+            },
+            mfa::{CodeOutcome, check_code},
+        },
+        core::{jwt::AuthMethod, throttle::retry_after_seconds, token},
+    },
+    util::{error::ApiError, request::RequestContext},
+};
+
+#[derive(Object, Debug)]
+pub struct MfaLoginPayload {
+    pub challenge_token: String,
+    /// A TOTP code, or one of the user's recovery codes
+    pub code: String,
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.mfa.login", skip(repositories, services, payload, context))]
+pub async fn login_mfa(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: MfaLoginPayload,
+    context: RequestContext,
+) -> LoginResponse {
+    let challenge = match repositories
+        .mfa_challenge
+        .by_hash(&token::digest(&payload.challenge_token))
+        .await
+    {
+        Ok(Some(challenge)) if challenge.is_usable() => challenge,
+        Ok(_) => return LoginResponse::InvalidCredentials,
+        Err(e) => {
+            tracing::error!("Database error during MFA login: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+
+    let user = match repositories.user.by_id(challenge.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return LoginResponse::InvalidCredentials,
+        Err(e) => {
+            tracing::error!("Database error during MFA login: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+    let user_id = user.user.user_id;
+    let username = user.user.username.clone();
+
+    if !user.user.enabled {
+        tracing::warn!("MFA login attempt for disabled user: {}", username);
+        record_login_event(
+            &repositories,
+            Some(user_id),
+            &username,
+            LoginOutcome::Disabled,
+            &context,
+        )
+        .await;
+        return LoginResponse::Disabled;
+    }
+
+    // TOTP may have been turned off since the challenge was issued
+    let totp = match repositories.user_totp.by_user(user_id).await {
+        Ok(Some(totp)) if totp.is_confirmed() => totp,
+        Ok(_) => return LoginResponse::InvalidCredentials,
+        Err(e) => {
+            tracing::error!("Failed to look up TOTP enrollment: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+
+    let agent = &format!("auth.login.mfa:{user_id}");
+    let method = match check_code(
+        &repositories,
+        &services,
+        &totp,
+        &payload.code,
+        &context,
+        agent,
+    )
+    .await
+    {
+        Ok(CodeOutcome::Accepted(method)) => method,
+        Ok(CodeOutcome::Rejected) => {
+            tracing::warn!("Invalid MFA code for user: {}", username);
+            record_login_event(
+                &repositories,
+                Some(user_id),
+                &username,
+                LoginOutcome::InvalidMfaCode,
+                &context,
+            )
+            .await;
+            return LoginResponse::InvalidCredentials;
+        }
+        Ok(CodeOutcome::Locked(retry_after)) => {
+            tracing::warn!("MFA login attempt for locked out user: {}", username);
+            record_login_event(
+                &repositories,
+                Some(user_id),
+                &username,
+                LoginOutcome::Locked,
+                &context,
+            )
+            .await;
+            return LoginResponse::Locked(retry_after_seconds(retry_after));
+        }
+        Err(e) => {
+            tracing::error!("Failed to check MFA code: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    };
+
+    // Consumed last so a wrong code doesn't burn the challenge, the lockout bounds retries
+    match repositories
+        .mfa_challenge
+        .consume(agent, challenge.mfa_challenge_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return LoginResponse::InvalidCredentials,
+        Err(e) => {
+            tracing::error!("Failed to consume MFA challenge: {:?}", e);
+            return LoginResponse::Failed(Json(ApiError::from(e)));
+        }
+    }
+
+    let user = match challenge.new_password_hash.as_deref() {
+        Some(hash) => match replace_temporary_password(&repositories, user_id, hash).await {
+            Ok(user) => user,
+            Err(response) => return response,
+        },
+        None => user,
+    };
+
+    complete_login(
+        &repositories,
+        &services,
+        user,
+        &username,
+        &AuthMethod::amr(&[AuthMethod::Password, method, AuthMethod::Mfa]),
+        &context,
+    )
+    .await
+}
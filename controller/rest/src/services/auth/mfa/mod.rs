@@ -0,0 +1,125 @@
+use chrono::{Duration, Utc};
+use data::{
+    dto::user_totp::UserTotpDto,
+    repository::{
+        login_throttle::LoginThrottleError, recovery_code::RecoveryCodeError,
+        user_totp::UserTotpError,
+    },
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        core::{jwt::AuthMethod, recovery_code, throttle::ThrottleAccount, token, totp::TotpError},
+    },
+    util::request::RequestContext,
+};
+
+pub mod confirm;
+pub mod disable;
+pub mod enroll;
+pub mod login;
+pub mod recovery_codes;
+pub mod status;
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum MfaServiceError {
+    #[error(transparent)]
+    UserTotp {
+        #[from]
+        inner_error: UserTotpError,
+    },
+    #[error(transparent)]
+    RecoveryCode {
+        #[from]
+        inner_error: RecoveryCodeError,
+    },
+    #[error(transparent)]
+    LoginThrottle {
+        #[from]
+        inner_error: LoginThrottleError,
+    },
+    #[error(transparent)]
+    Totp {
+        #[from]
+        inner_error: TotpError,
+    },
+}
+
+pub enum CodeOutcome {
+    /// With the method the code proved, a TOTP code or a recovery code
+    Accepted(AuthMethod),
+    Rejected,
+    /// Too many wrong codes, retry after the given duration
+    Locked(Duration),
+}
+
+/// Checks `code` as a TOTP code, falling back to a recovery code, and uses it up if it's valid.
+///
+/// Wrong codes count towards the same lockout as wrong passwords, otherwise six digits would
+/// fall to brute force long before the challenge expired.
+pub async fn check_code(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    totp: &UserTotpDto,
+    code: &str,
+    context: &RequestContext,
+    agent: &str,
+) -> Result<CodeOutcome, MfaServiceError> {
+    let account = ThrottleAccount::User(totp.user_id);
+    let source_ip = context.source_ip.as_deref();
+
+    if let Some(retry_after) = services.throttle.retry_after(account, source_ip).await? {
+        return Ok(CodeOutcome::Locked(retry_after));
+    }
+
+    let (accepted, method) = match services.totp.verify(&totp.secret, code, Utc::now())? {
+        Some(step) => (
+            repositories
+                .user_totp
+                .use_step(agent, totp.user_id, step)
+                .await?,
+            AuthMethod::Otp,
+        ),
+        None => {
+            let code = recovery_code::normalize(code);
+            let redeemed = !code.is_empty()
+                && repositories
+                    .recovery_code
+                    .redeem(agent, totp.user_id, &token::digest(&code))
+                    .await?;
+            (redeemed, AuthMethod::RecoveryCode)
+        }
+    };
+
+    if !accepted {
+        services.throttle.record_failure(account, source_ip).await?;
+        return Ok(CodeOutcome::Rejected);
+    }
+
+    Ok(CodeOutcome::Accepted(method))
+}
+
+/// Replaces the user's recovery codes, returning the new ones in the clear for the only time
+pub async fn issue_recovery_codes(
+    repositories: &ApiRepositories,
+    user_id: i32,
+    agent: &str,
+) -> Result<Vec<String>, MfaServiceError> {
+    let codes = recovery_code::generate();
+    let code_hashes = codes
+        .iter()
+        .map(|code| token::digest(&recovery_code::normalize(code)))
+        .collect::<Vec<_>>();
+
+    repositories
+        .recovery_code
+        .replace(agent, user_id, &code_hashes)
+        .await?;
+
+    Ok(codes)
+}
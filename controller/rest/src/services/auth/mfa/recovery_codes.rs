@@ -0,0 +1,67 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::mfa::RecoveryCodes,
+    services::{
+        ApiServices,
+        auth::mfa::{CodeOutcome, check_code, confirm::TotpCodePayload, issue_recovery_codes},
+        core::throttle::retry_after_seconds,
+    },
+    util::{error::ApiError, request::RequestContext},
+};
+
+#[derive(ApiResponse)]
+pub enum RegenerateRecoveryCodesResponse {
+    #[oai(status = 200)]
+    Ok(Json<RecoveryCodes>),
+    #[oai(status = 400)]
+    InvalidCode,
+    #[oai(status = 404)]
+    NotEnrolled,
+    #[oai(status = 429)]
+    Locked(#[oai(header = "Retry-After")] u64),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// Replaces every recovery code, used or not, with a fresh set
+pub async fn regenerate_recovery_codes(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+    payload: TotpCodePayload,
+    context: RequestContext,
+    agent: &str,
+) -> RegenerateRecoveryCodesResponse {
+    let totp = match repositories.user_totp.by_user(user_id).await {
+        Ok(Some(totp)) if totp.is_confirmed() => totp,
+        Ok(_) => return RegenerateRecoveryCodesResponse::NotEnrolled,
+        Err(e) => return RegenerateRecoveryCodesResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match check_code(
+        &repositories,
+        &services,
+        &totp,
+        &payload.code,
+        &context,
+        agent,
+    )
+    .await
+    {
+        Ok(CodeOutcome::Accepted(_)) => {}
+        Ok(CodeOutcome::Rejected) => return RegenerateRecoveryCodesResponse::InvalidCode,
+        Ok(CodeOutcome::Locked(retry_after)) => {
+            return RegenerateRecoveryCodesResponse::Locked(retry_after_seconds(retry_after));
+        }
+        Err(e) => return RegenerateRecoveryCodesResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    match issue_recovery_codes(&repositories, user_id, agent).await {
+        Ok(recovery_codes) => {
+            RegenerateRecoveryCodesResponse::Ok(Json(RecoveryCodes { recovery_codes }))
+        }
+        Err(e) => RegenerateRecoveryCodesResponse::Failed(Json(ApiError::from(e))),
+    }
+}
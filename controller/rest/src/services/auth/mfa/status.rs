@@ -0,0 +1,26 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, models::mfa::MfaStatus, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum MfaStatusResponse {
+    #[oai(status = 200)]
+    Ok(Json<MfaStatus>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+pub async fn mfa_status(repositories: ApiRepositories, user_id: i32) -> MfaStatusResponse {
+    let totp_enabled = match repositories.user_totp.by_user(user_id).await {
+        Ok(totp) => totp.is_some_and(|totp| totp.is_confirmed()),
+        Err(e) => return MfaStatusResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match repositories.recovery_code.remaining(user_id).await {
+        Ok(recovery_codes_remaining) => MfaStatusResponse::Ok(Json(MfaStatus {
+            totp_enabled,
+            recovery_codes_remaining,
+        })),
+        Err(e) => MfaStatusResponse::Failed(Json(ApiError::from(e))),
+    }
+}
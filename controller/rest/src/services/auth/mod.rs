@@ -1 +1,11 @@
+pub mod email_verification;
+pub mod federation;
 pub mod login;
+pub mod logout;
+pub mod mfa;
+pub mod password;
+pub mod password_reset;
+pub mod personal_access_token;
+pub mod refresh;
+pub mod revalidate;
+pub mod session;
@@ -0,0 +1,104 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::{logout::LogoutAllResponsePayload, session::end_user_sessions},
+        core::{
+            jwt::UserClaims,
+            throttle::{ThrottleAccount, retry_after_seconds},
+        },
+    },
+    util::{error::ApiError, request::RequestContext},
+};
+
+#[derive(Object, Debug)]
+pub struct ChangePasswordPayload {
+    pub current_password: String,
+    #[oai(validator(min_length = 8))]
+    pub new_password: String,
+}
+
+#[derive(ApiResponse)]
+pub enum ChangePasswordResponse {
+    /// Every other session was logged out, the one making the change stays open
+    #[oai(status = 200)]
+    Ok(Json<LogoutAllResponsePayload>),
+    #[oai(status = 400)]
+    InvalidCredentials,
+    #[oai(status = 429)]
+    Locked(#[oai(header = "Retry-After")] u64),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.change_password", skip(repositories, services, claims, payload, context), fields(user_id = claims.user_id))]
+pub async fn change_password(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+    payload: ChangePasswordPayload,
+    context: RequestContext,
+) -> ChangePasswordResponse {
+    let user = match repositories.user.by_id(claims.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return ChangePasswordResponse::InvalidCredentials,
+        Err(e) => return ChangePasswordResponse::Failed(Json(ApiError::from(e))),
+    };
+    let account = ThrottleAccount::User(claims.user_id);
+    let source_ip = context.source_ip.as_deref();
+
+    // A stolen access token shouldn't be a way around the login lockout
+    match services.throttle.retry_after(account, source_ip).await {
+        Ok(Some(retry_after)) => {
+            return ChangePasswordResponse::Locked(retry_after_seconds(retry_after));
+        }
+        Ok(None) => {}
+        Err(e) => return ChangePasswordResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    if services
+        .hasher
+        .verify(&user.user.password, &payload.current_password)
+        .is_err()
+    {
+        tracing::warn!("Incorrect current password for user: {}", claims.user_id);
+        if let Err(e) = services.throttle.record_failure(account, source_ip).await {
+            tracing::error!("Failed to record login failure: {:?}", e);
+        }
+        return ChangePasswordResponse::InvalidCredentials;
+    }
+
+    let hash = match services.hasher.hash(&payload.new_password) {
+        Ok(hash) => hash,
+        Err(e) => return ChangePasswordResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let agent = &format!("auth.change_password:{}", claims.user_id);
+    if let Err(e) = repositories
+        .user
+        .set_password(agent, claims.user_id, &hash, false)
+        .await
+    {
+        return ChangePasswordResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    match end_user_sessions(
+        &repositories,
+        &services,
+        claims.user_id,
+        Some(&claims.session_id),
+        agent,
+    )
+    .await
+    {
+        Ok(revoked_sessions) => {
+            ChangePasswordResponse::Ok(Json(LogoutAllResponsePayload { revoked_sessions }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to end other sessions: {:?}", e);
+            ChangePasswordResponse::Failed(Json(ApiError::from(e)))
+        }
+    }
+}
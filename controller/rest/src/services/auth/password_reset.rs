@@ -0,0 +1,222 @@
+use chrono::Utc;
+use data::dto::user::UserDto;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::session::end_user_sessions,
+        core::{mailer::Mail, token},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct ForgotPasswordPayload {
+    pub username: String,
+}
+
+#[derive(ApiResponse)]
+pub enum ForgotPasswordResponse {
+    /// Returned whether or not the user exists, so this can't be used to discover accounts
+    #[oai(status = 202)]
+    Accepted,
+}
+
+#[derive(Object, Debug)]
+pub struct CompletePasswordResetPayload {
+    pub token: String,
+    #[oai(validator(min_length = 8))]
+    pub new_password: String,
+}
+
+#[derive(ApiResponse)]
+pub enum CompletePasswordResetResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// Unknown, expired or already used
+    #[oai(status = 400)]
+    InvalidToken,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+fn reset_mail(services: &ApiServices, to: &str, reset_token: &str) -> Mail {
+    let minutes = services.lifetimes.password_reset.num_minutes();
+    let instructions = match &services.password_reset_url {
+        Some(url) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!(
+                "Follow this link to choose a new password:\n\n{url}{separator}token={reset_token}"
+            )
+        }
+        None => format!("Use this token to choose a new password:\n\n{reset_token}"),
+    };
+
+    Mail {
+        to: to.to_string(),
+        subject: "Reset your password".into(),
+        body: format!(
+            "{instructions}\n\nIt expires in {minutes} minutes. If you didn't ask to reset your password you can ignore this message."
+        ),
+    }
+}
+
+/// Issues a reset token for `user` and mails it to them, invalidating any earlier token
+async fn issue_password_reset(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: &UserDto,
+) {
+    if !user.enabled {
+        tracing::warn!(
+            "Password reset requested for disabled user: {}",
+            user.user_id
+        );
+        return;
+    }
+    let Some(email) = &user.email else {
+        tracing::warn!(
+            "Password reset requested for user without an email: {}",
+            user.user_id
+        );
+        return;
+    };
+
+    // Bounds how many mails a stranger can make someone receive
+    match repositories
+        .password_reset_token
+        .latest_for_user(user.user_id)
+        .await
+    {
+        Ok(Some(latest)) if Utc::now() - latest.created_at < services.password_reset_cooldown => {
+            tracing::warn!(
+                "Password reset requested again too soon for user: {}",
+                user.user_id
+            );
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!("Failed to look up earlier reset tokens: {:?}", e);
+            return;
+        }
+    }
+
+    let agent = &format!("auth.forgot_password:{}", user.user_id);
+    if let Err(e) = repositories
+        .password_reset_token
+        .invalidate_user(agent, user.user_id)
+        .await
+    {
+        tracing::error!("Failed to invalidate earlier reset tokens: {:?}", e);
+        return;
+    }
+
+    let reset_token = token::generate();
+    if let Err(e) = repositories
+        .password_reset_token
+        .create(
+            agent,
+            user.user_id,
+            &token::digest(&reset_token),
+            Utc::now() + services.lifetimes.password_reset,
+        )
+        .await
+    {
+        tracing::error!("Failed to create reset token: {:?}", e);
+        return;
+    }
+
+    if let Err(e) = services
+        .mailer
+        .send(reset_mail(services, email, &reset_token))
+        .await
+    {
+        tracing::error!("Failed to send password reset mail: {:?}", e);
+    }
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.forgot_password", skip(repositories, services, payload), fields(username = %payload.username))]
+pub async fn forgot_password(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: ForgotPasswordPayload,
+) -> ForgotPasswordResponse {
+    // Finished in the background so the response time doesn't give away whether the user exists
+    tokio::spawn(async move {
+        match repositories.user.by_username(&payload.username).await {
+            Ok(Some(user)) => issue_password_reset(&repositories, &services, &user.user).await,
+            Ok(None) => {}
+            Err(e) => tracing::error!("Database error during forgot password: {:?}", e),
+        }
+    });
+
+    ForgotPasswordResponse::Accepted
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.complete_password_reset", skip(repositories, services, payload))]
+pub async fn complete_password_reset(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: CompletePasswordResetPayload,
+) -> CompletePasswordResetResponse {
+    let reset = match repositories
+        .password_reset_token
+        .by_hash(&token::digest(&payload.token))
+        .await
+    {
+        Ok(Some(reset)) if reset.is_usable() => reset,
+        Ok(_) => return CompletePasswordResetResponse::InvalidToken,
+        Err(e) => return CompletePasswordResetResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let user = match repositories.user.by_id(reset.user_id).await {
+        Ok(Some(user)) if user.user.enabled => user,
+        Ok(_) => return CompletePasswordResetResponse::InvalidToken,
+        Err(e) => return CompletePasswordResetResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let agent = &format!("auth.reset_password:{}", reset.user_id);
+    match repositories
+        .password_reset_token
+        .consume(agent, reset.password_reset_token_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return CompletePasswordResetResponse::InvalidToken,
+        Err(e) => return CompletePasswordResetResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    let hash = match services.hasher.hash(&payload.new_password) {
+        Ok(hash) => hash,
+        Err(e) => return CompletePasswordResetResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    if let Err(e) = repositories
+        .user
+        .set_password(agent, reset.user_id, &hash, false)
+        .await
+    {
+        return CompletePasswordResetResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    // Whoever needed a reset may not have been the only one holding the old password
+    if let Err(e) = end_user_sessions(&repositories, &services, reset.user_id, None, agent).await {
+        return CompletePasswordResetResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    if let Err(e) = repositories
+        .password_reset_token
+        .invalidate_user(agent, reset.user_id)
+        .await
+    {
+        tracing::error!("Failed to invalidate remaining reset tokens: {:?}", e);
+    }
+    if let Err(e) = services.throttle.unlock(user.user.user_id).await {
+        tracing::error!("Failed to clear lockout after password reset: {:?}", e);
+    }
+
+    CompletePasswordResetResponse::Ok
+}
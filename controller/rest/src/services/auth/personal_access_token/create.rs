@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::personal_access_token::{CreatedPersonalAccessToken, PersonalAccessToken},
+    services::{
+        auth::personal_access_token::PREFIX,
+        core::{jwt::UserClaims, token},
+    },
+    util::{
+        error::ApiError,
+        grant_pattern::{GrantPattern, covered},
+    },
+};
+
+#[derive(Object, Debug)]
+pub struct CreatePersonalAccessTokenPayload {
+    #[oai(validator(min_length = 1, max_length = 255))]
+    pub name: String,
+    /// Must be grants the user currently holds, or patterns their grants cover
+    pub grants: Vec<String>,
+    /// Never expires when left out
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(ApiResponse)]
+pub enum CreatePersonalAccessTokenResponse {
+    #[oai(status = 200)]
+    Ok(Json<CreatedPersonalAccessToken>),
+    /// Asks for a grant the user doesn't hold, or expires in the past
+    #[oai(status = 400)]
+    Invalid,
+    /// Personal access tokens and OAuth tokens can't mint more tokens
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.create_personal_access_token", skip(repositories, claims, payload), fields(user_id = claims.user_id))]
+pub async fn create_personal_access_token(
+    repositories: ApiRepositories,
+    claims: UserClaims,
+    payload: CreatePersonalAccessTokenPayload,
+) -> CreatePersonalAccessTokenResponse {
+    // Otherwise a leaked token could be traded for one that never expires
+    if !claims.is_first_party() {
+        return CreatePersonalAccessTokenResponse::Forbidden;
+    }
+
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return CreatePersonalAccessTokenResponse::Invalid;
+    }
+
+    let user = match repositories.user.by_id(claims.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return CreatePersonalAccessTokenResponse::Invalid,
+        Err(e) => return CreatePersonalAccessTokenResponse::Failed(Json(ApiError::from(e))),
+    };
+    // Patterns are allowed, as long as one of the user's own grants covers them
+    let held = user
+        .effective_grants()
+        .iter()
+        .map(|grant| GrantPattern::lenient(&grant.grant_id))
+        .collect::<Vec<_>>();
+    let valid =
+        |grant_id: &String| grant_id.parse::<GrantPattern>().is_ok() && covered(&held, grant_id);
+    if !payload.grants.iter().all(valid) {
+        return CreatePersonalAccessTokenResponse::Invalid;
+    }
+
+    let token = format!("{PREFIX}{}", token::generate());
+    let agent = &format!("auth.personal_access_token.create:{}", claims.user_id);
+    match repositories
+        .personal_access_token
+        .create(
+            agent,
+            claims.user_id,
+            &payload.name,
+            &token::digest(&token),
+            &payload.grants,
+            payload.expires_at,
+        )
+        .await
+    {
+        Ok(created) => CreatePersonalAccessTokenResponse::Ok(Json(CreatedPersonalAccessToken {
+            token,
+            personal_access_token: PersonalAccessToken::from(created),
+        })),
+        Err(e) => CreatePersonalAccessTokenResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,25 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories, models::personal_access_token::PersonalAccessToken, util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum ListPersonalAccessTokensResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<PersonalAccessToken>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+pub async fn list_personal_access_tokens(
+    repositories: ApiRepositories,
+    user_id: i32,
+) -> ListPersonalAccessTokensResponse {
+    match repositories.personal_access_token.by_user(user_id).await {
+        Ok(tokens) => ListPersonalAccessTokensResponse::Ok(Json(
+            tokens.into_iter().map(PersonalAccessToken::from).collect(),
+        )),
+        Err(e) => ListPersonalAccessTokensResponse::Failed(Json(ApiError::from(e))),
+    }
+}
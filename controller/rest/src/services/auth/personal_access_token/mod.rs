@@ -0,0 +1,77 @@
+use data::repository::{personal_access_token::PersonalAccessTokenError, user::UserError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    api::ApiRepositories,
+    models::personal_access_token::PersonalAccessToken,
+    services::{
+        ApiServices,
+        core::{jwt::Claims, token},
+    },
+};
+
+pub mod create;
+pub mod list;
+pub mod revoke;
+
+/// Tells personal access tokens apart from JWTs, which never start with it
+pub const PREFIX: &str = "pat_";
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum PersonalAccessTokenServiceError {
+    #[error(transparent)]
+    PersonalAccessToken {
+        #[from]
+        inner_error: PersonalAccessTokenError,
+    },
+    #[error(transparent)]
+    User {
+        #[from]
+        inner_error: UserError,
+    },
+}
+
+pub fn is_personal_access_token(bearer: &str) -> bool {
+    bearer.starts_with(PREFIX)
+}
+
+/// Claims for a request made with `bearer`, `None` unless the token is live and its user
+/// enabled. Built fresh on every request, so grants the user loses are dropped immediately.
+pub async fn authenticate(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    bearer: &str,
+) -> Result<Option<Claims>, PersonalAccessTokenServiceError> {
+    let Some(token) = repositories
+        .personal_access_token
+        .by_hash(&token::digest(bearer))
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    if !token.is_usable() {
+        return Ok(None);
+    }
+
+    let user = match repositories.user.by_id(token.user_id).await? {
+        Some(user) if user.user.enabled => user,
+        _ => return Ok(None),
+    };
+
+    if let Err(e) = repositories
+        .personal_access_token
+        .touch(token.personal_access_token_id)
+        .await
+    {
+        tracing::error!("Failed to record personal access token use: {:?}", e);
+    }
+
+    Ok(Some(Claims::for_personal_access_token(
+        &user,
+        &PersonalAccessToken::from(token),
+        services.lifetimes.access_token,
+    )))
+}
@@ -0,0 +1,31 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum RevokePersonalAccessTokenResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// No such token, it belongs to someone else, or it was already revoked
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+pub async fn revoke_personal_access_token(
+    repositories: ApiRepositories,
+    user_id: i32,
+    personal_access_token_id: i32,
+    agent: &str,
+) -> RevokePersonalAccessTokenResponse {
+    match repositories
+        .personal_access_token
+        .revoke(agent, user_id, personal_access_token_id)
+        .await
+    {
+        Ok(true) => RevokePersonalAccessTokenResponse::Ok,
+        Ok(false) => RevokePersonalAccessTokenResponse::NotFound,
+        Err(e) => RevokePersonalAccessTokenResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,166 @@
+use data::repository::refresh_token::RefreshTokenError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::session::{IssuedTokens, SessionServiceError, continue_session, end_session},
+        core::{jwt::Claims, token},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct RefreshPayload {
+    pub refresh_token: String,
+}
+
+#[derive(Object, Debug)]
+pub struct RefreshResponsePayload {
+    pub claims: Claims,
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(ApiResponse)]
+pub enum RefreshResponse {
+    #[oai(status = 200)]
+    Ok(Json<RefreshResponsePayload>),
+    #[oai(status = 401)]
+    InvalidToken,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+async fn revoke_family(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    family_id: &str,
+    user_id: i32,
+) {
+    let agent = &format!("auth.refresh.reuse:{user_id}");
+    match end_session(repositories, services, family_id, agent).await {
+        Ok(_) => tracing::warn!("Revoked session and refresh token family after reuse"),
+        Err(e) => tracing::error!("Failed to revoke refresh token family: {:?}", e),
+    }
+}
+
+#[tracing::instrument(level = tracing::Level::INFO, "services.auth.refresh", skip(repositories, services, payload))]
+pub async fn refresh(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: RefreshPayload,
+) -> RefreshResponse {
+    match refresh_tokens(&repositories, &services, &payload.refresh_token, None).await {
+        Ok(IssuedTokens {
+            claims,
+            token,
+            refresh_token,
+        }) => RefreshResponse::Ok(Json(RefreshResponsePayload {
+            claims,
+            token,
+            refresh_token,
+        })),
+        Err(response) => response,
+    }
+}
+
+/// Rotates `refresh_token` within its session. `client_id` is the OAuth client the session
+/// must have been opened for, `None` for sessions opened by a first party login.
+pub async fn refresh_tokens(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    refresh_token: &str,
+    client_id: Option<&str>,
+) -> Result<IssuedTokens, RefreshResponse> {
+    let previous = match repositories
+        .refresh_token
+        .by_hash(&token::digest(refresh_token))
+        .await
+    {
+        Ok(Some(previous)) => previous,
+        Ok(None) => return Err(RefreshResponse::InvalidToken),
+        Err(e) => {
+            tracing::error!("Database error during refresh: {:?}", e);
+            return Err(RefreshResponse::Failed(Json(ApiError::from(e))));
+        }
+    };
+
+    if previous.revoked_at.is_some() {
+        tracing::warn!(
+            "Revoked refresh token presented for user: {}",
+            previous.user_id
+        );
+        return Err(RefreshResponse::InvalidToken);
+    }
+
+    // A refresh token is single use, seeing one again means it was leaked. We can't tell
+    // which party is legitimate, so the whole family goes
+    if previous.used_at.is_some() {
+        tracing::warn!(
+            "Refresh token reuse detected for user: {}",
+            previous.user_id
+        );
+        revoke_family(
+            repositories,
+            services,
+            &previous.family_id,
+            previous.user_id,
+        )
+        .await;
+        return Err(RefreshResponse::InvalidToken);
+    }
+
+    if previous.is_expired() {
+        return Err(RefreshResponse::InvalidToken);
+    }
+
+    // A client can only refresh its own sessions, and first party sessions stay first party
+    match repositories.session.by_id(&previous.family_id).await {
+        Ok(Some(session)) if session.application_id.as_deref() == client_id => {}
+        Ok(_) => return Err(RefreshResponse::InvalidToken),
+        Err(e) => {
+            tracing::error!("Database error during refresh: {:?}", e);
+            return Err(RefreshResponse::Failed(Json(ApiError::from(e))));
+        }
+    }
+
+    let user = match repositories.user.by_id(previous.user_id).await {
+        Ok(Some(user)) if user.user.enabled => user,
+        Ok(Some(_)) => {
+            tracing::warn!("Refresh attempt for disabled user: {}", previous.user_id);
+            return Err(RefreshResponse::InvalidToken);
+        }
+        Ok(None) => return Err(RefreshResponse::InvalidToken),
+        Err(e) => {
+            tracing::error!("Database error during refresh: {:?}", e);
+            return Err(RefreshResponse::Failed(Json(ApiError::from(e))));
+        }
+    };
+
+    let agent = &format!("auth.refresh:{}", user.user.user_id);
+    match continue_session(repositories, services, &user, &previous, agent).await {
+        Ok(issued) => Ok(issued),
+        Err(SessionServiceError::RefreshToken {
+            inner_error: RefreshTokenError::TokenAlreadyUsed { .. },
+        }) => {
+            tracing::warn!(
+                "Concurrent refresh token reuse for user: {}",
+                user.user.user_id
+            );
+            revoke_family(
+                repositories,
+                services,
+                &previous.family_id,
+                user.user.user_id,
+            )
+            .await;
+            Err(RefreshResponse::InvalidToken)
+        }
+        Err(e) => {
+            tracing::error!("Failed to rotate refresh token: {:?}", e);
+            Err(RefreshResponse::Failed(Json(ApiError::from(e))))
+        }
+    }
+}
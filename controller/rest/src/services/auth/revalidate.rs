@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+
+use data::repository::{application::ApplicationError, user::UserError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    api::ApiRepositories,
+    services::core::jwt::{Claims, SubjectType},
+    util::grant_pattern::{GrantPattern, covered},
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum RevalidateError {
+    #[error(transparent)]
+    User {
+        #[from]
+        inner_error: UserError,
+    },
+    #[error(transparent)]
+    Application {
+        #[from]
+        inner_error: ApplicationError,
+    },
+}
+
+/// Re-checks `claims` against the subject's current state. `None` if the user has since been
+/// deleted or disabled, or the client deleted or stripped of its secret, otherwise the claims
+/// narrowed to the grants the subject still holds. Grants, and `email_verified`, are only ever
+/// removed here, never added.
+pub async fn revalidate(
+    repositories: &ApiRepositories,
+    claims: Claims,
+) -> Result<Option<Claims>, RevalidateError> {
+    match claims.subject_type {
+        SubjectType::User => revalidate_user(repositories, claims).await,
+        SubjectType::Client => revalidate_client(repositories, claims).await,
+    }
+}
+
+async fn revalidate_user(
+    repositories: &ApiRepositories,
+    mut claims: Claims,
+) -> Result<Option<Claims>, RevalidateError> {
+    let Some(user_id) = claims.user_id() else {
+        return Ok(None);
+    };
+    let Some(user) = repositories.user.by_id(user_id).await? else {
+        return Ok(None);
+    };
+
+    if !user.user.enabled {
+        return Ok(None);
+    }
+
+    let effective = user.effective_grants();
+    let current = effective
+        .iter()
+        .map(|grant| GrantPattern::lenient(&grant.grant_id))
+        .collect::<Vec<_>>();
+    claims.grants.retain(|grant_id| covered(&current, grant_id));
+
+    let apps = retained_apps(
+        &claims.grants,
+        effective
+            .iter()
+            .map(|grant| (grant.grant_id.as_str(), grant.application_id.as_str())),
+    );
+    claims.apps.retain(|app| apps.contains(app.as_str()));
+
+    claims.email_verified &= user.user.is_email_verified();
+
+    Ok(Some(claims))
+}
+
+async fn revalidate_client(
+    repositories: &ApiRepositories,
+    mut claims: Claims,
+) -> Result<Option<Claims>, RevalidateError> {
+    let Some(application) = repositories.application.by_id(&claims.subject).await? else {
+        return Ok(None);
+    };
+
+    // Removing the secret is how a client is shut out
+    if application.application.client_secret.is_none() {
+        return Ok(None);
+    }
+
+    let enabled = application
+        .client_grants
+        .iter()
+        .filter(|grant| grant.client_grant.enabled)
+        .collect::<Vec<_>>();
+    let current = enabled
+        .iter()
+        .map(|grant| GrantPattern::lenient(&grant.grant.grant_id))
+        .collect::<Vec<_>>();
+    claims.grants.retain(|grant_id| covered(&current, grant_id));
+
+    let apps = retained_apps(
+        &claims.grants,
+        enabled.iter().map(|grant| {
+            (
+                grant.grant.grant_id.as_str(),
+                grant.grant.application_id.as_str(),
+            )
+        }),
+    );
+    claims.apps.retain(|app| apps.contains(app.as_str()));
+
+    Ok(Some(claims))
+}
+
+/// The applications of the current grants that still overlap a grant left in the claims,
+/// either way round as the claims may hold a compacted pattern or a narrower grant
+fn retained_apps<'a>(
+    grants: &[String],
+    current: impl Iterator<Item = (&'a str, &'a str)>,
+) -> HashSet<&'a str> {
+    let kept = grants
+        .iter()
+        .map(|grant_id| GrantPattern::lenient(grant_id))
+        .collect::<Vec<_>>();
+
+    current
+        .filter(|(grant_id, _)| {
+            let it = GrantPattern::lenient(grant_id);
+            kept.iter().any(|kept| kept.covers(&it) || it.covers(kept))
+        })
+        .map(|(_, application_id)| application_id)
+        .collect()
+}
@@ -0,0 +1,219 @@
+use chrono::Utc;
+use data::{
+    dto::{refresh_token::RefreshTokenDto, user::UserDetailDto},
+    repository::{grant::GrantError, refresh_token::RefreshTokenError, session::SessionError},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        core::{
+            jwt::{Claims, JwtError},
+            token,
+        },
+    },
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum SessionServiceError {
+    #[error(transparent)]
+    Session {
+        #[from]
+        inner_error: SessionError,
+    },
+    #[error(transparent)]
+    RefreshToken {
+        #[from]
+        inner_error: RefreshTokenError,
+    },
+    #[error(transparent)]
+    Grant {
+        #[from]
+        inner_error: GrantError,
+    },
+    #[error(transparent)]
+    Jwt {
+        #[from]
+        inner_error: JwtError,
+    },
+}
+
+#[derive(Debug)]
+pub struct IssuedTokens {
+    pub claims: Claims,
+    pub token: String,
+    pub refresh_token: String,
+}
+
+/// Opens a new session for `user`, the refresh token family shares the session's id.
+/// `amr` is carried into every access token issued within the session, as are `client_id`
+/// and `scope` for sessions opened through OAuth.
+pub async fn start_session(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: &UserDetailDto,
+    amr: &[String],
+    client_id: Option<&str>,
+    scope: &[String],
+    agent: &str,
+) -> Result<IssuedTokens, SessionServiceError> {
+    let session_id = token::generate();
+    let implications = services.implications.get(repositories).await?;
+    let claims = Claims::r#for(
+        user,
+        &implications,
+        &session_id,
+        amr,
+        client_id,
+        scope,
+        services.lifetimes.access_token,
+    );
+    let expires_at = Utc::now() + services.lifetimes.refresh_token;
+
+    repositories
+        .session
+        .create(
+            agent,
+            &session_id,
+            user.user.user_id,
+            &claims.token_id,
+            amr,
+            client_id,
+            scope,
+            expires_at,
+        )
+        .await?;
+
+    let refresh_token = token::generate();
+    repositories
+        .refresh_token
+        .create(
+            agent,
+            user.user.user_id,
+            &session_id,
+            &token::digest(&refresh_token),
+            expires_at,
+        )
+        .await?;
+
+    let token = services.jwt.sign(&claims)?;
+
+    Ok(IssuedTokens {
+        claims,
+        token,
+        refresh_token,
+    })
+}
+
+/// Rotates `previous` and issues a fresh access token within the same session
+pub async fn continue_session(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: &UserDetailDto,
+    previous: &RefreshTokenDto,
+    agent: &str,
+) -> Result<IssuedTokens, SessionServiceError> {
+    let expires_at = Utc::now() + services.lifetimes.refresh_token;
+
+    let refresh_token = token::generate();
+    repositories
+        .refresh_token
+        .rotate(agent, previous, &token::digest(&refresh_token), expires_at)
+        .await?;
+
+    let session = repositories
+        .session
+        .by_id(&previous.family_id)
+        .await?
+        .ok_or(SessionError::SessionNotFound {
+            session_id: previous.family_id.clone(),
+        })?;
+    let implications = services.implications.get(repositories).await?;
+    let claims = Claims::r#for(
+        user,
+        &implications,
+        &previous.family_id,
+        &session.amr,
+        session.application_id.as_deref(),
+        &session.scope,
+        services.lifetimes.access_token,
+    );
+
+    repositories
+        .session
+        .record_token(agent, &previous.family_id, &claims.token_id, expires_at)
+        .await?;
+
+    let token = services.jwt.sign(&claims)?;
+
+    Ok(IssuedTokens {
+        claims,
+        token,
+        refresh_token,
+    })
+}
+
+/// Revokes a session along with its refresh tokens
+pub async fn end_session(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    session_id: &str,
+    agent: &str,
+) -> Result<(), SessionServiceError> {
+    repositories.session.revoke(agent, session_id).await?;
+    repositories
+        .refresh_token
+        .revoke_family(agent, session_id)
+        .await?;
+    services.revocations.insert(session_id, true);
+
+    Ok(())
+}
+
+/// Revokes every session belonging to `user_id`, other than `except`, returning how many were
+/// revoked
+pub async fn end_user_sessions(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user_id: i32,
+    except: Option<&str>,
+    agent: &str,
+) -> Result<usize, SessionServiceError> {
+    let session_ids = repositories
+        .session
+        .revoke_user(agent, user_id, except)
+        .await?;
+    repositories
+        .refresh_token
+        .revoke_user(agent, user_id, except)
+        .await?;
+
+    for session_id in &session_ids {
+        services.revocations.insert(session_id, true);
+    }
+
+    Ok(session_ids.len())
+}
+
+pub async fn is_session_revoked(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    session_id: &str,
+) -> Result<bool, SessionServiceError> {
+    if let Some(revoked) = services.revocations.get(session_id) {
+        return Ok(revoked);
+    }
+
+    let revoked = match repositories.session.by_id(session_id).await? {
+        Some(session) => !session.is_active(),
+        None => true,
+    };
+
+    services.revocations.insert(session_id, revoked);
+
+    Ok(revoked)
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use crate::api::ApiRepositories;
+
+/// Recorded as `updated_by` on every grant the sweeper disables
+const AGENT: &str = "user.expire_grants:grant_sweeper";
+
+/// Disables user grants past their `valid_until` every `interval`, for as long as the process
+/// runs. Tokens already leave expired grants out, this keeps the stored state in line with them.
+pub fn spawn(repositories: ApiRepositories, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            match repositories.user.expire_grants(AGENT).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Disabled {count} expired user grants"),
+                Err(e) => tracing::error!("Failed to disable expired user grants: {:?}", e),
+            }
+        }
+    });
+}
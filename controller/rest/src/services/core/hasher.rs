@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use argon2::{
-    Argon2, PasswordHash, PasswordVerifier,
+    Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version,
     password_hash::{PasswordHasher, SaltString, rand_core::OsRng},
 };
 use liberror::AnyError;
@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use valuable::Valuable;
 
+use crate::Args;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Valuable, Error)]
 pub enum HasherError {
     #[error("Unsupported algorithm")]
@@ -87,47 +89,127 @@ impl From<argon2::password_hash::Error> for HasherError {
         }
     }
 }
+impl From<argon2::Error> for HasherError {
+    fn from(value: argon2::Error) -> Self {
+        Self::from(argon2::password_hash::Error::from(value))
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Hasher {
     dummy_hash: Arc<String>,
-    salt: Arc<SaltString>,
+    params: Params,
 }
 
 impl Hasher {
-    pub fn new() -> Result<Self, HasherError> {
+    pub fn new(params: Params) -> Result<Self, HasherError> {
         let mut rng = rand::rng();
         let dummy_password_len = 18;
         let dummy_password = (0..dummy_password_len)
             .map(|_| rng.random::<char>())
             .collect::<String>();
 
-        let salt = SaltString::generate(&mut OsRng);
+        let mut this = Self {
+            dummy_hash: Arc::new(String::new()),
+            params,
+        };
+        this.dummy_hash = Arc::new(this.hash(&dummy_password)?);
 
-        let argon = Argon2::default();
-        let dummy_hash = argon.hash_password(dummy_password.as_bytes(), salt.as_salt())?;
+        Ok(this)
+    }
+
+    pub fn from_args(args: &Args) -> Result<Self, HasherError> {
+        let params = Params::new(
+            args.argon2_memory_kib,
+            args.argon2_iterations,
+            args.argon2_parallelism,
+            None,
+        )?;
+
+        Self::new(params)
+    }
 
-        Ok(Self {
-            dummy_hash: Arc::new(dummy_hash.to_string()),
-            salt: Arc::new(salt),
-        })
+    fn argon(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
     }
 
     pub fn dummy_verification(&self, password: &str) {
         let _ = self.verify(&self.dummy_hash, password);
     }
 
+    /// Hashes with a freshly generated salt, so no two hashes share one
     pub fn hash(&self, password: &str) -> Result<String, HasherError> {
-        let argon = Argon2::default();
-        let salt = self.salt.as_salt();
-        let hash = argon.hash_password(password.as_bytes(), salt)?;
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self.argon().hash_password(password.as_bytes(), &salt)?;
         Ok(hash.to_string())
     }
 
     pub fn verify(&self, hash: &str, password: &str) -> Result<(), HasherError> {
+        // Verification takes its parameters from the PHC string, not from `self.params`
         let argon = Argon2::default();
         let hash = PasswordHash::new(hash)?;
         argon.verify_password(password.as_bytes(), &hash)?;
         Ok(())
     }
+
+    /// The salt segment of a PHC string
+    pub fn salt_of(hash: &str) -> Result<String, HasherError> {
+        let hash = PasswordHash::new(hash)?;
+        hash.salt
+            .map(|salt| salt.as_str().to_string())
+            .ok_or(HasherError::PhcStringField)
+    }
+
+    /// Whether `hash` was produced with anything other than our current algorithm and parameters
+    pub fn needs_rehash(&self, hash: &str) -> Result<bool, HasherError> {
+        let hash = PasswordHash::new(hash)?;
+
+        if hash.algorithm != Algorithm::Argon2id.ident() {
+            return Ok(true);
+        }
+        if hash.version != Some(Version::V0x13.into()) {
+            return Ok(true);
+        }
+
+        let params = Params::try_from(&hash)?;
+        Ok(params.m_cost() != self.params.m_cost()
+            || params.t_cost() != self.params.t_cost()
+            || params.p_cost() != self.params.p_cost())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use argon2::Params;
+
+    use crate::services::core::hasher::Hasher;
+
+    fn hasher(m_cost: u32, t_cost: u32) -> Hasher {
+        Hasher::new(Params::new(m_cost, t_cost, 1, None).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_hash_uses_unique_salts() {
+        let hasher = hasher(1024, 1);
+
+        let a = hasher.hash("hunter2").unwrap();
+        let b = hasher.hash("hunter2").unwrap();
+
+        assert_ne!(a, b);
+        assert_ne!(Hasher::salt_of(&a).unwrap(), Hasher::salt_of(&b).unwrap());
+        assert!(hasher.verify(&a, "hunter2").is_ok());
+        assert!(hasher.verify(&b, "hunter2").is_ok());
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let old = hasher(1024, 1);
+        let new = hasher(2048, 1);
+
+        let hash = old.hash("hunter2").unwrap();
+
+        assert_eq!(false, old.needs_rehash(&hash).unwrap());
+        assert_eq!(true, new.needs_rehash(&hash).unwrap());
+        assert!(new.verify(&hash, "hunter2").is_ok());
+    }
 }
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use data::repository::grant::GrantError;
+
+use crate::{
+    api::ApiRepositories,
+    util::grant_pattern::{GrantPattern, covered},
+};
+
+/// The grants each grant implies directly, each mapped to its application
+#[derive(Debug, Clone, Default)]
+pub struct Implications {
+    edges: HashMap<String, Vec<(String, String)>>,
+}
+impl Implications {
+    /// From `(grant_id, implied_grant_id, application_id)` triples, the application being the
+    /// implied grant's
+    pub fn new(edges: impl IntoIterator<Item = (String, String, String)>) -> Self {
+        let mut this = Self::default();
+        for (grant_id, implied_grant_id, application_id) in edges {
+            this.edges
+                .entry(grant_id)
+                .or_default()
+                .push((implied_grant_id, application_id));
+        }
+
+        this
+    }
+
+    /// Every grant `grant_ids` imply, however indirectly, mapped to its application. A held
+    /// pattern implies whatever the grants it matches imply.
+    pub fn expand<'a>(
+        &self,
+        grant_ids: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, String> {
+        let mut held = grant_ids
+            .into_iter()
+            .map(GrantPattern::lenient)
+            .collect::<Vec<_>>();
+        let mut implied = HashMap::new();
+
+        // Runs until nothing new turns up, which a cycle can't prevent as `implied` only grows
+        loop {
+            let before = implied.len();
+
+            for (grant_id, targets) in &self.edges {
+                if !covered(&held, grant_id) {
+                    continue;
+                }
+
+                for (implied_grant_id, application_id) in targets {
+                    if !implied.contains_key(implied_grant_id) {
+                        implied.insert(implied_grant_id.clone(), application_id.clone());
+                        held.push(GrantPattern::lenient(implied_grant_id));
+                    }
+                }
+            }
+
+            if implied.len() == before {
+                return implied;
+            }
+        }
+    }
+}
+
+/// Short-lived, in-process memo of the implication graph so `BearerPrincipal` doesn't load it
+/// on every request. Changes made by this instance are visible immediately, changes made by
+/// other instances are visible once the memo expires.
+#[derive(Clone, Debug)]
+pub struct ImplicationCache {
+    ttl: Duration,
+    entry: Arc<RwLock<Option<(Arc<Implications>, Instant)>>>,
+}
+impl ImplicationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entry: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn get(
+        &self,
+        repositories: &ApiRepositories,
+    ) -> Result<Arc<Implications>, GrantError> {
+        if let Some(it) = self.fresh() {
+            return Ok(it);
+        }
+
+        let them = repositories.grant.implications().await?;
+        let it = Arc::new(Implications::new(them.into_iter().map(|it| {
+            (
+                it.implication.grant_id,
+                it.implication.implied_grant_id,
+                it.implied.application_id,
+            )
+        })));
+
+        if let Ok(mut entry) = self.entry.write() {
+            *entry = Some((it.clone(), Instant::now()));
+        }
+
+        Ok(it)
+    }
+
+    /// Drops the memo, for after this instance changed an implication
+    pub fn invalidate(&self) {
+        if let Ok(mut entry) = self.entry.write() {
+            *entry = None;
+        }
+    }
+
+    fn fresh(&self) -> Option<Arc<Implications>> {
+        let entry = self.entry.read().ok()?;
+        let (it, cached_at) = entry.as_ref()?;
+        if cached_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(it.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::core::implication::Implications;
+
+    fn implications(edges: &[(&str, &str)]) -> Implications {
+        Implications::new(
+            edges
+                .iter()
+                .map(|(from, to)| (from.to_string(), to.to_string(), "app".to_string())),
+        )
+    }
+
+    fn sorted(it: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut it = it.into_iter().collect::<Vec<_>>();
+        it.sort();
+        it
+    }
+
+    #[test]
+    fn test_expand_transitive() {
+        let it = implications(&[
+            ("a.manage", "a.get"),
+            ("a.manage", "a.edit"),
+            ("a.edit", "a.list"),
+        ]);
+
+        assert_eq!(
+            vec!["a.edit", "a.get", "a.list"],
+            sorted(it.expand(["a.manage"]).into_keys())
+        );
+        assert_eq!(vec!["a.list"], sorted(it.expand(["a.edit"]).into_keys()));
+        assert!(it.expand(["a.get"]).is_empty());
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let it = implications(&[("a.manage", "b.get"), ("b.get", "c.get"), ("b.*", "d.get")]);
+
+        // A held pattern implies what its matches imply, holding a match doesn't imply the pattern
+        assert_eq!(
+            vec!["b.get", "c.get"],
+            sorted(it.expand(["a.*"]).into_keys())
+        );
+    }
+
+    #[test]
+    fn test_expand_cycle() {
+        let it = implications(&[("a", "b"), ("b", "a")]);
+
+        assert_eq!(vec!["a", "b"], sorted(it.expand(["a"]).into_keys()));
+    }
+}
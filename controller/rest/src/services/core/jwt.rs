@@ -1,22 +1,66 @@
-use std::{collections::HashSet, fmt::Debug};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    ops::Deref,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 
-use chrono::Utc;
-use hmac::Hmac;
-use jwt::{SignWithKey, VerifyWithKey};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use data::dto::user::UserDetailDto;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+    jwk::{Jwk, JwkSet, PublicKeyUse},
+};
 use liberror::AnyError;
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, digest::KeyInit};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use valuable::Valuable;
 
-use crate::models::user::User;
+use crate::{
+    Args,
+    models::{application::Application, personal_access_token::PersonalAccessToken},
+    services::core::{implication::Implications, token},
+    util::{
+        grant_pattern::{GrantPattern, compact, covered},
+        grants::token_expiry,
+    },
+};
 
-#[derive(Serialize, Deserialize, Debug, Valuable, Object)]
+/// What a token's `sub` names
+#[derive(Serialize, Deserialize, Enum, Debug, Clone, Copy, Default, PartialEq, Eq, Valuable)]
+#[oai(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectType {
+    /// `sub` is a user id
+    #[default]
+    User,
+    /// `sub` is the client id of an application acting on its own behalf
+    Client,
+}
+
+/// Who is really behind an impersonation token, RFC 8693 section 4.1
+#[derive(Serialize, Deserialize, Debug, Clone, Valuable, Object)]
+pub struct Actor {
+    /// The impersonating user's id
+    #[oai(rename = "sub")]
+    #[serde(rename = "sub")]
+    pub subject: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Valuable, Object)]
 pub struct Claims {
+    /// The user id for user subjects, the client id for client subjects
     #[oai(rename = "sub")]
     #[serde(rename = "sub")]
-    pub user_id: i32,
+    pub subject: String,
+    #[oai(rename = "sub_type")]
+    #[serde(rename = "sub_type", default)]
+    pub subject_type: SubjectType,
     #[oai(rename = "iss")]
     #[serde(rename = "iss")]
     pub issuer: String,
@@ -32,50 +76,548 @@ pub struct Claims {
     #[oai(rename = "exp")]
     #[serde(rename = "exp")]
     pub expires: u64,
+    #[oai(rename = "jti")]
+    #[serde(rename = "jti")]
+    pub token_id: String,
+    /// Client subjects have no session, their `sid` repeats the `jti`
+    #[oai(rename = "sid")]
+    #[serde(rename = "sid")]
+    pub session_id: String,
+    /// Authentication methods used to open the session, RFC 8176 values such as `pwd` and `otp`
+    #[serde(default)]
+    pub amr: Vec<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// The OAuth client the token was issued to, its grants are limited to that client's
+    /// namespace. `None` for first party logins.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Space separated OAuth scopes, RFC 9068. `None` for first party logins.
+    #[oai(skip_serializing_if_is_none)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Set when the request was made with a personal access token rather than a JWT, such
+    /// claims are built per request and never signed
+    #[oai(rename = "pat", skip_serializing_if_is_none)]
+    #[serde(rename = "pat", default, skip_serializing_if = "Option::is_none")]
+    pub personal_access_token_id: Option<i32>,
+    /// Set on tokens minted by an admin impersonating the subject
+    #[oai(rename = "act", skip_serializing_if_is_none)]
+    #[serde(rename = "act", default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Actor>,
 }
 impl Claims {
-    pub fn r#for(user: &User) -> Self {
+    pub fn r#for(
+        user: &UserDetailDto,
+        implications: &Implications,
+        session_id: &str,
+        amr: &[String],
+        client_id: Option<&str>,
+        scope: &[String],
+        lifetime: chrono::Duration,
+    ) -> Self {
+        let in_scope = |application_id: &str| client_id.is_none_or(|it| it == application_id);
+        let grants = user
+            .effective_grants()
+            .into_iter()
+            .filter(|grant| in_scope(&grant.application_id))
+            .map(|grant| (grant.grant_id.as_str(), grant.application_id.as_str()))
+            .collect::<HashMap<_, _>>();
+        let expires = token_expiry(user, in_scope, Utc::now(), lifetime);
+
         Self {
-            user_id: user.user_id,
+            subject: user.user.user_id.to_string(),
+            subject_type: SubjectType::User,
             issuer: crate::PRODUCT_IDENTIFIER.to_string(),
-            grants: user
-                .grants
+            grants: compact(grants.keys().copied()),
+            apps: grants
                 .values()
-                .map(|v| &v.grant_id)
-                .cloned()
+                .map(|it| it.to_string())
                 // This is stupid
                 .collect::<HashSet<_>>()
                 .into_iter()
                 .collect(),
-            apps: user
+            issued_at: Utc::now().timestamp() as u64,
+            expires: expires.timestamp() as u64,
+            token_id: token::generate(),
+            session_id: session_id.to_string(),
+            amr: amr.to_vec(),
+            email_verified: user.user.is_email_verified(),
+            client_id: client_id.map(String::from),
+            scope: (!scope.is_empty()).then(|| scope.join(" ")),
+            personal_access_token_id: None,
+            actor: None,
+        }
+        .with_implied(implications)
+    }
+
+    /// Claims for a client_credentials token, carrying the grants the application holds
+    /// itself, which may belong to any application
+    pub fn for_client(application: &Application, lifetime: chrono::Duration) -> Self {
+        let token_id = token::generate();
+
+        Self {
+            subject: application.application_id.clone(),
+            subject_type: SubjectType::Client,
+            issuer: crate::PRODUCT_IDENTIFIER.to_string(),
+            grants: compact(
+                application
+                    .client_grants
+                    .iter()
+                    .filter(|v| v.enabled)
+                    .map(|v| v.grant_id.as_str()),
+            ),
+            apps: application
+                .client_grants
+                .iter()
+                .filter(|v| v.enabled)
+                .map(|v| v.application_id.clone())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect(),
+            issued_at: Utc::now().timestamp() as u64,
+            expires: (Utc::now() + lifetime).timestamp() as u64,
+            session_id: token_id.clone(),
+            token_id,
+            amr: vec![],
+            email_verified: false,
+            client_id: Some(application.application_id.clone()),
+            scope: None,
+            personal_access_token_id: None,
+            actor: None,
+        }
+    }
+
+    /// Claims for a request made with a personal access token, carrying the token's grants
+    /// that the user still holds. They expire with the token, or after `lifetime` for tokens
+    /// that don't, which only matters to anything reading `exp`.
+    pub fn for_personal_access_token(
+        user: &UserDetailDto,
+        token: &PersonalAccessToken,
+        lifetime: chrono::Duration,
+    ) -> Self {
+        let effective = user
+            .effective_grants()
+            .into_iter()
+            .map(|grant| (grant.grant_id.as_str(), grant.application_id.as_str()))
+            .collect::<HashMap<_, _>>();
+        let held = effective
+            .keys()
+            .map(|grant_id| GrantPattern::lenient(grant_id))
+            .collect::<Vec<_>>();
+        let grants = compact(
+            token
                 .grants
-                .values()
-                .map(|v| &v.application_id)
-                .cloned()
-                // This is stupid
+                .iter()
+                .map(String::as_str)
+                .filter(|grant_id| covered(&held, grant_id)),
+        );
+        let patterns = grants
+            .iter()
+            .map(|grant_id| GrantPattern::lenient(grant_id))
+            .collect::<Vec<_>>();
+        // Either way round, the token may hold a pattern narrower or wider than the user's
+        let apps = effective
+            .iter()
+            .filter(|(grant_id, _)| {
+                let it = GrantPattern::lenient(grant_id);
+                patterns
+                    .iter()
+                    .any(|grant| grant.covers(&it) || it.covers(grant))
+            })
+            .map(|(_, application_id)| *application_id);
+        let session_id = format!("pat:{}", token.personal_access_token_id);
+
+        Self {
+            subject: user.user.user_id.to_string(),
+            subject_type: SubjectType::User,
+            issuer: crate::PRODUCT_IDENTIFIER.to_string(),
+            grants,
+            apps: apps
+                .map(|it| it.to_string())
                 .collect::<HashSet<_>>()
                 .into_iter()
                 .collect(),
             issued_at: Utc::now().timestamp() as u64,
-            // i dont care have a 24h token
-            expires: (Utc::now() + chrono::Duration::hours(24)).timestamp() as u64,
+            expires: token
+                .expires_at
+                .unwrap_or_else(|| Utc::now() + lifetime)
+                .timestamp() as u64,
+            token_id: session_id.clone(),
+            session_id,
+            amr: vec![],
+            email_verified: user.user.is_email_verified(),
+            client_id: None,
+            scope: None,
+            personal_access_token_id: Some(token.personal_access_token_id),
+            actor: None,
         }
     }
+
+    /// Claims for `user` minted on behalf of `actor_user_id`, within a session of their own
+    pub fn impersonating(
+        user: &UserDetailDto,
+        implications: &Implications,
+        actor_user_id: i32,
+        session_id: &str,
+        amr: &[String],
+        lifetime: chrono::Duration,
+    ) -> Self {
+        Self {
+            actor: Some(Actor {
+                subject: actor_user_id.to_string(),
+            }),
+            ..Self::r#for(user, implications, session_id, amr, None, &[], lifetime)
+        }
+    }
+
+    /// Adds the grants these already imply, with their applications. A user token issued to a
+    /// client only gains grants of the client's application, the same scoping `r#for` applies.
+    pub fn with_implied(mut self, implications: &Implications) -> Self {
+        let scoped = match self.subject_type {
+            SubjectType::User => self.client_id.clone(),
+            SubjectType::Client => None,
+        };
+
+        let implied = implications.expand(self.grants.iter().map(String::as_str));
+        for (grant_id, application_id) in implied {
+            if scoped.as_ref().is_some_and(|it| *it != application_id) {
+                continue;
+            }
+
+            self.grants.push(grant_id);
+            if !self.apps.contains(&application_id) {
+                self.apps.push(application_id);
+            }
+        }
+        self.grants = compact(self.grants.iter().map(String::as_str));
+
+        self
+    }
+
+    /// Whether the user logged in to this API themselves, rather than the token standing in
+    /// for them through an OAuth client, a personal access token or an impersonating admin
+    pub fn is_first_party(&self) -> bool {
+        self.client_id.is_none() && self.personal_access_token_id.is_none() && self.actor.is_none()
+    }
+
+    /// The user the token was issued to, `None` for client subjects
+    pub fn user_id(&self) -> Option<i32> {
+        match self.subject_type {
+            SubjectType::User => self.subject.parse().ok(),
+            SubjectType::Client => None,
+        }
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scope
+            .as_deref()
+            .is_some_and(|it| it.split_whitespace().any(|it| it == scope))
+    }
+
+    /// Whether the session was opened with a second factor
+    pub fn has_mfa(&self) -> bool {
+        let mfa = AuthMethod::Mfa.to_string();
+        self.amr.iter().any(|method| *method == mfa)
+    }
+}
+
+/// Claims of a token issued to a user, with its subject parsed back into a user id
+#[derive(Debug, Clone)]
+pub struct UserClaims {
+    pub user_id: i32,
+    pub claims: Claims,
+}
+impl TryFrom<Claims> for UserClaims {
+    type Error = Claims;
+
+    fn try_from(claims: Claims) -> Result<Self, Self::Error> {
+        match claims.user_id() {
+            Some(user_id) => Ok(Self { user_id, claims }),
+            None => Err(claims),
+        }
+    }
+}
+impl Deref for UserClaims {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.claims
+    }
+}
+
+/// Authentication method references, RFC 8176
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display)]
+pub enum AuthMethod {
+    #[strum(to_string = "pwd")]
+    Password,
+    #[strum(to_string = "otp")]
+    Otp,
+    /// A recovery code stood in for the one-time password, not registered with RFC 8176
+    #[strum(to_string = "rec")]
+    RecoveryCode,
+    #[strum(to_string = "mfa")]
+    Mfa,
+    /// Logged in through an upstream provider, whatever it says it checked isn't ours to vouch for
+    #[strum(to_string = "fed")]
+    Federated,
+}
+impl AuthMethod {
+    pub fn amr(methods: &[AuthMethod]) -> Vec<String> {
+        methods.iter().map(ToString::to_string).collect()
+    }
 }
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
 pub enum JwtError {
     #[error("Failed to create signing key: {inner_error}")]
     CreateSigningKey { inner_error: AnyError },
+    #[error("Failed to read key file '{path}': {inner_error}")]
+    ReadKeyFile { path: String, inner_error: AnyError },
+    #[error("Signing algorithm {algorithm} requires a --signing-key-file")]
+    MissingKeyFile { algorithm: String },
     #[error("Failed to sign claims: {inner_error}")]
     Sign { inner_error: AnyError },
     #[error("Failed to verify token: {inner_error}")]
     Verify { inner_error: AnyError },
+    #[error("Token was signed with an unknown key kid={kid}")]
+    UnknownKey { kid: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, strum::Display)]
+pub enum SigningAlgorithm {
+    #[value(name = "HS256")]
+    #[strum(to_string = "HS256")]
+    Hs256,
+    #[value(name = "RS256")]
+    #[strum(to_string = "RS256")]
+    Rs256,
+    #[value(name = "ES256")]
+    #[strum(to_string = "ES256")]
+    Es256,
+    #[value(name = "EdDSA")]
+    #[strum(to_string = "EdDSA")]
+    EdDsa,
+}
+impl From<SigningAlgorithm> for Algorithm {
+    fn from(value: SigningAlgorithm) -> Self {
+        match value {
+            SigningAlgorithm::Hs256 => Algorithm::HS256,
+            SigningAlgorithm::Rs256 => Algorithm::RS256,
+            SigningAlgorithm::Es256 => Algorithm::ES256,
+            SigningAlgorithm::EdDsa => Algorithm::EdDSA,
+        }
+    }
+}
+
+/// A `--verification-key-files` entry, `ALGORITHM=path` or just the path for a key of the
+/// `--signing-algorithm`. Keys of the algorithm being rotated away from keep verifying that way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationKeyFile {
+    pub algorithm: Option<SigningAlgorithm>,
+    pub path: PathBuf,
+}
+impl FromStr for VerificationKeyFile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once('=') {
+            Some((algorithm, path)) => Ok(Self {
+                algorithm: Some(<SigningAlgorithm as ValueEnum>::from_str(algorithm, true)?),
+                path: path.into(),
+            }),
+            None => Ok(Self {
+                algorithm: None,
+                path: value.into(),
+            }),
+        }
+    }
+}
+
+pub struct SigningKey {
+    kid: String,
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Public half of the key, `None` for symmetric keys which must never be published
+    jwk: Option<Jwk>,
+}
+impl SigningKey {
+    pub fn hmac(secret: &str, kid: Option<&str>) -> Self {
+        let kid = kid.map(str::to_string).unwrap_or_else(|| {
+            // Never publish the raw secret, only enough of its digest to tell keys apart
+            format!("{:x}", Sha256::digest(secret.as_bytes()))[..16].to_string()
+        });
+
+        Self {
+            kid,
+            algorithm: Algorithm::HS256,
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            jwk: None,
+        }
+    }
+
+    /// Loads a PEM encoded private key, the public key is derived from it.
+    /// For HS256 the file holds the raw shared secret instead.
+    pub fn from_pem_file(
+        algorithm: SigningAlgorithm,
+        path: &Path,
+        kid: Option<&str>,
+    ) -> Result<Self, JwtError> {
+        let pem = std::fs::read(path).map_err(|e| JwtError::ReadKeyFile {
+            path: path.display().to_string(),
+            inner_error: e.into(),
+        })?;
+
+        let encoding = match algorithm {
+            SigningAlgorithm::Hs256 => {
+                let secret = String::from_utf8_lossy(&pem);
+                return Ok(Self::hmac(secret.trim(), kid));
+            }
+            SigningAlgorithm::Rs256 => EncodingKey::from_rsa_pem(&pem),
+            SigningAlgorithm::Es256 => EncodingKey::from_ec_pem(&pem),
+            SigningAlgorithm::EdDsa => EncodingKey::from_ed_pem(&pem),
+        }
+        .map_err(|e| JwtError::CreateSigningKey {
+            inner_error: e.into(),
+        })?;
+
+        let mut jwk = Jwk::from_encoding_key(&encoding, algorithm.into()).map_err(|e| {
+            JwtError::CreateSigningKey {
+                inner_error: e.into(),
+            }
+        })?;
+
+        let kid = match kid {
+            Some(kid) => kid.to_string(),
+            None => {
+                let public = serde_json::to_string(&jwk.algorithm).map_err(|e| {
+                    JwtError::CreateSigningKey {
+                        inner_error: e.into(),
+                    }
+                })?;
+                format!("{:x}", Sha256::digest(public.as_bytes()))[..16].to_string()
+            }
+        };
+
+        jwk.common.key_id = Some(kid.clone());
+        jwk.common.public_key_use = Some(PublicKeyUse::Signature);
+
+        let decoding = DecodingKey::from_jwk(&jwk).map_err(|e| JwtError::CreateSigningKey {
+            inner_error: e.into(),
+        })?;
+
+        Ok(Self {
+            kid,
+            algorithm: algorithm.into(),
+            encoding,
+            decoding,
+            jwk: Some(jwk),
+        })
+    }
+
+    pub fn from_args(args: &Args) -> Result<Self, JwtError> {
+        let kid = args.signing_key_id.as_deref();
+        match (&args.signing_key_file, args.signing_algorithm) {
+            (Some(path), algorithm) => Self::from_pem_file(algorithm, path, kid),
+            (None, SigningAlgorithm::Hs256) => Ok(Self::hmac(&args.signing_key, kid)),
+            (None, algorithm) => Err(JwtError::MissingKeyFile {
+                algorithm: algorithm.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// Signs new tokens
+    Active,
+    /// Verifies tokens and can be promoted, configured up front so it is published before use
+    Standby,
+    /// Previously active, kept around until every token it signed has expired
+    Retiring,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyInfo {
+    pub kid: String,
+    pub algorithm: String,
+    pub status: KeyStatus,
+    pub retire_at: Option<DateTime<Utc>>,
+}
+
+struct VerificationKey {
+    key: Arc<SigningKey>,
+    retire_at: Option<DateTime<Utc>>,
+}
+impl VerificationKey {
+    fn is_live(&self) -> bool {
+        self.retire_at
+            .is_none_or(|retire_at| retire_at > Utc::now())
+    }
+}
+
+/// One active signing key plus any number of verification-only keys, selected by `kid`
+pub struct Keyring {
+    active: Arc<SigningKey>,
+    verification: HashMap<String, VerificationKey>,
+}
+impl Keyring {
+    pub fn new(active: SigningKey, verification: Vec<SigningKey>) -> Self {
+        Self {
+            active: Arc::new(active),
+            verification: verification
+                .into_iter()
+                .map(|key| {
+                    (
+                        key.kid.clone(),
+                        VerificationKey {
+                            key: Arc::new(key),
+                            retire_at: None,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_args(args: &Args) -> Result<Self, JwtError> {
+        let active = SigningKey::from_args(args)?;
+        let verification = args
+            .verification_key_files
+            .iter()
+            .map(|file| {
+                let algorithm = file.algorithm.unwrap_or(args.signing_algorithm);
+                SigningKey::from_pem_file(algorithm, &file.path, None)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(active, verification))
+    }
+
+    fn find(&self, kid: &str) -> Option<Arc<SigningKey>> {
+        if self.active.kid == kid {
+            return Some(self.active.clone());
+        }
+
+        self.verification
+            .get(kid)
+            .filter(|key| key.is_live())
+            .map(|key| key.key.clone())
+    }
+
+    fn prune(&mut self) {
+        self.verification.retain(|_, key| key.is_live());
+    }
 }
 
 #[derive(Clone)]
 pub struct Jwt {
-    key: Hmac<Sha256>,
+    keyring: Arc<RwLock<Keyring>>,
+    /// How long a retired key must keep verifying, i.e. the longest lifetime of any token we sign
+    retire_after: chrono::Duration,
 }
 impl Debug for Jwt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -84,24 +626,173 @@ impl Debug for Jwt {
 }
 
 impl Jwt {
-    pub fn new(key: &str) -> Result<Self, JwtError> {
-        let hash =
-            Hmac::new_from_slice(key.as_bytes()).map_err(|e| JwtError::CreateSigningKey {
-                inner_error: e.into(),
-            })?;
-        Ok(Self { key: hash })
+    pub fn new(keyring: Keyring, retire_after: chrono::Duration) -> Self {
+        Self {
+            keyring: Arc::new(RwLock::new(keyring)),
+            retire_after,
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Keyring> {
+        self.keyring.read().unwrap_or_else(|e| e.into_inner())
     }
 
-    pub fn sign(&self, claims: &Claims) -> Result<String, JwtError> {
-        claims.sign_with_key(&self.key).map_err(|e| JwtError::Sign {
+    fn write(&self) -> RwLockWriteGuard<'_, Keyring> {
+        self.keyring.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Generic so ID tokens can be signed with the same keys as access tokens
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, JwtError> {
+        let key = self.read().active.clone();
+
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+
+        jsonwebtoken::encode(&header, claims, &key.encoding).map_err(|e| JwtError::Sign {
             inner_error: e.into(),
         })
     }
 
+    /// Algorithm of the active key, as published in the discovery document
+    pub fn algorithm(&self) -> String {
+        format!("{:?}", self.read().active.algorithm)
+    }
+
     pub fn verify(&self, jwt: &str) -> Result<Claims, JwtError> {
-        jwt.verify_with_key(&self.key)
+        let header = jsonwebtoken::decode_header(jwt).map_err(|e| JwtError::Verify {
+            inner_error: e.into(),
+        })?;
+
+        // Tokens minted before kids were introduced don't carry one
+        let key = match header.kid {
+            Some(kid) => self.read().find(&kid).ok_or(JwtError::UnknownKey { kid })?,
+            None => self.read().active.clone(),
+        };
+
+        let mut validation = Validation::new(key.algorithm);
+        validation.leeway = 0;
+
+        jsonwebtoken::decode::<Claims>(jwt, &key.decoding, &validation)
+            .map(|data| data.claims)
             .map_err(|e| JwtError::Verify {
                 inner_error: e.into(),
             })
     }
+
+    /// Whether `kid` is loaded and still verifying, so it can be promoted
+    pub fn knows(&self, kid: &str) -> bool {
+        self.read().find(kid).is_some()
+    }
+
+    /// Makes `kid`, promoted at `promoted_at`, the signing key. The previous signing key keeps
+    /// verifying for `retire_after` from then, so tokens it already signed stay valid until they
+    /// expire. Every instance adopting the same promotion retires the same keys at the same time.
+    pub fn adopt(&self, kid: &str, promoted_at: DateTime<Utc>) -> Result<KeyInfo, JwtError> {
+        let mut keyring = self.write();
+        keyring.prune();
+
+        if keyring.active.kid == kid {
+            return Ok(KeyInfo::from_key(&keyring.active, KeyStatus::Active, None));
+        }
+
+        let promoted = keyring
+            .verification
+            .remove(kid)
+            .ok_or(JwtError::UnknownKey { kid: kid.into() })?;
+
+        let retired = std::mem::replace(&mut keyring.active, promoted.key);
+        tracing::info!(
+            "Promoted signing key kid={}, retiring kid={}",
+            keyring.active.kid,
+            retired.kid
+        );
+
+        keyring.verification.insert(
+            retired.kid.clone(),
+            VerificationKey {
+                key: retired,
+                retire_at: Some(promoted_at + self.retire_after),
+            },
+        );
+
+        Ok(KeyInfo::from_key(&keyring.active, KeyStatus::Active, None))
+    }
+
+    pub fn keys(&self) -> Vec<KeyInfo> {
+        let keyring = self.read();
+
+        std::iter::once(KeyInfo::from_key(&keyring.active, KeyStatus::Active, None))
+            .chain(
+                keyring
+                    .verification
+                    .values()
+                    .filter(|key| key.is_live())
+                    .map(|key| match key.retire_at {
+                        Some(retire_at) => {
+                            KeyInfo::from_key(&key.key, KeyStatus::Retiring, Some(retire_at))
+                        }
+                        None => KeyInfo::from_key(&key.key, KeyStatus::Standby, None),
+                    }),
+            )
+            .collect()
+    }
+
+    /// Public keys that can verify our tokens, symmetric keys are never included
+    pub fn jwks(&self) -> JwkSet {
+        let keyring = self.read();
+
+        JwkSet {
+            keys: std::iter::once(&keyring.active)
+                .chain(
+                    keyring
+                        .verification
+                        .values()
+                        .filter(|key| key.is_live())
+                        .map(|key| &key.key),
+                )
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+impl KeyInfo {
+    fn from_key(key: &SigningKey, status: KeyStatus, retire_at: Option<DateTime<Utc>>) -> Self {
+        Self {
+            kid: key.kid.clone(),
+            algorithm: format!("{:?}", key.algorithm),
+            status,
+            retire_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use crate::services::core::jwt::{SigningAlgorithm, VerificationKeyFile};
+
+    #[test]
+    fn test_verification_key_file() {
+        assert_eq!(
+            Ok(VerificationKeyFile {
+                algorithm: None,
+                path: PathBuf::from("/run/secrets/next.pem"),
+            }),
+            VerificationKeyFile::from_str("/run/secrets/next.pem")
+        );
+        assert_eq!(
+            Ok(VerificationKeyFile {
+                algorithm: Some(SigningAlgorithm::Hs256),
+                path: PathBuf::from("/run/secrets/previous"),
+            }),
+            VerificationKeyFile::from_str("HS256=/run/secrets/previous")
+        );
+        assert_eq!(
+            Ok(Some(SigningAlgorithm::EdDsa)),
+            VerificationKeyFile::from_str("eddsa=key.pem").map(|it| it.algorithm)
+        );
+        assert!(VerificationKeyFile::from_str("PS256=key.pem").is_err());
+    }
 }
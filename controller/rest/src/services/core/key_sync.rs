@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use data::repository::key_promotion::KeyPromotionError;
+
+use crate::{api::ApiRepositories, services::core::jwt::Jwt};
+
+/// Signs with the key most recently promoted by any instance. A promoted key this instance
+/// didn't load is logged as an error every time, it keeps signing with its current key until
+/// the key is added to its `--verification-key-files`.
+pub async fn sync(repositories: &ApiRepositories, jwt: &Jwt) -> Result<(), KeyPromotionError> {
+    let Some(promotion) = repositories.key_promotion.latest().await? else {
+        return Ok(());
+    };
+
+    if let Err(e) = jwt.adopt(&promotion.kid, promotion.created_at) {
+        tracing::error!(
+            "Failed to adopt signing key kid={} promoted by {}: {e}",
+            promotion.kid,
+            promotion.created_by
+        );
+    }
+
+    Ok(())
+}
+
+/// Picks up promotions made by other instances every `interval`, for as long as the process runs
+pub fn spawn(repositories: ApiRepositories, jwt: Jwt, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = sync(&repositories, &jwt).await {
+                tracing::error!("Failed to load the latest signing key promotion: {:?}", e);
+            }
+        }
+    });
+}
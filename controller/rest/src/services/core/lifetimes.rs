@@ -0,0 +1,37 @@
+use chrono::Duration;
+
+use crate::Args;
+
+#[derive(Clone, Debug)]
+pub struct Lifetimes {
+    pub access_token: Duration,
+    pub refresh_token: Duration,
+    pub mfa_challenge: Duration,
+    pub password_reset: Duration,
+    pub email_verification: Duration,
+    pub authorization_code: Duration,
+    pub device_code: Duration,
+    pub federation_request: Duration,
+    pub impersonation: Duration,
+}
+impl Lifetimes {
+    pub fn new(args: &Args) -> Self {
+        Self {
+            access_token: Duration::minutes(args.access_token_lifetime_minutes),
+            refresh_token: Duration::days(args.refresh_token_lifetime_days),
+            mfa_challenge: Duration::minutes(args.mfa_challenge_lifetime_minutes),
+            password_reset: Duration::minutes(args.password_reset_lifetime_minutes),
+            email_verification: Duration::hours(args.email_verification_lifetime_hours),
+            authorization_code: Duration::seconds(args.authorization_code_lifetime_seconds),
+            device_code: Duration::seconds(args.device_code_lifetime_seconds),
+            federation_request: Duration::minutes(args.federation_request_lifetime_minutes),
+            impersonation: Duration::minutes(args.impersonation_lifetime_minutes),
+        }
+    }
+
+    /// The longest any token we sign stays valid, how long a retired key must keep verifying.
+    /// ID tokens share their access token's expiry and capped tokens only ever expire sooner.
+    pub fn longest_signed(&self) -> Duration {
+        self.access_token.max(self.impersonation)
+    }
+}
@@ -1,2 +1,15 @@
+pub mod grant_sweeper;
 pub mod hasher;
+pub mod implication;
 pub mod jwt;
+pub mod key_sync;
+pub mod lifetimes;
+pub mod mailer;
+pub mod pkce;
+pub mod recovery_code;
+pub mod revocation;
+pub mod throttle;
+pub mod token;
+pub mod totp;
+pub mod upstream;
+pub mod user_code;
@@ -0,0 +1,57 @@
+use data_encoding::BASE64URL_NOPAD;
+use sha2::{Digest, Sha256};
+
+/// The only challenge method accepted, `plain` gives no protection against a leaked code
+pub const S256: &str = "S256";
+
+/// RFC 7636 section 4.1, 43 to 128 characters from the unreserved set
+pub fn is_valid_verifier(verifier: &str) -> bool {
+    (43..=128).contains(&verifier.len())
+        && verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'))
+}
+
+/// An S256 challenge is the unpadded base64url SHA-256 of its verifier, so is always 43 characters
+pub fn is_valid_challenge(challenge: &str) -> bool {
+    challenge.len() == 43
+        && BASE64URL_NOPAD
+            .decode(challenge.as_bytes())
+            .is_ok_and(|it| it.len() == 32)
+}
+
+pub fn challenge(verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(verifier.as_bytes()))
+}
+
+pub fn verify(verifier: &str, challenge: &str) -> bool {
+    is_valid_verifier(verifier) && self::challenge(verifier) == challenge
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::core::pkce::{challenge, is_valid_challenge, is_valid_verifier, verify};
+
+    // RFC 7636 appendix B
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[test]
+    fn test_challenge() {
+        assert_eq!(CHALLENGE, challenge(VERIFIER));
+        assert!(is_valid_challenge(CHALLENGE));
+        assert!(verify(VERIFIER, CHALLENGE));
+        assert!(!verify(
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXK",
+            CHALLENGE
+        ));
+    }
+
+    #[test]
+    fn test_is_valid_verifier() {
+        assert!(is_valid_verifier(VERIFIER));
+        assert!(!is_valid_verifier(&VERIFIER[..42]));
+        assert!(!is_valid_verifier(&"a".repeat(129)));
+        assert!(!is_valid_verifier(&format!("{}+", &VERIFIER[..42])));
+    }
+}
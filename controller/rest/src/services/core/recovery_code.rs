@@ -0,0 +1,33 @@
+use rand::Rng;
+
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const GROUP_LENGTH: usize = 5;
+/// Lowercase alphanumerics without the easily confused 0/o and 1/l
+const CHARSET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+/// A fresh set of one-time recovery codes, formatted like `k3m9x-7qpwd`.
+/// As with opaque tokens only ever persist their digest.
+pub fn generate() -> Vec<String> {
+    let mut rng = rand::rng();
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut code = String::with_capacity(GROUP_LENGTH * 2 + 1);
+            for i in 0..GROUP_LENGTH * 2 {
+                if i == GROUP_LENGTH {
+                    code.push('-');
+                }
+                code.push(CHARSET[rng.random_range(0..CHARSET.len())] as char);
+            }
+            code
+        })
+        .collect()
+}
+
+/// Strips the formatting users tend to mangle, so `K3M9X 7QPWD` matches `k3m9x-7qpwd`
+pub fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
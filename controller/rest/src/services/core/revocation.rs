@@ -0,0 +1,48 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Bounds how many sessions we remember before sweeping stale entries
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Short-lived, in-process memo of session revocation state so `BearerJwt` doesn't hit the
+/// database on every request. Revocations made by this instance are visible immediately,
+/// revocations made by other instances are visible once the cached entry expires.
+#[derive(Clone, Debug)]
+pub struct RevocationCache {
+    ttl: Duration,
+    entries: Arc<RwLock<HashMap<String, (bool, Instant)>>>,
+}
+impl RevocationCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// `Some(true)` if the session is known revoked, `None` if we don't know
+    pub fn get(&self, session_id: &str) -> Option<bool> {
+        let entries = self.entries.read().ok()?;
+        let (revoked, cached_at) = entries.get(session_id)?;
+        if cached_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(*revoked)
+    }
+
+    pub fn insert(&self, session_id: &str, revoked: bool) {
+        let Ok(mut entries) = self.entries.write() else {
+            return;
+        };
+
+        if entries.len() >= SWEEP_THRESHOLD {
+            let ttl = self.ttl;
+            entries.retain(|_, (_, cached_at)| cached_at.elapsed() <= ttl);
+        }
+
+        entries.insert(session_id.to_string(), (revoked, Instant::now()));
+    }
+}
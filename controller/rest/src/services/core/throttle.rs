@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+use data::repository::login_throttle::{LoginThrottleError, LoginThrottleRepository};
+
+use crate::Args;
+
+/// Bounds how many keys the in-memory store holds before sweeping stale entries
+const SWEEP_THRESHOLD: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ThrottleStoreKind {
+    /// Counters live in this process only
+    Memory,
+    /// Counters are shared between every instance through the database
+    Database,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ThrottlePolicy {
+    /// Failures allowed before the first lockout
+    pub threshold: u32,
+    /// Length of the first lockout, doubled on every failure after it
+    pub base: Duration,
+    /// Longest lockout, and how long after the last failure a counter is forgotten
+    pub max: Duration,
+}
+impl ThrottlePolicy {
+    pub fn lockout_for(&self, failures: u32) -> Option<Duration> {
+        if failures < self.threshold {
+            return None;
+        }
+
+        let exponent = (failures - self.threshold).min(30);
+        let lockout = self
+            .base
+            .checked_mul(2i32.pow(exponent))
+            .unwrap_or(self.max);
+        Some(lockout.min(self.max))
+    }
+}
+
+/// Whole seconds for a `Retry-After` header, rounded up so clients never retry a moment too early
+pub fn retry_after_seconds(retry_after: Duration) -> u64 {
+    (retry_after.num_milliseconds().max(0) as u64).div_ceil(1000)
+}
+
+#[derive(Debug, Clone)]
+pub struct ThrottleState {
+    pub failures: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub last_failure_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub enum ThrottleStore {
+    Memory(Arc<Mutex<HashMap<String, ThrottleState>>>),
+    Database(LoginThrottleRepository),
+}
+impl ThrottleStore {
+    async fn get(&self, key: &str) -> Result<Option<ThrottleState>, LoginThrottleError> {
+        match self {
+            Self::Memory(states) => Ok(states
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(key)
+                .cloned()),
+            Self::Database(repository) => {
+                Ok(repository.by_key(key).await?.map(|it| ThrottleState {
+                    failures: it.failures.max(0) as u32,
+                    locked_until: it.locked_until,
+                    last_failure_at: it.last_failure_at,
+                }))
+            }
+        }
+    }
+
+    /// Counts a failure against `key` and locks it per `policy`, atomically in either store
+    async fn record_failure(
+        &self,
+        key: &str,
+        policy: ThrottlePolicy,
+        now: DateTime<Utc>,
+    ) -> Result<(), LoginThrottleError> {
+        match self {
+            Self::Memory(states) => {
+                let mut states = states.lock().unwrap_or_else(|e| e.into_inner());
+                if states.len() >= SWEEP_THRESHOLD {
+                    states.retain(|_, state| now - state.last_failure_at < policy.max);
+                }
+
+                let failures = states
+                    .get(key)
+                    .filter(|state| now - state.last_failure_at < policy.max)
+                    .map(|state| state.failures)
+                    .unwrap_or(0)
+                    + 1;
+
+                states.insert(
+                    key.to_string(),
+                    ThrottleState {
+                        failures,
+                        locked_until: policy.lockout_for(failures).map(|lockout| now + lockout),
+                        last_failure_at: now,
+                    },
+                );
+                Ok(())
+            }
+            Self::Database(repository) => {
+                repository
+                    .record_failure(key, now - policy.max, |failures| {
+                        policy
+                            .lockout_for(failures.max(0) as u32)
+                            .map(|lockout| now + lockout)
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), LoginThrottleError> {
+        match self {
+            Self::Memory(states) => {
+                states.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+                Ok(())
+            }
+            Self::Database(repository) => repository.delete(key).await,
+        }
+    }
+}
+
+/// Whose failure budget an attempt counts against
+#[derive(Debug, Clone, Copy)]
+pub enum ThrottleAccount<'a> {
+    /// An existing user, whether they gave their username, in any case, or their email
+    User(i32),
+    /// An identifier that matched no user, so guesses at it are still bounded
+    Unknown(&'a str),
+}
+impl ThrottleAccount<'_> {
+    fn key(&self) -> String {
+        match self {
+            Self::User(user_id) => format!("user:{user_id}"),
+            Self::Unknown(identifier) => format!("unknown:{}", identifier.to_lowercase()),
+        }
+    }
+}
+
+/// Failure counters for `/login`, tracked separately per account and per source IP
+#[derive(Clone, Debug)]
+pub struct LoginThrottle {
+    username_policy: ThrottlePolicy,
+    ip_policy: ThrottlePolicy,
+    store: ThrottleStore,
+}
+impl LoginThrottle {
+    pub fn new(
+        username_policy: ThrottlePolicy,
+        ip_policy: ThrottlePolicy,
+        store: ThrottleStore,
+    ) -> Self {
+        Self {
+            username_policy,
+            ip_policy,
+            store,
+        }
+    }
+
+    pub fn from_args(args: &Args, repository: LoginThrottleRepository) -> Self {
+        let base = Duration::seconds(args.lockout_base_seconds);
+        let max = Duration::seconds(args.lockout_max_seconds);
+
+        let store = match args.throttle_store {
+            ThrottleStoreKind::Memory => {
+                ThrottleStore::Memory(Arc::new(Mutex::new(HashMap::new())))
+            }
+            ThrottleStoreKind::Database => ThrottleStore::Database(repository),
+        };
+
+        Self::new(
+            ThrottlePolicy {
+                threshold: args.lockout_username_threshold,
+                base,
+                max,
+            },
+            ThrottlePolicy {
+                threshold: args.lockout_ip_threshold,
+                base,
+                max,
+            },
+            store,
+        )
+    }
+
+    fn keys(
+        &self,
+        account: ThrottleAccount,
+        source_ip: Option<&str>,
+    ) -> Vec<(String, ThrottlePolicy)> {
+        let mut keys = vec![(account.key(), self.username_policy)];
+        if let Some(ip) = source_ip {
+            keys.push((format!("ip:{ip}"), self.ip_policy));
+        }
+        keys
+    }
+
+    /// How long until a login may be attempted, `None` if it may be attempted now
+    pub async fn retry_after(
+        &self,
+        account: ThrottleAccount<'_>,
+        source_ip: Option<&str>,
+    ) -> Result<Option<Duration>, LoginThrottleError> {
+        let now = Utc::now();
+        let mut retry_after = None;
+
+        for (key, _) in self.keys(account, source_ip) {
+            let Some(locked_until) = self.store.get(&key).await?.and_then(|it| it.locked_until)
+            else {
+                continue;
+            };
+
+            if locked_until > now {
+                retry_after = retry_after.max(Some(locked_until - now));
+            }
+        }
+
+        Ok(retry_after)
+    }
+
+    pub async fn record_failure(
+        &self,
+        account: ThrottleAccount<'_>,
+        source_ip: Option<&str>,
+    ) -> Result<(), LoginThrottleError> {
+        let now = Utc::now();
+
+        for (key, policy) in self.keys(account, source_ip) {
+            self.store.record_failure(&key, policy, now).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Clears the user's counter, the IP's counter is left to decay on its own
+    pub async fn record_success(&self, user_id: i32) -> Result<(), LoginThrottleError> {
+        self.unlock(user_id).await
+    }
+
+    pub async fn unlock(&self, user_id: i32) -> Result<(), LoginThrottleError> {
+        self.store
+            .clear(&ThrottleAccount::User(user_id).key())
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::services::core::throttle::{ThrottleAccount, ThrottlePolicy};
+
+    #[test]
+    fn test_lockout_for() {
+        let policy = ThrottlePolicy {
+            threshold: 3,
+            base: Duration::seconds(10),
+            max: Duration::seconds(60),
+        };
+
+        assert_eq!(None, policy.lockout_for(0));
+        assert_eq!(None, policy.lockout_for(2));
+        assert_eq!(Some(Duration::seconds(10)), policy.lockout_for(3));
+        assert_eq!(Some(Duration::seconds(20)), policy.lockout_for(4));
+        assert_eq!(Some(Duration::seconds(40)), policy.lockout_for(5));
+        assert_eq!(Some(Duration::seconds(60)), policy.lockout_for(6));
+        assert_eq!(Some(Duration::seconds(60)), policy.lockout_for(u32::MAX));
+    }
+
+    #[test]
+    fn test_account_key() {
+        assert_eq!("user:7", ThrottleAccount::User(7).key());
+        assert_eq!(
+            ThrottleAccount::Unknown("Mallory").key(),
+            ThrottleAccount::Unknown("mallory").key()
+        );
+        assert_ne!(
+            ThrottleAccount::User(7).key(),
+            ThrottleAccount::Unknown("7").key()
+        );
+    }
+}
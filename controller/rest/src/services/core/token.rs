@@ -0,0 +1,19 @@
+use rand::{Rng, distr::Alphanumeric};
+use sha2::{Digest, Sha256};
+
+const OPAQUE_TOKEN_LENGTH: usize = 48;
+
+/// Generates a random opaque token, safe to hand out as-is.
+/// Only ever persist its [`digest`], never the token itself.
+pub fn generate() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(OPAQUE_TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Hex-encoded SHA-256 of an opaque token, used as its lookup key in the database
+pub fn digest(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,397 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use liberror::AnyError;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+use crate::{
+    Args,
+    services::{core::pkce, oauth::with_query},
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum UpstreamError {
+    #[error("Failed to read upstream providers file '{path}': {inner_error}")]
+    ReadProvidersFile { path: String, inner_error: AnyError },
+    #[error("Provider '{provider_id}' is configured more than once")]
+    DuplicateProvider { provider_id: String },
+    #[error("Failed to configure upstream client: {inner_error}")]
+    Configure { inner_error: AnyError },
+    #[error("Request to provider '{provider_id}' failed: {inner_error}")]
+    Request {
+        provider_id: String,
+        inner_error: AnyError,
+    },
+    #[error("Provider '{provider_id}' published issuer '{issuer}', expected '{expected}'")]
+    IssuerMismatch {
+        provider_id: String,
+        issuer: String,
+        expected: String,
+    },
+    #[error("Provider '{provider_id}' rejected the authorization code with status {status}")]
+    Exchange { provider_id: String, status: u16 },
+    #[error("Provider '{provider_id}' returned no id_token")]
+    MissingIdToken { provider_id: String },
+    #[error("ID token from provider '{provider_id}' was signed with unsupported alg={algorithm}")]
+    UnsupportedAlgorithm {
+        provider_id: String,
+        algorithm: String,
+    },
+    #[error("ID token from provider '{provider_id}' was signed with an unknown key kid={kid}")]
+    UnknownKey { provider_id: String, kid: String },
+    #[error("Invalid ID token from provider '{provider_id}': {inner_error}")]
+    InvalidIdToken {
+        provider_id: String,
+        inner_error: AnyError,
+    },
+    #[error("ID token from provider '{provider_id}' doesn't carry the expected nonce")]
+    NonceMismatch { provider_id: String },
+}
+
+fn default_scope() -> String {
+    "openid profile email".to_string()
+}
+
+/// An OpenID Connect provider users may log in through, as read from --upstream-providers-file
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpstreamProvider {
+    /// Ours, stored alongside every identity from the provider so must never change
+    pub provider_id: String,
+    pub display_name: String,
+    /// Discovery is fetched from `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default = "default_scope")]
+    pub scope: String,
+    /// Enabled for users provisioned on their first login through the provider
+    #[serde(default)]
+    pub default_grants: Vec<String>,
+}
+
+/// The parts of a provider's discovery document we use
+#[derive(Clone, Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpstreamTokenResponse {
+    id_token: Option<String>,
+}
+
+/// What we read out of an upstream ID token
+#[derive(Clone, Debug, Deserialize)]
+pub struct UpstreamClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+}
+impl UpstreamClaims {
+    /// Only an email the provider vouches for is worth carrying over
+    pub fn verified_email(&self) -> Option<&str> {
+        self.email.as_deref().filter(|_| self.email_verified)
+    }
+}
+
+/// Talks to the upstream providers. Their discovery documents and signing keys are fetched on
+/// first use and cached, the keys are fetched again when a token names one we haven't seen.
+#[derive(Clone, Debug)]
+pub struct Upstream {
+    client: reqwest::Client,
+    providers: Arc<Vec<UpstreamProvider>>,
+    metadata: Arc<RwLock<HashMap<String, ProviderMetadata>>>,
+    keys: Arc<RwLock<HashMap<String, JwkSet>>>,
+}
+impl Upstream {
+    pub fn from_args(args: &Args) -> Result<Self, UpstreamError> {
+        let providers = match &args.upstream_providers_file {
+            Some(path) => Self::read_providers(path)?,
+            None => vec![],
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| UpstreamError::Configure {
+                inner_error: e.into(),
+            })?;
+
+        Ok(Self {
+            client,
+            providers: Arc::new(providers),
+            metadata: Arc::new(RwLock::new(HashMap::new())),
+            keys: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    fn read_providers(path: &Path) -> Result<Vec<UpstreamProvider>, UpstreamError> {
+        let read_error = |inner_error: AnyError| UpstreamError::ReadProvidersFile {
+            path: path.display().to_string(),
+            inner_error,
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|e| read_error(e.into()))?;
+        let providers: Vec<UpstreamProvider> =
+            serde_json::from_str(&contents).map_err(|e| read_error(e.into()))?;
+
+        for (index, provider) in providers.iter().enumerate() {
+            if providers[..index]
+                .iter()
+                .any(|it| it.provider_id == provider.provider_id)
+            {
+                return Err(UpstreamError::DuplicateProvider {
+                    provider_id: provider.provider_id.clone(),
+                });
+            }
+        }
+
+        Ok(providers)
+    }
+
+    pub fn providers(&self) -> &[UpstreamProvider] {
+        &self.providers
+    }
+
+    pub fn provider(&self, provider_id: &str) -> Option<&UpstreamProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.provider_id == provider_id)
+    }
+
+    /// Where to send the user to log in at `provider`, with PKCE on top of the client secret
+    pub async fn authorization_url(
+        &self,
+        provider: &UpstreamProvider,
+        redirect_uri: &str,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String, UpstreamError> {
+        let metadata = self.metadata(provider).await?;
+
+        Ok(with_query(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &provider.client_id),
+                ("redirect_uri", redirect_uri),
+                ("scope", &provider.scope),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", code_challenge),
+                ("code_challenge_method", pkce::S256),
+            ],
+        ))
+    }
+
+    /// Exchanges an authorization code at `provider` and verifies the ID token it returns
+    pub async fn exchange(
+        &self,
+        provider: &UpstreamProvider,
+        redirect_uri: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<UpstreamClaims, UpstreamError> {
+        let metadata = self.metadata(provider).await?;
+        let request_error = |e: reqwest::Error| UpstreamError::Request {
+            provider_id: provider.provider_id.clone(),
+            inner_error: e.into(),
+        };
+
+        // RFC 6749 section 2.3.1 has both form encoded before they're joined
+        let encode = |it: &str| utf8_percent_encode(it, NON_ALPHANUMERIC).to_string();
+        let response = self
+            .client
+            .post(&metadata.token_endpoint)
+            .basic_auth(
+                encode(&provider.client_id),
+                Some(encode(&provider.client_secret)),
+            )
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if !response.status().is_success() {
+            return Err(UpstreamError::Exchange {
+                provider_id: provider.provider_id.clone(),
+                status: response.status().as_u16(),
+            });
+        }
+
+        let id_token = response
+            .json::<UpstreamTokenResponse>()
+            .await
+            .map_err(request_error)?
+            .id_token
+            .ok_or(UpstreamError::MissingIdToken {
+                provider_id: provider.provider_id.clone(),
+            })?;
+
+        let claims = self.verify(provider, &metadata, &id_token).await?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(UpstreamError::NonceMismatch {
+                provider_id: provider.provider_id.clone(),
+            });
+        }
+
+        Ok(claims)
+    }
+
+    async fn verify(
+        &self,
+        provider: &UpstreamProvider,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<UpstreamClaims, UpstreamError> {
+        let invalid = |e: jsonwebtoken::errors::Error| UpstreamError::InvalidIdToken {
+            provider_id: provider.provider_id.clone(),
+            inner_error: e.into(),
+        };
+
+        let header = jsonwebtoken::decode_header(id_token).map_err(invalid)?;
+        // A shared secret would let anyone holding the client secret mint identities
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(UpstreamError::UnsupportedAlgorithm {
+                provider_id: provider.provider_id.clone(),
+                algorithm: format!("{:?}", header.alg),
+            });
+        }
+
+        let key = self.key(provider, metadata, header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&provider.client_id]);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        jsonwebtoken::decode::<UpstreamClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(invalid)
+    }
+
+    async fn metadata(
+        &self,
+        provider: &UpstreamProvider,
+    ) -> Result<ProviderMetadata, UpstreamError> {
+        if let Some(metadata) = self
+            .metadata
+            .read()
+            .ok()
+            .and_then(|it| it.get(&provider.provider_id).cloned())
+        {
+            return Ok(metadata);
+        }
+
+        let issuer = provider.issuer.trim_end_matches('/');
+        let metadata: ProviderMetadata = self
+            .get_json(
+                provider,
+                &format!("{issuer}/.well-known/openid-configuration"),
+            )
+            .await?;
+
+        // OpenID Connect Discovery section 4.3
+        if metadata.issuer.trim_end_matches('/') != issuer {
+            return Err(UpstreamError::IssuerMismatch {
+                provider_id: provider.provider_id.clone(),
+                issuer: metadata.issuer,
+                expected: provider.issuer.clone(),
+            });
+        }
+
+        if let Ok(mut cache) = self.metadata.write() {
+            cache.insert(provider.provider_id.clone(), metadata.clone());
+        }
+
+        Ok(metadata)
+    }
+
+    /// The key named by `kid`, or the only key when the token doesn't name one
+    async fn key(
+        &self,
+        provider: &UpstreamProvider,
+        metadata: &ProviderMetadata,
+        kid: Option<&str>,
+    ) -> Result<DecodingKey, UpstreamError> {
+        let find = |keys: &JwkSet| match kid {
+            Some(kid) => keys.find(kid).cloned(),
+            None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+            None => None,
+        };
+
+        let cached = self
+            .keys
+            .read()
+            .ok()
+            .and_then(|it| it.get(&provider.provider_id).and_then(find));
+        let jwk = match cached {
+            Some(jwk) => Some(jwk),
+            // Either the first token from the provider, or it rotated its keys since
+            None => {
+                let keys: JwkSet = self.get_json(provider, &metadata.jwks_uri).await?;
+                let jwk = find(&keys);
+                if let Ok(mut cache) = self.keys.write() {
+                    cache.insert(provider.provider_id.clone(), keys);
+                }
+                jwk
+            }
+        };
+
+        let jwk = jwk.ok_or(UpstreamError::UnknownKey {
+            provider_id: provider.provider_id.clone(),
+            kid: kid.unwrap_or_default().to_string(),
+        })?;
+
+        DecodingKey::from_jwk(&jwk).map_err(|e| UpstreamError::InvalidIdToken {
+            provider_id: provider.provider_id.clone(),
+            inner_error: e.into(),
+        })
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        provider: &UpstreamProvider,
+        url: &str,
+    ) -> Result<T, UpstreamError> {
+        let request_error = |e: reqwest::Error| UpstreamError::Request {
+            provider_id: provider.provider_id.clone(),
+            inner_error: e.into(),
+        };
+
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(request_error)?
+            .json::<T>()
+            .await
+            .map_err(request_error)
+    }
+}
@@ -0,0 +1,48 @@
+use rand::Rng;
+
+const GROUP_LENGTH: usize = 4;
+/// Consonants only, RFC 8628 section 6.1, so codes can't spell words and survive being read
+/// off a TV screen. 20^8 codes, only guessable by a logged in user within the code's lifetime.
+const CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// A fresh user code for the device flow, formatted like `WDJB-MJHT`. As with opaque tokens
+/// only ever persist the digest of its normalized form.
+pub fn generate() -> String {
+    let mut rng = rand::rng();
+
+    let mut code = String::with_capacity(GROUP_LENGTH * 2 + 1);
+    for i in 0..GROUP_LENGTH * 2 {
+        if i == GROUP_LENGTH {
+            code.push('-');
+        }
+        code.push(CHARSET[rng.random_range(0..CHARSET.len())] as char);
+    }
+    code
+}
+
+/// Strips the formatting users tend to mangle, so `wdjb mjht` matches `WDJB-MJHT`
+pub fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::core::user_code::{CHARSET, generate, normalize};
+
+    #[test]
+    fn test_generate() {
+        let code = generate();
+        assert_eq!(9, code.len());
+        assert_eq!(Some(4), code.find('-'));
+        assert!(normalize(&code).bytes().all(|c| CHARSET.contains(&c)));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!("WDJBMJHT", normalize("wdjb mjht"));
+        assert_eq!("WDJBMJHT", normalize("WDJB-MJHT"));
+    }
+}
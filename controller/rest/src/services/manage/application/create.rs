@@ -1,6 +1,10 @@
 use poem_openapi::{ApiResponse, Object, payload::Json};
 
-use crate::{api::ApiRepositories, models::application::Application, util::error::ApiError};
+use crate::{
+    api::ApiRepositories,
+    models::application::{Application, ClientType},
+    util::error::ApiError,
+};
 
 #[derive(Debug, Object, Clone, Eq, PartialEq)]
 pub struct CreateApplicationPayload {
@@ -8,6 +12,12 @@ pub struct CreateApplicationPayload {
     application_id: String,
     display_name: Option<String>,
     description: String,
+    /// Defaults to `public`
+    client_type: Option<ClientType>,
+    /// Where `/oauth/authorize` may send users back to, the application can't be used as an
+    /// OAuth client without at least one
+    #[oai(default)]
+    redirect_uris: Vec<String>,
 }
 
 #[derive(ApiResponse)]
@@ -34,6 +44,11 @@ pub async fn create_application(
                 .display_name
                 .unwrap_or(payload.application_id.clone()),
             &payload.description,
+            &payload
+                .client_type
+                .unwrap_or(ClientType::Public)
+                .to_string(),
+            &payload.redirect_uris,
         )
         .await
     {
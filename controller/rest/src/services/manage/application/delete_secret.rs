@@ -0,0 +1,33 @@
+use data::repository::application::ApplicationError;
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum DeleteSecretResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+/// Shuts the client out of client_credentials, and of everything else once its tokens expire
+pub async fn delete_secret(
+    repositories: ApiRepositories,
+    application_id: String,
+    agent: &str,
+) -> DeleteSecretResponse {
+    match repositories
+        .application
+        .set_client_secret(agent, &application_id, None)
+        .await
+    {
+        Ok(_) => DeleteSecretResponse::Ok,
+        Err(ApplicationError::ApplicationNotFound { .. }) => DeleteSecretResponse::NotFound,
+        Err(e) => DeleteSecretResponse::Failed(Json(ApiError::from(e))),
+    }
+}
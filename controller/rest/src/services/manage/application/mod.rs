@@ -1,4 +1,7 @@
 pub mod create;
+pub mod delete_secret;
 pub mod get;
 pub mod list;
+pub mod modify_grant;
+pub mod rotate_secret;
 pub mod update;
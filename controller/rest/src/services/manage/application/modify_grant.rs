@@ -0,0 +1,45 @@
+use data::repository::application::ApplicationError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyClientGrantPayload {
+    /// The application whose service principal holds the grant
+    pub application_id: String,
+    pub grant_id: String,
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyClientGrantResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_client_grant(
+    repositories: ApiRepositories,
+    payload: ModifyClientGrantPayload,
+    agent: &str,
+) -> ModifyClientGrantResponse {
+    match repositories
+        .application
+        .update_client_grant(
+            agent,
+            &payload.application_id,
+            &payload.grant_id,
+            payload.enabled,
+        )
+        .await
+    {
+        Ok(_) => ModifyClientGrantResponse::Ok,
+        Err(ApplicationError::ApplicationNotFound { .. }) => ModifyClientGrantResponse::NotFound,
+        Err(e) => ModifyClientGrantResponse::Failed(Json(ApiError::from(e))),
+    }
+}
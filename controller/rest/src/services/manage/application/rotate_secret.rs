@@ -0,0 +1,58 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::application::ClientType,
+    services::{ApiServices, core::token},
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug)]
+pub struct RotateSecretResponsePayload {
+    /// Shown only this once, the previous secret stops working immediately
+    pub client_secret: String,
+}
+
+#[derive(ApiResponse)]
+pub enum RotateSecretResponse {
+    #[oai(status = 200)]
+    Ok(Json<RotateSecretResponsePayload>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    /// Public clients can't keep a secret
+    #[oai(status = 409)]
+    PublicClient,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn rotate_secret(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    application_id: String,
+    agent: &str,
+) -> RotateSecretResponse {
+    match repositories.application.by_id(&application_id).await {
+        Ok(Some(app)) if app.application.client_type == ClientType::Confidential.to_string() => {}
+        Ok(Some(_)) => return RotateSecretResponse::PublicClient,
+        Ok(None) => return RotateSecretResponse::NotFound,
+        Err(e) => return RotateSecretResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    let client_secret = token::generate();
+    let hash = match services.hasher.hash(&client_secret) {
+        Ok(hash) => hash,
+        Err(e) => return RotateSecretResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match repositories
+        .application
+        .set_client_secret(agent, &application_id, Some(&hash))
+        .await
+    {
+        Ok(_) => RotateSecretResponse::Ok(Json(RotateSecretResponsePayload { client_secret })),
+        Err(e) => RotateSecretResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -1,12 +1,19 @@
 use poem_openapi::{ApiResponse, Object, payload::Json};
 
-use crate::{api::ApiRepositories, models::application::Application, util::error::ApiError};
+use crate::{
+    api::ApiRepositories,
+    models::application::{Application, ClientType},
+    util::error::ApiError,
+};
 
 #[derive(Debug, Object, Clone, Eq, PartialEq)]
 pub struct UpdateApplicationPayload {
     application_id: String,
     display_name: Option<String>,
     description: Option<String>,
+    client_type: Option<ClientType>,
+    /// Replaces every registered redirect URI
+    redirect_uris: Option<Vec<String>>,
 }
 
 #[derive(ApiResponse)]
@@ -31,6 +38,11 @@ pub async fn update_application(
             &payload.application_id,
             payload.display_name.as_deref(),
             payload.description.as_deref(),
+            payload
+                .client_type
+                .map(|client_type| client_type.to_string())
+                .as_deref(),
+            payload.redirect_uris.as_deref(),
         )
         .await
     {
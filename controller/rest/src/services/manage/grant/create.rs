@@ -1,9 +1,14 @@
 use poem_openapi::{ApiResponse, Object, payload::Json};
 
-use crate::{api::ApiRepositories, models::grant::Grant, util::error::ApiError};
+use crate::{
+    api::ApiRepositories,
+    models::grant::Grant,
+    util::{error::ApiError, grant_pattern::GrantPattern},
+};
 
 #[derive(Debug, Object, Clone, Eq, PartialEq)]
 pub struct CreateGrantPayload {
+    /// May be a pattern, `*` standing for any one segment and a trailing `**` for any number
     #[oai(validator(min_length = 3))]
     grant_id: String,
     #[oai(validator(min_length = 3))]
@@ -16,6 +21,13 @@ pub struct CreateGrantPayload {
 pub enum CreateGrantResponse {
     #[oai(status = 200)]
     Ok(Json<Grant>),
+    /// The grant id isn't a valid pattern
+    #[oai(status = 400)]
+    Invalid(Json<ApiError>),
+    /// The grant id overlaps a grant of another application, whose checks its holders would
+    /// otherwise pass
+    #[oai(status = 409)]
+    Overlaps,
     #[oai(status = 500)]
     Failed(Json<ApiError>),
     #[oai(status = 401)]
@@ -27,6 +39,27 @@ pub async fn create_grant(
     payload: CreateGrantPayload,
     agent: &str,
 ) -> CreateGrantResponse {
+    let pattern = match payload.grant_id.parse::<GrantPattern>() {
+        Ok(pattern) => pattern,
+        Err(e) => return CreateGrantResponse::Invalid(Json(ApiError::from(e))),
+    };
+
+    // Checked both ways, a literal grant may also fall under another application's pattern
+    let foreign = match repositories
+        .grant
+        .outside_application(&payload.application_id)
+        .await
+    {
+        Ok(foreign) => foreign,
+        Err(e) => return CreateGrantResponse::Failed(Json(ApiError::from(e))),
+    };
+    if foreign
+        .iter()
+        .any(|grant| pattern.overlaps(&GrantPattern::lenient(&grant.grant_id)))
+    {
+        return CreateGrantResponse::Overlaps;
+    }
+
     match repositories
         .grant
         .create(
@@ -0,0 +1,31 @@
+use data::repository::grant::GrantError;
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories, models::grant_implication::GrantImplication, util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum ListGrantImplicationsResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<GrantImplication>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn list_grant_implications(
+    repositories: ApiRepositories,
+    grant_id: &str,
+) -> ListGrantImplicationsResponse {
+    match repositories.grant.implications_of(grant_id).await {
+        Ok(them) => ListGrantImplicationsResponse::Ok(Json(
+            them.into_iter().map(GrantImplication::from).collect(),
+        )),
+        Err(GrantError::GrantNotFound { .. }) => ListGrantImplicationsResponse::NotFound,
+        Err(e) => ListGrantImplicationsResponse::Failed(Json(ApiError::from(e))),
+    }
+}
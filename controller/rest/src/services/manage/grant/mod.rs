@@ -1,3 +1,5 @@
 pub mod create;
 pub mod get_by_application;
 pub mod get_by_id;
+pub mod list_implications;
+pub mod modify_implication;
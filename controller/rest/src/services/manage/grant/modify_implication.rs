@@ -0,0 +1,54 @@
+use data::repository::grant::GrantError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, services::ApiServices, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyGrantImplicationPayload {
+    pub grant_id: String,
+    /// May belong to any application
+    pub implied_grant_id: String,
+    /// Whether holding `grant_id` implies holding `implied_grant_id`
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyGrantImplicationResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// `implied_grant_id` already implies `grant_id`
+    #[oai(status = 409)]
+    Cycle,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_grant_implication(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: ModifyGrantImplicationPayload,
+    agent: &str,
+) -> ModifyGrantImplicationResponse {
+    match repositories
+        .grant
+        .update_implication(
+            agent,
+            &payload.grant_id,
+            &payload.implied_grant_id,
+            payload.enabled,
+        )
+        .await
+    {
+        Ok(_) => {
+            services.implications.invalidate();
+            ModifyGrantImplicationResponse::Ok
+        }
+        Err(GrantError::Cycle { .. }) => ModifyGrantImplicationResponse::Cycle,
+        Err(GrantError::GrantNotFound { .. }) => ModifyGrantImplicationResponse::NotFound,
+        Err(e) => ModifyGrantImplicationResponse::Failed(Json(ApiError::from(e))),
+    }
+}
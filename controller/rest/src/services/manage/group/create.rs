@@ -0,0 +1,41 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, models::group::Group, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct CreateGroupPayload {
+    #[oai(validator(min_length = 3))]
+    group_id: String,
+    display_name: Option<String>,
+    description: String,
+}
+
+#[derive(ApiResponse)]
+pub enum CreateGroupResponse {
+    #[oai(status = 200)]
+    Ok(Json<Group>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn create_group(
+    repositories: ApiRepositories,
+    payload: CreateGroupPayload,
+    agent: &str,
+) -> CreateGroupResponse {
+    match repositories
+        .group
+        .create(
+            agent,
+            &payload.group_id,
+            &payload.display_name.unwrap_or(payload.group_id.clone()),
+            &payload.description,
+        )
+        .await
+    {
+        Ok(group) => CreateGroupResponse::Ok(Json(Group::from(group))),
+        Err(e) => CreateGroupResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,23 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum DeleteGroupResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn delete_group(repositories: ApiRepositories, group_id: &str) -> DeleteGroupResponse {
+    match repositories.group.delete(group_id).await {
+        Ok(true) => DeleteGroupResponse::Ok,
+        Ok(false) => DeleteGroupResponse::NotFound,
+        Err(e) => DeleteGroupResponse::Failed(Json(ApiError::from(e))),
+    }
+}
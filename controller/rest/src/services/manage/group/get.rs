@@ -0,0 +1,35 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::group::{Group, GroupMember},
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum GetGroupResponse {
+    #[oai(status = 200)]
+    Ok(Json<Group>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn get_group(repositories: ApiRepositories, group_id: &str) -> GetGroupResponse {
+    let mut group = match repositories.group.by_id(group_id).await {
+        Ok(Some(group)) => Group::from(group),
+        Ok(None) => return GetGroupResponse::NotFound,
+        Err(e) => return GetGroupResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match repositories.group.members(group_id).await {
+        Ok(members) => {
+            group.members = members.into_iter().map(GroupMember::from).collect();
+            GetGroupResponse::Ok(Json(group))
+        }
+        Err(e) => GetGroupResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,20 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, models::group::Group, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum ListGroupsResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<Group>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn list_groups(repositories: ApiRepositories) -> ListGroupsResponse {
+    match repositories.group.list().await {
+        Ok(groups) => ListGroupsResponse::Ok(Json(groups.into_iter().map(Group::from).collect())),
+        Err(e) => ListGroupsResponse::Failed(Json(ApiError::from(e))),
+    }
+}
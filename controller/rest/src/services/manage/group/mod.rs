@@ -0,0 +1,8 @@
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod modify_grant;
+pub mod modify_member;
+pub mod modify_role;
+pub mod update;
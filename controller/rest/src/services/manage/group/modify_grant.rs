@@ -0,0 +1,42 @@
+use data::repository::group::GroupError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyGroupGrantPayload {
+    pub group_id: String,
+    pub grant_id: String,
+    /// Whether the group's members are given the grant
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyGroupGrantResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_group_grant(
+    repositories: ApiRepositories,
+    payload: ModifyGroupGrantPayload,
+    agent: &str,
+) -> ModifyGroupGrantResponse {
+    match repositories
+        .group
+        .update_grant(agent, &payload.group_id, &payload.grant_id, payload.enabled)
+        .await
+    {
+        Ok(_) => ModifyGroupGrantResponse::Ok,
+        Err(GroupError::GroupNotFound { .. } | GroupError::GrantNotFound { .. }) => {
+            ModifyGroupGrantResponse::NotFound
+        }
+        Err(e) => ModifyGroupGrantResponse::Failed(Json(ApiError::from(e))),
+    }
+}
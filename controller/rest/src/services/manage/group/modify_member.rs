@@ -0,0 +1,62 @@
+use data::repository::group::GroupError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+/// Names exactly one of `user_id` and `member_group_id`
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyGroupMemberPayload {
+    pub group_id: String,
+    pub user_id: Option<i32>,
+    /// A group to nest, its members inherit this group's grants and roles
+    pub member_group_id: Option<String>,
+    /// Whether the user or group is a member
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyGroupMemberResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 400)]
+    Invalid,
+    #[oai(status = 409)]
+    Cycle,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_group_member(
+    repositories: ApiRepositories,
+    payload: ModifyGroupMemberPayload,
+    agent: &str,
+) -> ModifyGroupMemberResponse {
+    let result = match (payload.user_id, payload.member_group_id.as_deref()) {
+        (Some(user_id), None) => {
+            repositories
+                .group
+                .update_user(agent, &payload.group_id, user_id, payload.enabled)
+                .await
+        }
+        (None, Some(member_group_id)) => {
+            repositories
+                .group
+                .update_member_group(agent, &payload.group_id, member_group_id, payload.enabled)
+                .await
+        }
+        _ => return ModifyGroupMemberResponse::Invalid,
+    };
+
+    match result {
+        Ok(_) => ModifyGroupMemberResponse::Ok,
+        Err(GroupError::Cycle { .. }) => ModifyGroupMemberResponse::Cycle,
+        Err(GroupError::GroupNotFound { .. } | GroupError::UserNotFound { .. }) => {
+            ModifyGroupMemberResponse::NotFound
+        }
+        Err(e) => ModifyGroupMemberResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,42 @@
+use data::repository::group::GroupError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyGroupRolePayload {
+    pub group_id: String,
+    pub role_id: String,
+    /// Whether the group's members are given the role
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyGroupRoleResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_group_role(
+    repositories: ApiRepositories,
+    payload: ModifyGroupRolePayload,
+    agent: &str,
+) -> ModifyGroupRoleResponse {
+    match repositories
+        .group
+        .update_role(agent, &payload.group_id, &payload.role_id, payload.enabled)
+        .await
+    {
+        Ok(_) => ModifyGroupRoleResponse::Ok,
+        Err(GroupError::GroupNotFound { .. } | GroupError::RoleNotFound { .. }) => {
+            ModifyGroupRoleResponse::NotFound
+        }
+        Err(e) => ModifyGroupRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,47 @@
+use data::repository::group::GroupError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, models::group::Group, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct UpdateGroupPayload {
+    group_id: String,
+    display_name: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum UpdateGroupResponse {
+    #[oai(status = 200)]
+    Ok(Json<Group>),
+    #[oai(status = 400)]
+    Invalid,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn update_group(
+    repositories: ApiRepositories,
+    payload: UpdateGroupPayload,
+    agent: &str,
+) -> UpdateGroupResponse {
+    match repositories
+        .group
+        .update(
+            agent,
+            &payload.group_id,
+            payload.display_name.as_deref(),
+            payload.description.as_deref(),
+        )
+        .await
+    {
+        Ok(group) => UpdateGroupResponse::Ok(Json(Group::from(group))),
+        Err(GroupError::NoChangeRequested) => UpdateGroupResponse::Invalid,
+        Err(GroupError::GroupNotFound { .. }) => UpdateGroupResponse::NotFound,
+        Err(e) => UpdateGroupResponse::Failed(Json(ApiError::from(e))),
+    }
+}
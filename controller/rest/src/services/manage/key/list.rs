@@ -0,0 +1,22 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{models::signing_key::SigningKey, services::ApiServices};
+
+#[derive(ApiResponse)]
+pub enum ListKeysResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<SigningKey>>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn list_keys(services: ApiServices) -> ListKeysResponse {
+    ListKeysResponse::Ok(Json(
+        services
+            .jwt
+            .keys()
+            .into_iter()
+            .map(SigningKey::from)
+            .collect(),
+    ))
+}
@@ -0,0 +1,53 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::signing_key::SigningKey,
+    services::{ApiServices, core::jwt::JwtError},
+    util::error::ApiError,
+};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct PromoteKeyPayload {
+    pub kid: String,
+}
+
+#[derive(ApiResponse)]
+pub enum PromoteKeyResponse {
+    #[oai(status = 200)]
+    Ok(Json<SigningKey>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+/// Records the promotion for every instance to pick up, then adopts it here straight away
+pub async fn promote_key(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    payload: PromoteKeyPayload,
+    agent: &str,
+) -> PromoteKeyResponse {
+    tracing::info!(
+        "Signing key promotion to kid={} requested by {agent}",
+        payload.kid
+    );
+
+    if !services.jwt.knows(&payload.kid) {
+        return PromoteKeyResponse::NotFound;
+    }
+
+    let promotion = match repositories.key_promotion.create(agent, &payload.kid).await {
+        Ok(promotion) => promotion,
+        Err(e) => return PromoteKeyResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match services.jwt.adopt(&promotion.kid, promotion.created_at) {
+        Ok(key) => PromoteKeyResponse::Ok(Json(SigningKey::from(key))),
+        Err(JwtError::UnknownKey { .. }) => PromoteKeyResponse::NotFound,
+        Err(e) => PromoteKeyResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -1,3 +1,6 @@
 pub mod application;
 pub mod grant;
+pub mod group;
+pub mod key;
+pub mod role;
 pub mod user;
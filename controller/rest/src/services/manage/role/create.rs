@@ -0,0 +1,49 @@
+use data::repository::role::RoleError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, models::role::Role, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct CreateRolePayload {
+    #[oai(validator(min_length = 3))]
+    role_id: String,
+    /// The application owning the role, only its grants can be added to it
+    #[oai(validator(min_length = 3))]
+    application_id: String,
+    display_name: Option<String>,
+    description: String,
+}
+
+#[derive(ApiResponse)]
+pub enum CreateRoleResponse {
+    #[oai(status = 200)]
+    Ok(Json<Role>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn create_role(
+    repositories: ApiRepositories,
+    payload: CreateRolePayload,
+    agent: &str,
+) -> CreateRoleResponse {
+    match repositories
+        .role
+        .create(
+            agent,
+            &payload.role_id,
+            &payload.application_id,
+            &payload.display_name.unwrap_or(payload.role_id.clone()),
+            &payload.description,
+        )
+        .await
+    {
+        Ok(role) => CreateRoleResponse::Ok(Json(Role::from(role))),
+        Err(RoleError::ApplicationNotFound { .. }) => CreateRoleResponse::NotFound,
+        Err(e) => CreateRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
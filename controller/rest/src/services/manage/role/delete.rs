@@ -0,0 +1,23 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum DeleteRoleResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn delete_role(repositories: ApiRepositories, role_id: &str) -> DeleteRoleResponse {
+    match repositories.role.delete(role_id).await {
+        Ok(true) => DeleteRoleResponse::Ok,
+        Ok(false) => DeleteRoleResponse::NotFound,
+        Err(e) => DeleteRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
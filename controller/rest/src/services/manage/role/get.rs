@@ -0,0 +1,23 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, models::role::Role, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum GetRoleResponse {
+    #[oai(status = 200)]
+    Ok(Json<Role>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn get_role(repositories: ApiRepositories, role_id: &str) -> GetRoleResponse {
+    match repositories.role.by_id(role_id).await {
+        Ok(Some(role)) => GetRoleResponse::Ok(Json(Role::from(role))),
+        Ok(None) => GetRoleResponse::NotFound,
+        Err(e) => GetRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,24 @@
+use data::repository::role::RoleError;
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, models::role::Role, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum ListRolesResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<Role>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn list_roles(repositories: ApiRepositories, application_id: &str) -> ListRolesResponse {
+    match repositories.role.by_application(application_id).await {
+        Ok(roles) => ListRolesResponse::Ok(Json(roles.into_iter().map(Role::from).collect())),
+        Err(RoleError::ApplicationNotFound { .. }) => ListRolesResponse::NotFound,
+        Err(e) => ListRolesResponse::Failed(Json(ApiError::from(e))),
+    }
+}
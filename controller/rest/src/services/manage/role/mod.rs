@@ -0,0 +1,6 @@
+pub mod create;
+pub mod delete;
+pub mod get;
+pub mod list;
+pub mod modify_grant;
+pub mod update;
@@ -0,0 +1,46 @@
+use data::repository::role::RoleError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyRoleGrantPayload {
+    pub role_id: String,
+    /// Must belong to the role's application
+    pub grant_id: String,
+    /// Whether the role includes the grant
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyRoleGrantResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 400)]
+    Invalid,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_role_grant(
+    repositories: ApiRepositories,
+    payload: ModifyRoleGrantPayload,
+    agent: &str,
+) -> ModifyRoleGrantResponse {
+    match repositories
+        .role
+        .update_grant(agent, &payload.role_id, &payload.grant_id, payload.enabled)
+        .await
+    {
+        Ok(_) => ModifyRoleGrantResponse::Ok,
+        Err(RoleError::GrantOutsideApplication { .. }) => ModifyRoleGrantResponse::Invalid,
+        Err(RoleError::RoleNotFound { .. } | RoleError::GrantNotFound { .. }) => {
+            ModifyRoleGrantResponse::NotFound
+        }
+        Err(e) => ModifyRoleGrantResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,47 @@
+use data::repository::role::RoleError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, models::role::Role, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct UpdateRolePayload {
+    role_id: String,
+    display_name: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum UpdateRoleResponse {
+    #[oai(status = 200)]
+    Ok(Json<Role>),
+    #[oai(status = 400)]
+    Invalid,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn update_role(
+    repositories: ApiRepositories,
+    payload: UpdateRolePayload,
+    agent: &str,
+) -> UpdateRoleResponse {
+    match repositories
+        .role
+        .update(
+            agent,
+            &payload.role_id,
+            payload.display_name.as_deref(),
+            payload.description.as_deref(),
+        )
+        .await
+    {
+        Ok(role) => UpdateRoleResponse::Ok(Json(Role::from(role))),
+        Err(RoleError::NoChangeRequested) => UpdateRoleResponse::Invalid,
+        Err(RoleError::RoleNotFound { .. }) => UpdateRoleResponse::NotFound,
+        Err(e) => UpdateRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::Utc;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::impersonation::ImpersonationToken,
+    services::{
+        ApiServices,
+        core::{jwt::Claims, token},
+    },
+    util::{error::ApiError, grants::holds_all_of, request::RequestContext},
+};
+
+#[derive(Object, Debug)]
+pub struct ImpersonatePayload {
+    /// Recorded in the audit trail, e.g. the support ticket being worked on
+    #[oai(validator(min_length = 1, max_length = 255))]
+    pub reason: String,
+}
+
+#[derive(ApiResponse)]
+pub enum ImpersonateResponse {
+    #[oai(status = 200)]
+    Ok(Json<ImpersonationToken>),
+    /// The caller themselves, or a disabled user
+    #[oai(status = 400)]
+    Invalid,
+    /// The user holds grants the caller doesn't, or the caller didn't log in as themselves
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+/// Mints a short lived token for `user_id` carrying the caller as its `act` claim, and records
+/// it in the audit trail before handing it out
+#[tracing::instrument(level = tracing::Level::INFO, "services.manage.user.impersonate", skip(repositories, services, claims, payload, context), fields(actor = %claims.subject))]
+pub async fn impersonate(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: Claims,
+    user_id: i32,
+    payload: ImpersonatePayload,
+    context: RequestContext,
+) -> ImpersonateResponse {
+    // Someone has to answer for it, so never a client or a token standing in for its user
+    let Some(actor_id) = claims.user_id().filter(|_| claims.is_first_party()) else {
+        return ImpersonateResponse::Forbidden;
+    };
+    if actor_id == user_id {
+        return ImpersonateResponse::Invalid;
+    }
+
+    let actor = match repositories.user.by_id(actor_id).await {
+        Ok(Some(actor)) => actor,
+        Ok(None) => return ImpersonateResponse::Forbidden,
+        Err(e) => return ImpersonateResponse::Failed(Json(ApiError::from(e))),
+    };
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return ImpersonateResponse::NotFound,
+        Err(e) => return ImpersonateResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    if !user.user.enabled {
+        return ImpersonateResponse::Invalid;
+    }
+
+    // Checked against the actor's current grants rather than their token's, which may be stale
+    if !holds_all_of(&actor, &user) {
+        tracing::warn!("User {actor_id} may not impersonate user {user_id}, who holds more grants");
+        return ImpersonateResponse::Forbidden;
+    }
+
+    let implications = match services.implications.get(&repositories).await {
+        Ok(implications) => implications,
+        Err(e) => return ImpersonateResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let agent = &format!("user.impersonate:{actor_id}");
+    let session_id = token::generate();
+    let expires_at = Utc::now() + services.lifetimes.impersonation;
+    let claims = Claims::impersonating(
+        &user,
+        &implications,
+        actor_id,
+        &session_id,
+        &claims.amr,
+        services.lifetimes.impersonation,
+    );
+
+    // A session of its own, so ending the user's sessions ends the impersonation too
+    if let Err(e) = repositories
+        .session
+        .create(
+            agent,
+            &session_id,
+            user_id,
+            &claims.token_id,
+            &claims.amr,
+            None,
+            &[],
+            expires_at,
+        )
+        .await
+    {
+        tracing::error!("Failed to start impersonation session: {:?}", e);
+        return ImpersonateResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    // Unlike login events, no token is handed out without its audit row
+    if let Err(e) = repositories
+        .impersonation_event
+        .create(
+            agent,
+            user_id,
+            actor_id,
+            &actor.user.username,
+            &session_id,
+            &payload.reason,
+            expires_at,
+            context.source_ip.as_deref(),
+            context.user_agent.as_deref(),
+        )
+        .await
+    {
+        tracing::error!("Failed to record impersonation: {:?}", e);
+        return ImpersonateResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    let token = match services.jwt.sign(&claims) {
+        Ok(token) => token,
+        Err(e) => return ImpersonateResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    tracing::info!(
+        "User {actor_id} is impersonating user {user_id}: {}",
+        payload.reason
+    );
+
+    ImpersonateResponse::Ok(Json(ImpersonationToken {
+        token,
+        claims,
+        expires_at,
+    }))
+}
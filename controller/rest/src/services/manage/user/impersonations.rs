@@ -0,0 +1,32 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories, models::impersonation::ImpersonationEvent, util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum ImpersonationsResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<ImpersonationEvent>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn impersonations(
+    repositories: ApiRepositories,
+    user_id: i32,
+    limit: u64,
+) -> ImpersonationsResponse {
+    match repositories
+        .impersonation_event
+        .by_user(user_id, limit)
+        .await
+    {
+        Ok(events) => ImpersonationsResponse::Ok(Json(
+            events.into_iter().map(ImpersonationEvent::from).collect(),
+        )),
+        Err(e) => ImpersonationsResponse::Failed(Json(ApiError::from(e))),
+    }
+}
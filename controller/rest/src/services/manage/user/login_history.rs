@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::login_event::{LoginEvent, LoginOutcome},
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum LoginHistoryResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<LoginEvent>>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn login_history(
+    repositories: ApiRepositories,
+    user_id: i32,
+    outcome: Option<LoginOutcome>,
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: u64,
+) -> LoginHistoryResponse {
+    match repositories
+        .login_event
+        .by_user(
+            user_id,
+            outcome.map(|outcome| outcome.to_string()).as_deref(),
+            from,
+            until,
+            limit,
+        )
+        .await
+    {
+        Ok(events) => {
+            LoginHistoryResponse::Ok(Json(events.into_iter().map(LoginEvent::from).collect()))
+        }
+        Err(e) => LoginHistoryResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -1,5 +1,12 @@
 pub mod create;
 pub mod delete;
 pub mod get;
+pub mod impersonate;
+pub mod impersonations;
 pub mod list;
+pub mod login_history;
 pub mod modify_grant;
+pub mod modify_role;
+pub mod reset_password;
+pub mod revoke_sessions;
+pub mod unlock;
@@ -1,4 +1,6 @@
-use poem_openapi::{ApiResponse, Object, payload::Json};
+use chrono::{DateTime, Utc};
+use data::repository::user::UserError;
+use poem_openapi::{ApiResponse, Object, payload::Json, types::MaybeUndefined};
 
 use crate::{api::ApiRepositories, util::error::ApiError};
 
@@ -7,12 +9,30 @@ pub struct ModifyGrantPayload {
     pub user_id: i32,
     pub grant_id: String,
     pub enabled: bool,
+    /// The grant only counts from then on. Left as it is when left out, `null` lets it count
+    /// immediately.
+    pub valid_from: MaybeUndefined<DateTime<Utc>>,
+    /// The grant stops counting then and is disabled soon after. Left as it is when left out,
+    /// `null` means it never expires.
+    pub valid_until: MaybeUndefined<DateTime<Utc>>,
+}
+
+/// `None` to leave a value as it is, `Some(None)` to clear it
+fn change<T>(value: MaybeUndefined<T>) -> Option<Option<T>> {
+    match value {
+        MaybeUndefined::Undefined => None,
+        MaybeUndefined::Null => Some(None),
+        MaybeUndefined::Value(value) => Some(Some(value)),
+    }
 }
 
 #[derive(ApiResponse)]
 pub enum ModifyGrantResponse {
     #[oai(status = 200)]
     Ok,
+    /// `valid_until` isn't after `valid_from`, or has passed on a grant being enabled
+    #[oai(status = 400)]
+    Invalid,
     #[oai(status = 500)]
     Failed(Json<ApiError>),
     #[oai(status = 401)]
@@ -26,10 +46,19 @@ pub async fn modify_grant(
 ) -> ModifyGrantResponse {
     match repositories
         .user
-        .update_grant(agent, payload.user_id, &payload.grant_id, payload.enabled)
+        .update_grant(
+            agent,
+            payload.user_id,
+            &payload.grant_id,
+            payload.enabled,
+            change(payload.valid_from),
+            change(payload.valid_until),
+        )
         .await
     {
         Ok(_) => ModifyGrantResponse::Ok,
+        Err(UserError::InvalidValidity { .. }) => ModifyGrantResponse::Invalid,
+        Err(UserError::ValidityExpired { .. }) => ModifyGrantResponse::Invalid,
         Err(e) => ModifyGrantResponse::Failed(Json(ApiError::from(e))),
     }
 }
@@ -0,0 +1,42 @@
+use data::repository::role::RoleError;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{api::ApiRepositories, util::error::ApiError};
+
+#[derive(Debug, Object, Clone, Eq, PartialEq)]
+pub struct ModifyRolePayload {
+    pub user_id: i32,
+    pub role_id: String,
+    /// Whether the user is assigned the role
+    pub enabled: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum ModifyRoleResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn modify_role(
+    repositories: ApiRepositories,
+    payload: ModifyRolePayload,
+    agent: &str,
+) -> ModifyRoleResponse {
+    match repositories
+        .role
+        .update_user(agent, payload.user_id, &payload.role_id, payload.enabled)
+        .await
+    {
+        Ok(_) => ModifyRoleResponse::Ok,
+        Err(RoleError::RoleNotFound { .. } | RoleError::UserNotFound { .. }) => {
+            ModifyRoleResponse::NotFound
+        }
+        Err(e) => ModifyRoleResponse::Failed(Json(ApiError::from(e))),
+    }
+}
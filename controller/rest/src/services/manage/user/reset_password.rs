@@ -0,0 +1,86 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{ApiServices, auth::session::end_user_sessions, core::jwt::Claims},
+    util::{error::ApiError, grants::holds_all_of},
+};
+
+#[derive(Object, Debug)]
+pub struct ResetPasswordPayload {
+    /// Temporary, the user has to replace it when they next log in
+    #[oai(validator(min_length = 8))]
+    pub password: String,
+}
+
+#[derive(ApiResponse)]
+pub enum ResetPasswordResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// The user holds grants the caller doesn't, or the caller didn't log in as themselves
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+/// Sets a temporary password, logs the user out everywhere and clears any lockout
+pub async fn reset_password(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: Claims,
+    user_id: i32,
+    payload: ResetPasswordPayload,
+    agent: &str,
+) -> ResetPasswordResponse {
+    // Someone has to answer for it, so never a client or a token standing in for its user
+    let Some(actor_id) = claims.user_id().filter(|_| claims.is_first_party()) else {
+        return ResetPasswordResponse::Forbidden;
+    };
+
+    let actor = match repositories.user.by_id(actor_id).await {
+        Ok(Some(actor)) => actor,
+        Ok(None) => return ResetPasswordResponse::Forbidden,
+        Err(e) => return ResetPasswordResponse::Failed(Json(ApiError::from(e))),
+    };
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return ResetPasswordResponse::NotFound,
+        Err(e) => return ResetPasswordResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    // Otherwise logging in with the new password would hand the caller grants they don't hold
+    if !holds_all_of(&actor, &user) {
+        tracing::warn!(
+            "User {actor_id} may not reset the password of user {user_id}, who holds more grants"
+        );
+        return ResetPasswordResponse::Forbidden;
+    }
+
+    let hash = match services.hasher.hash(&payload.password) {
+        Ok(hash) => hash,
+        Err(e) => return ResetPasswordResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    if let Err(e) = repositories
+        .user
+        .set_password(agent, user_id, &hash, true)
+        .await
+    {
+        return ResetPasswordResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    if let Err(e) = end_user_sessions(&repositories, &services, user_id, None, agent).await {
+        return ResetPasswordResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    if let Err(e) = services.throttle.unlock(user.user.user_id).await {
+        return ResetPasswordResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    ResetPasswordResponse::Ok
+}
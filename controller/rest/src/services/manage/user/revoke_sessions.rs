@@ -0,0 +1,34 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    services::{
+        ApiServices,
+        auth::{logout::LogoutAllResponsePayload, session::end_user_sessions},
+    },
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum RevokeSessionsResponse {
+    #[oai(status = 200)]
+    Ok(Json<LogoutAllResponsePayload>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+pub async fn revoke_sessions(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+    agent: &str,
+) -> RevokeSessionsResponse {
+    match end_user_sessions(&repositories, &services, user_id, None, agent).await {
+        Ok(revoked_sessions) => {
+            RevokeSessionsResponse::Ok(Json(LogoutAllResponsePayload { revoked_sessions }))
+        }
+        Err(e) => RevokeSessionsResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,33 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{api::ApiRepositories, services::ApiServices, util::error::ApiError};
+
+#[derive(ApiResponse)]
+pub enum UnlockUserResponse {
+    #[oai(status = 200)]
+    Ok,
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+    #[oai(status = 401)]
+    Unauthorized,
+}
+
+/// Clears the user's failed login counter, addresses that were locked out stay locked out
+pub async fn unlock_user(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    user_id: i32,
+) -> UnlockUserResponse {
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return UnlockUserResponse::NotFound,
+        Err(e) => return UnlockUserResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    match services.throttle.unlock(user.user.user_id).await {
+        Ok(_) => UnlockUserResponse::Ok,
+        Err(e) => UnlockUserResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use libbuildinfo::BuildInfo;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -5,15 +7,24 @@ use valuable::Valuable;
 
 use crate::{
     Args,
+    api::ApiRepositories,
     services::core::{
         hasher::{Hasher, HasherError},
-        jwt::{Jwt, JwtError},
+        implication::ImplicationCache,
+        jwt::{Jwt, JwtError, Keyring},
+        lifetimes::Lifetimes,
+        mailer::{self, Mailer, MailerError},
+        revocation::RevocationCache,
+        throttle::LoginThrottle,
+        totp::Totp,
+        upstream::{Upstream, UpstreamError},
     },
 };
 
 pub mod auth;
 pub mod core;
 pub mod manage;
+pub mod oauth;
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
 pub enum ApiServicesError {
@@ -27,18 +38,87 @@ pub enum ApiServicesError {
         #[from]
         inner_error: HasherError,
     },
+    #[error(transparent)]
+    Mailer {
+        #[from]
+        inner_error: MailerError,
+    },
+    #[error(transparent)]
+    Upstream {
+        #[from]
+        inner_error: UpstreamError,
+    },
 }
 
 #[derive(Clone, Debug)]
 pub struct ApiServices {
     pub hasher: Hasher,
     pub jwt: Jwt,
+    pub lifetimes: Lifetimes,
+    pub revocations: RevocationCache,
+    pub implications: ImplicationCache,
+    pub throttle: LoginThrottle,
+    pub totp: Totp,
+    pub mailer: Arc<dyn Mailer>,
+    pub upstream: Upstream,
+    /// Page password reset mails link to, the bare token is mailed when unset
+    pub password_reset_url: Option<String>,
+    /// How long after a reset mail another one may be sent to the same user
+    pub password_reset_cooldown: chrono::Duration,
+    /// Page email verification mails link to, the bare token is mailed when unset
+    pub email_verification_url: Option<String>,
+    /// Page `/oauth/authorize` sends users to, OAuth logins are unavailable when unset
+    pub oauth_consent_url: Option<String>,
+    /// `iss` of ID tokens and the base of every URL in the discovery document
+    pub issuer_url: String,
+    /// Page devices send users to, the device flow is unavailable when unset
+    pub device_verification_url: Option<String>,
+    /// Seconds devices must wait between polls of `/oauth/token`
+    pub device_code_interval_seconds: i32,
+    /// Page upstream providers redirect back to, federated logins are unavailable when unset
+    pub federation_callback_url: Option<String>,
+    /// Re-check every bearer token against the user's current state
+    pub revalidate_tokens: bool,
+    /// Take the client address from `X-Forwarded-For`, only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+    /// Accept a verified email in place of the username at `/login`
+    pub login_by_email: bool,
 }
 impl ApiServices {
-    pub async fn new(args: &Args, _build_info: &BuildInfo) -> Result<Self, ApiServicesError> {
+    pub async fn new(
+        args: &Args,
+        _build_info: &BuildInfo,
+        repositories: &ApiRepositories,
+    ) -> Result<Self, ApiServicesError> {
+        let lifetimes = Lifetimes::new(args);
+
         Ok(Self {
-            hasher: Hasher::new()?,
-            jwt: Jwt::new(&args.signing_key)?,
+            hasher: Hasher::from_args(args)?,
+            jwt: Jwt::new(Keyring::from_args(args)?, lifetimes.longest_signed()),
+            lifetimes,
+            revocations: RevocationCache::new(std::time::Duration::from_secs(
+                args.session_cache_ttl_seconds,
+            )),
+            implications: ImplicationCache::new(std::time::Duration::from_secs(
+                args.implication_cache_ttl_seconds,
+            )),
+            throttle: LoginThrottle::from_args(args, repositories.login_throttle.clone()),
+            totp: Totp::from_args(args),
+            mailer: mailer::from_args(args)?,
+            upstream: Upstream::from_args(args)?,
+            password_reset_url: args.password_reset_url.clone(),
+            password_reset_cooldown: chrono::Duration::seconds(
+                args.password_reset_cooldown_seconds,
+            ),
+            email_verification_url: args.email_verification_url.clone(),
+            oauth_consent_url: args.oauth_consent_url.clone(),
+            issuer_url: args.public_url(),
+            device_verification_url: args.device_verification_url.clone(),
+            device_code_interval_seconds: args.device_code_interval_seconds,
+            federation_callback_url: args.federation_callback_url.clone(),
+            revalidate_tokens: args.revalidate_tokens,
+            trust_forwarded_for: args.trust_forwarded_for,
+            login_by_email: args.login_by_email,
         })
     }
 }
@@ -0,0 +1,347 @@
+use chrono::Utc;
+use data::dto::application::ApplicationDetailDto;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::oauth::{AuthorizationRedirect, ConsentPrompt, OAuthError, OAuthErrorCode},
+    services::{
+        ApiServices,
+        core::{jwt::UserClaims, pkce, token},
+        oauth::{error_redirect, parse_scope, with_query},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Debug, Clone)]
+pub struct AuthorizationRequest {
+    /// Must be `code`
+    pub response_type: String,
+    pub client_id: String,
+    /// May be left out when the client registered exactly one
+    pub redirect_uri: Option<String>,
+    pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    /// Must be `S256`, which is also the default
+    pub code_challenge_method: Option<String>,
+    /// Space separated, include `openid` for an ID token. Unsupported scopes are ignored.
+    pub scope: Option<String>,
+    /// Echoed in the ID token so the client can tie it to this request
+    pub nonce: Option<String>,
+}
+
+#[derive(Object, Debug)]
+pub struct ApproveAuthorizationPayload {
+    #[oai(flatten)]
+    pub request: AuthorizationRequest,
+    /// `false` sends the user back to the client with `access_denied`
+    pub approve: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum AuthorizeResponse {
+    /// On to the consent page, or straight back to the client with an error
+    #[oai(status = 302)]
+    Redirect(#[oai(header = "Location")] String),
+    /// The client or redirect URI is unknown, so there's nowhere safe to send the error
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    /// No consent page is configured
+    #[oai(status = 503)]
+    Unavailable,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum ApproveAuthorizationResponse {
+    #[oai(status = 200)]
+    Ok(Json<AuthorizationRedirect>),
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    /// Only tokens from a first party login can approve clients
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum ConsentPromptResponse {
+    #[oai(status = 200)]
+    Ok(Json<ConsentPrompt>),
+    /// Only tokens from a first party login can approve clients
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+enum Rejection {
+    /// The client or redirect URI can't be trusted, so the error goes to the user instead
+    Invalid(OAuthError),
+    /// Sent back to the client's redirect URI
+    Redirect(String),
+    Failed(ApiError),
+}
+
+struct ValidatedRequest {
+    application: ApplicationDetailDto,
+    redirect_uri: String,
+    /// Given by the client rather than defaulted to its only registered one
+    redirect_uri_explicit: bool,
+    code_challenge: String,
+    scope: Vec<String>,
+}
+
+async fn validate(
+    repositories: &ApiRepositories,
+    request: &AuthorizationRequest,
+) -> Result<ValidatedRequest, Rejection> {
+    let application = match repositories.application.by_id(&request.client_id).await {
+        Ok(Some(application)) => application,
+        Ok(None) => {
+            return Err(Rejection::Invalid(OAuthError::new(
+                OAuthErrorCode::InvalidClient,
+                "Unknown client_id",
+            )));
+        }
+        Err(e) => return Err(Rejection::Failed(ApiError::from(e))),
+    };
+
+    // Matched exactly, anything looser lets an attacker steer the code somewhere else
+    let redirect_uri = match &request.redirect_uri {
+        Some(redirect_uri) if application.redirect_uris.contains(redirect_uri) => {
+            redirect_uri.clone()
+        }
+        None if application.redirect_uris.len() == 1 => application.redirect_uris[0].clone(),
+        _ => {
+            return Err(Rejection::Invalid(OAuthError::new(
+                OAuthErrorCode::InvalidRequest,
+                "redirect_uri is not registered for this client",
+            )));
+        }
+    };
+
+    let reject = |error, error_description| {
+        Rejection::Redirect(error_redirect(
+            &redirect_uri,
+            error,
+            error_description,
+            request.state.as_deref(),
+        ))
+    };
+
+    if request.response_type != "code" {
+        return Err(reject(
+            OAuthErrorCode::UnsupportedResponseType,
+            "Only the code response type is supported",
+        ));
+    }
+    if request
+        .code_challenge_method
+        .as_deref()
+        .unwrap_or(pkce::S256)
+        != pkce::S256
+    {
+        return Err(reject(
+            OAuthErrorCode::InvalidRequest,
+            "code_challenge_method must be S256",
+        ));
+    }
+    let Some(code_challenge) = request
+        .code_challenge
+        .clone()
+        .filter(|code_challenge| pkce::is_valid_challenge(code_challenge))
+    else {
+        return Err(reject(
+            OAuthErrorCode::InvalidRequest,
+            "A valid S256 code_challenge is required",
+        ));
+    };
+
+    if request
+        .nonce
+        .as_ref()
+        .is_some_and(|nonce| nonce.len() > 255)
+    {
+        return Err(reject(
+            OAuthErrorCode::InvalidRequest,
+            "nonce must be at most 255 characters",
+        ));
+    }
+
+    Ok(ValidatedRequest {
+        application,
+        redirect_uri,
+        redirect_uri_explicit: request.redirect_uri.is_some(),
+        code_challenge,
+        scope: parse_scope(request.scope.as_deref()),
+    })
+}
+
+/// Checks the request and sends the user on to the consent page with the same parameters,
+/// which it posts back to `approve_authorization` once the user is logged in
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.authorize", skip(repositories, services))]
+pub async fn authorize(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    request: AuthorizationRequest,
+) -> AuthorizeResponse {
+    let Some(consent_url) = &services.oauth_consent_url else {
+        return AuthorizeResponse::Unavailable;
+    };
+
+    match validate(&repositories, &request).await {
+        Ok(_) => {}
+        Err(Rejection::Invalid(error)) => return AuthorizeResponse::Invalid(Json(error)),
+        Err(Rejection::Redirect(redirect_to)) => return AuthorizeResponse::Redirect(redirect_to),
+        Err(Rejection::Failed(e)) => return AuthorizeResponse::Failed(Json(e)),
+    }
+
+    let params = [
+        ("response_type", Some(request.response_type.as_str())),
+        ("client_id", Some(request.client_id.as_str())),
+        ("redirect_uri", request.redirect_uri.as_deref()),
+        ("state", request.state.as_deref()),
+        ("code_challenge", request.code_challenge.as_deref()),
+        (
+            "code_challenge_method",
+            request.code_challenge_method.as_deref(),
+        ),
+        ("scope", request.scope.as_deref()),
+        ("nonce", request.nonce.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.map(|value| (key, value)))
+    .collect::<Vec<_>>();
+
+    AuthorizeResponse::Redirect(with_query(consent_url, &params))
+}
+
+/// Describes the client so the consent page can ask the user about it
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.consent_prompt", skip(repositories, claims), fields(user_id = claims.user_id))]
+pub async fn consent_prompt(
+    repositories: ApiRepositories,
+    claims: UserClaims,
+    client_id: String,
+) -> ConsentPromptResponse {
+    if !claims.is_first_party() {
+        return ConsentPromptResponse::Forbidden;
+    }
+
+    let application = match repositories.application.by_id(&client_id).await {
+        Ok(Some(application)) => application.application,
+        Ok(None) => return ConsentPromptResponse::NotFound,
+        Err(e) => return ConsentPromptResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let user = match repositories.user.by_id(claims.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return ConsentPromptResponse::NotFound,
+        Err(e) => return ConsentPromptResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let consented = match repositories
+        .consent
+        .exists(user.user.user_id, &client_id)
+        .await
+    {
+        Ok(consented) => consented,
+        Err(e) => return ConsentPromptResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    ConsentPromptResponse::Ok(Json(ConsentPrompt {
+        grants: user
+            .effective_grants()
+            .into_iter()
+            .filter(|grant| grant.application_id == client_id)
+            .map(|grant| grant.grant_id.clone())
+            .collect(),
+        client_id,
+        display_name: application.display_name,
+        description: application.description,
+        consented,
+    }))
+}
+
+/// Records the user's decision and hands back where to send them: to the client's redirect
+/// URI with a single use code, or with `access_denied`
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.approve_authorization", skip(repositories, services, claims, payload), fields(user_id = claims.user_id))]
+pub async fn approve_authorization(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    claims: UserClaims,
+    payload: ApproveAuthorizationPayload,
+) -> ApproveAuthorizationResponse {
+    // Otherwise a client could use its own tokens to approve itself, or any other client, and
+    // a leaked personal access token could be traded for a refreshable session
+    if !claims.is_first_party() {
+        return ApproveAuthorizationResponse::Forbidden;
+    }
+
+    let request = payload.request;
+    let validated = match validate(&repositories, &request).await {
+        Ok(validated) => validated,
+        Err(Rejection::Invalid(error)) => {
+            return ApproveAuthorizationResponse::Invalid(Json(error));
+        }
+        Err(Rejection::Redirect(redirect_to)) => {
+            return ApproveAuthorizationResponse::Ok(Json(AuthorizationRedirect { redirect_to }));
+        }
+        Err(Rejection::Failed(e)) => return ApproveAuthorizationResponse::Failed(Json(e)),
+    };
+
+    if !payload.approve {
+        return ApproveAuthorizationResponse::Ok(Json(AuthorizationRedirect {
+            redirect_to: error_redirect(
+                &validated.redirect_uri,
+                OAuthErrorCode::AccessDenied,
+                "The user denied the request",
+                request.state.as_deref(),
+            ),
+        }));
+    }
+
+    let client_id = &validated.application.application.application_id;
+    let agent = &format!("oauth.authorize:{}", claims.user_id);
+    if let Err(e) = repositories
+        .consent
+        .grant(agent, claims.user_id, client_id)
+        .await
+    {
+        return ApproveAuthorizationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    let code = token::generate();
+    if let Err(e) = repositories
+        .authorization_code
+        .create(
+            agent,
+            claims.user_id,
+            client_id,
+            &token::digest(&code),
+            &validated.redirect_uri,
+            validated.redirect_uri_explicit,
+            &validated.code_challenge,
+            &claims.amr,
+            &validated.scope,
+            request.nonce.as_deref(),
+            Utc::now() + services.lifetimes.authorization_code,
+        )
+        .await
+    {
+        return ApproveAuthorizationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    let mut params = vec![("code", code.as_str())];
+    if let Some(state) = request.state.as_deref() {
+        params.push(("state", state));
+    }
+
+    ApproveAuthorizationResponse::Ok(Json(AuthorizationRedirect {
+        redirect_to: with_query(&validated.redirect_uri, &params),
+    }))
+}
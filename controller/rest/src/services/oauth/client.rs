@@ -0,0 +1,94 @@
+use data::dto::application::ApplicationDetailDto;
+
+use crate::{
+    api::ApiRepositories,
+    models::{
+        application::ClientType,
+        oauth::{OAuthError, OAuthErrorCode},
+    },
+    services::ApiServices,
+    util::error::ApiError,
+};
+
+/// Why a client couldn't be identified at one of the `/oauth` endpoints
+pub enum ClientAuthError {
+    /// The request itself is malformed, answered with `400`
+    Invalid(OAuthError),
+    /// Unknown client or wrong secret, answered with `401`
+    InvalidClient(OAuthError),
+    Failed(ApiError),
+}
+impl ClientAuthError {
+    fn invalid_client(error_description: &str) -> Self {
+        Self::InvalidClient(OAuthError::new(
+            OAuthErrorCode::InvalidClient,
+            error_description,
+        ))
+    }
+}
+
+/// Identifies the client, which must prove itself with its secret when confidential.
+/// `basic` holds the client id and secret from an `Authorization: Basic` header, which takes
+/// precedence over `client_id` and `client_secret` from the form.
+pub async fn authenticate_client(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+    basic: Option<(String, String)>,
+) -> Result<ApplicationDetailDto, ClientAuthError> {
+    let (client_id, client_secret) = match basic {
+        Some((basic_client_id, _)) if client_id.is_some_and(|it| it != basic_client_id) => {
+            return Err(ClientAuthError::Invalid(OAuthError::new(
+                OAuthErrorCode::InvalidRequest,
+                "client_id doesn't match the Authorization header",
+            )));
+        }
+        Some((client_id, client_secret)) => (client_id, Some(client_secret)),
+        None => match client_id {
+            Some(client_id) => (client_id.to_string(), client_secret.map(String::from)),
+            None => {
+                return Err(ClientAuthError::Invalid(OAuthError::new(
+                    OAuthErrorCode::InvalidRequest,
+                    "client_id is required",
+                )));
+            }
+        },
+    };
+
+    let application = match repositories.application.by_id(&client_id).await {
+        Ok(Some(application)) => application,
+        Ok(None) => {
+            if let Some(client_secret) = &client_secret {
+                services.hasher.dummy_verification(client_secret);
+            }
+            return Err(ClientAuthError::invalid_client("Unknown client_id"));
+        }
+        Err(e) => return Err(ClientAuthError::Failed(ApiError::from(e))),
+    };
+
+    if !is_confidential(&application) {
+        return Ok(application);
+    }
+
+    let (Some(client_secret), Some(hash)) =
+        (&client_secret, &application.application.client_secret)
+    else {
+        return Err(ClientAuthError::invalid_client(
+            "Confidential clients must authenticate with their client_secret",
+        ));
+    };
+    if services.hasher.verify(hash, client_secret).is_err() {
+        tracing::warn!("Invalid client secret for client: {}", client_id);
+        return Err(ClientAuthError::invalid_client(
+            "Invalid client credentials",
+        ));
+    }
+
+    Ok(application)
+}
+
+/// Only confidential clients have a secret, so only they are known to be who they say
+pub fn is_confidential(application: &ApplicationDetailDto) -> bool {
+    application.application.client_type == ClientType::Confidential.to_string()
+}
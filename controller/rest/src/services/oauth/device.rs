@@ -0,0 +1,241 @@
+use chrono::Utc;
+use data::dto::{application::ApplicationDetailDto, device_code::DeviceCodeDto};
+use poem_openapi::{ApiResponse, Object, payload::Json};
+use serde::Deserialize;
+
+use crate::{
+    api::ApiRepositories,
+    models::oauth::{DeviceAuthorization, DevicePrompt, OAuthError},
+    services::{
+        ApiServices,
+        core::{jwt::UserClaims, token, user_code},
+        oauth::{
+            client::{ClientAuthError, authenticate_client},
+            parse_scope, with_query,
+        },
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Deserialize, Debug)]
+pub struct DeviceAuthorizationRequest {
+    pub client_id: Option<String>,
+    /// For confidential clients that can't send HTTP Basic credentials
+    pub client_secret: Option<String>,
+    /// Space separated, unsupported scopes are ignored
+    pub scope: Option<String>,
+}
+
+#[derive(Object, Debug)]
+pub struct DeviceDecisionPayload {
+    pub user_code: String,
+    /// `false` makes the device's next poll fail with `access_denied`
+    pub approve: bool,
+}
+
+#[derive(ApiResponse)]
+pub enum DeviceAuthorizationResponse {
+    #[oai(status = 200)]
+    Ok(
+        Json<DeviceAuthorization>,
+        #[oai(header = "Cache-Control")] String,
+    ),
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    #[oai(status = 401)]
+    InvalidClient(Json<OAuthError>),
+    /// No verification page is configured
+    #[oai(status = 503)]
+    Unavailable,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum DevicePromptResponse {
+    #[oai(status = 200)]
+    Ok(Json<DevicePrompt>),
+    /// Only tokens from a first party login can approve devices
+    #[oai(status = 403)]
+    Forbidden,
+    /// Unknown or expired code, or one that was already approved or denied
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+#[derive(ApiResponse)]
+pub enum DeviceDecisionResponse {
+    #[oai(status = 200)]
+    Ok,
+    /// Only tokens from a first party login can approve devices
+    #[oai(status = 403)]
+    Forbidden,
+    /// Unknown or expired code, or one that was already approved or denied
+    #[oai(status = 404)]
+    NotFound,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// RFC 8628 section 3.1, starts the flow for a device that can't receive a redirect
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.device_authorization", skip(repositories, services, request, basic))]
+pub async fn device_authorization(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    request: DeviceAuthorizationRequest,
+    basic: Option<(String, String)>,
+) -> DeviceAuthorizationResponse {
+    let Some(verification_url) = &services.device_verification_url else {
+        return DeviceAuthorizationResponse::Unavailable;
+    };
+
+    let application = match authenticate_client(
+        &repositories,
+        &services,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic,
+    )
+    .await
+    {
+        Ok(application) => application,
+        Err(ClientAuthError::Invalid(error)) => {
+            return DeviceAuthorizationResponse::Invalid(Json(error));
+        }
+        Err(ClientAuthError::InvalidClient(error)) => {
+            return DeviceAuthorizationResponse::InvalidClient(Json(error));
+        }
+        Err(ClientAuthError::Failed(e)) => return DeviceAuthorizationResponse::Failed(Json(e)),
+    };
+    let client_id = &application.application.application_id;
+
+    let device_code = token::generate();
+    let user_code = user_code::generate();
+    let agent = &format!("oauth.device_authorization:{}", client_id);
+    let expires_at = Utc::now() + services.lifetimes.device_code;
+    if let Err(e) = repositories
+        .device_code
+        .create(
+            agent,
+            client_id,
+            &token::digest(&device_code),
+            &token::digest(&user_code::normalize(&user_code)),
+            &parse_scope(request.scope.as_deref()),
+            services.device_code_interval_seconds,
+            expires_at,
+        )
+        .await
+    {
+        return DeviceAuthorizationResponse::Failed(Json(ApiError::from(e)));
+    }
+
+    DeviceAuthorizationResponse::Ok(
+        Json(DeviceAuthorization {
+            device_code,
+            verification_uri_complete: with_query(
+                verification_url,
+                &[("user_code", user_code.as_str())],
+            ),
+            user_code,
+            verification_uri: verification_url.clone(),
+            expires_in: services.lifetimes.device_code.num_seconds() as u64,
+            interval: services.device_code_interval_seconds as u64,
+        }),
+        "no-store".to_string(),
+    )
+}
+
+/// The device code behind `user_code` and the client it was issued to, if it still awaits a
+/// decision
+async fn pending(
+    repositories: &ApiRepositories,
+    user_code: &str,
+) -> Result<Option<(DeviceCodeDto, ApplicationDetailDto)>, ApiError> {
+    let code = match repositories
+        .device_code
+        .by_user_code_hash(&token::digest(&user_code::normalize(user_code)))
+        .await?
+    {
+        Some(code) if code.is_pending() => code,
+        _ => return Ok(None),
+    };
+
+    Ok(repositories
+        .application
+        .by_id(&code.application_id)
+        .await?
+        .map(|application| (code, application)))
+}
+
+/// Describes the device's client so the verification page can ask the user about it
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.device_prompt", skip(repositories, claims, user_code), fields(user_id = claims.user_id))]
+pub async fn device_prompt(
+    repositories: ApiRepositories,
+    claims: UserClaims,
+    user_code: String,
+) -> DevicePromptResponse {
+    if !claims.is_first_party() {
+        return DevicePromptResponse::Forbidden;
+    }
+
+    match pending(&repositories, &user_code).await {
+        Ok(Some((code, application))) => DevicePromptResponse::Ok(Json(DevicePrompt {
+            client_id: application.application.application_id,
+            display_name: application.application.display_name,
+            description: application.application.description,
+            scope: code.scope,
+        })),
+        Ok(None) => DevicePromptResponse::NotFound,
+        Err(e) => DevicePromptResponse::Failed(Json(e)),
+    }
+}
+
+/// Records the user's decision, which the device learns on its next poll
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.decide_device", skip(repositories, claims, payload), fields(user_id = claims.user_id))]
+pub async fn decide_device(
+    repositories: ApiRepositories,
+    claims: UserClaims,
+    payload: DeviceDecisionPayload,
+) -> DeviceDecisionResponse {
+    // Otherwise a client could use its own tokens to approve a device, and a leaked personal
+    // access token could be traded for a refreshable session
+    if !claims.is_first_party() {
+        return DeviceDecisionResponse::Forbidden;
+    }
+
+    let code = match pending(&repositories, &payload.user_code).await {
+        Ok(Some((code, _))) => code,
+        Ok(None) => return DeviceDecisionResponse::NotFound,
+        Err(e) => return DeviceDecisionResponse::Failed(Json(e)),
+    };
+
+    let agent = &format!("oauth.device:{}", claims.user_id);
+    if payload.approve {
+        match repositories
+            .consent
+            .grant(agent, claims.user_id, &code.application_id)
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => return DeviceDecisionResponse::Failed(Json(ApiError::from(e))),
+        }
+    }
+
+    match repositories
+        .device_code
+        .decide(
+            agent,
+            code.device_code_id,
+            claims.user_id,
+            &claims.amr,
+            payload.approve,
+        )
+        .await
+    {
+        Ok(true) => DeviceDecisionResponse::Ok,
+        Ok(false) => DeviceDecisionResponse::NotFound,
+        Err(e) => DeviceDecisionResponse::Failed(Json(ApiError::from(e))),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{
+    models::oauth::{OpenIdConfiguration, Scope},
+    services::{ApiServices, oauth::token::DEVICE_CODE_GRANT_TYPE},
+};
+
+/// OpenID Connect discovery section 3, every URL is relative to the issuer
+pub fn openid_configuration(services: &ApiServices) -> OpenIdConfiguration {
+    let issuer = &services.issuer_url;
+    let strings = |values: &[&str]| values.iter().map(|it| it.to_string()).collect();
+
+    OpenIdConfiguration {
+        issuer: issuer.clone(),
+        authorization_endpoint: format!("{issuer}/oauth/authorize"),
+        token_endpoint: format!("{issuer}/oauth/token"),
+        userinfo_endpoint: format!("{issuer}/userinfo"),
+        jwks_uri: format!("{issuer}/.well-known/jwks.json"),
+        introspection_endpoint: format!("{issuer}/oauth/introspect"),
+        revocation_endpoint: format!("{issuer}/oauth/revoke"),
+        device_authorization_endpoint: format!("{issuer}/oauth/device_authorization"),
+        scopes_supported: Scope::ALL.iter().map(|it| it.to_string()).collect(),
+        response_types_supported: strings(&["code"]),
+        grant_types_supported: strings(&[
+            "authorization_code",
+            "refresh_token",
+            "client_credentials",
+            DEVICE_CODE_GRANT_TYPE,
+        ]),
+        subject_types_supported: strings(&["public"]),
+        id_token_signing_alg_values_supported: vec![services.jwt.algorithm()],
+        token_endpoint_auth_methods_supported: strings(&[
+            "client_secret_basic",
+            "client_secret_post",
+            "none",
+        ]),
+        code_challenge_methods_supported: strings(&["S256"]),
+        claims_supported: strings(&[
+            "sub",
+            "iss",
+            "aud",
+            "exp",
+            "iat",
+            "nonce",
+            "amr",
+            "sid",
+            "name",
+            "preferred_username",
+            "picture",
+            "email",
+            "email_verified",
+        ]),
+    }
+}
@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+use crate::{
+    models::{oauth::UserInfo, user::User},
+    services::{
+        ApiServices,
+        core::jwt::{Claims, JwtError},
+    },
+};
+
+/// OpenID Connect core section 2, the profile claims sit alongside the token's own
+#[derive(Serialize, Debug)]
+pub struct IdTokenClaims {
+    #[serde(flatten)]
+    pub user_info: UserInfo,
+    pub iss: String,
+    pub aud: String,
+    /// The party the token was issued to, always `aud` as there is only ever one audience
+    pub azp: String,
+    pub iat: u64,
+    pub exp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    pub amr: Vec<String>,
+    pub sid: String,
+}
+
+/// Signs an ID token for the session `claims` were issued for. It expires alongside the
+/// access token, clients only look at it once.
+pub fn issue_id_token(
+    services: &ApiServices,
+    user: &User,
+    claims: &Claims,
+    client_id: &str,
+    scope: &[String],
+    nonce: Option<&str>,
+) -> Result<String, JwtError> {
+    services.jwt.sign(&IdTokenClaims {
+        user_info: UserInfo::new(user, scope),
+        iss: services.issuer_url.clone(),
+        aud: client_id.to_string(),
+        azp: client_id.to_string(),
+        iat: claims.issued_at,
+        exp: claims.expires,
+        nonce: nonce.map(String::from),
+        amr: claims.amr.clone(),
+        sid: claims.session_id.clone(),
+    })
+}
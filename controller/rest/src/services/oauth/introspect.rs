@@ -0,0 +1,151 @@
+use chrono::Utc;
+use poem_openapi::{ApiResponse, Object, payload::Json};
+use serde::Deserialize;
+
+use crate::{
+    api::ApiRepositories,
+    models::oauth::{Introspection, OAuthError, OAuthErrorCode},
+    services::{
+        ApiServices,
+        auth::{
+            personal_access_token::{authenticate, is_personal_access_token},
+            revalidate::revalidate,
+            session::is_session_revoked,
+        },
+        core::jwt::{Claims, SubjectType},
+        oauth::client::{ClientAuthError, authenticate_client, is_confidential},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Deserialize, Debug)]
+pub struct IntrospectionRequest {
+    pub token: String,
+    /// Ignored, access tokens and personal access tokens are told apart by their format
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    /// For clients that can't send HTTP Basic credentials
+    pub client_secret: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum IntrospectResponse {
+    /// Also for tokens that are invalid, expired or revoked, which are just not `active`
+    #[oai(status = 200)]
+    Ok(Json<Introspection>, #[oai(header = "Cache-Control")] String),
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    /// Unknown client, wrong secret, or a public client
+    #[oai(status = 401)]
+    InvalidClient(Json<OAuthError>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+impl IntrospectResponse {
+    fn inactive() -> Self {
+        Self::Ok(
+            Json(Introspection {
+                active: false,
+                claims: None,
+            }),
+            "no-store".to_string(),
+        )
+    }
+}
+
+/// RFC 7662, lets a confidential client check an access token or personal access token the way
+/// our own endpoints do, including session revocation and, when enabled, revalidation. Refresh
+/// tokens only matter to the client holding them and are always reported inactive.
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.introspect", skip(repositories, services, request, basic))]
+pub async fn introspect(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    request: IntrospectionRequest,
+    basic: Option<(String, String)>,
+) -> IntrospectResponse {
+    let application = match authenticate_client(
+        &repositories,
+        &services,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic,
+    )
+    .await
+    {
+        Ok(application) => application,
+        Err(ClientAuthError::Invalid(error)) => return IntrospectResponse::Invalid(Json(error)),
+        Err(ClientAuthError::InvalidClient(error)) => {
+            return IntrospectResponse::InvalidClient(Json(error));
+        }
+        Err(ClientAuthError::Failed(e)) => return IntrospectResponse::Failed(Json(e)),
+    };
+
+    // Anyone can register a public client, so they can't be trusted with other clients' tokens
+    if !is_confidential(&application) {
+        return IntrospectResponse::InvalidClient(Json(OAuthError::new(
+            OAuthErrorCode::InvalidClient,
+            "Only confidential clients may introspect tokens",
+        )));
+    }
+
+    match active_claims(&repositories, &services, &request.token).await {
+        Ok(Some(claims)) => IntrospectResponse::Ok(
+            Json(Introspection {
+                active: true,
+                claims: Some(claims),
+            }),
+            "no-store".to_string(),
+        ),
+        Ok(None) => IntrospectResponse::inactive(),
+        Err(e) => IntrospectResponse::Failed(Json(e)),
+    }
+}
+
+/// The claims of `token` if it would be accepted as a bearer token right now, with implied
+/// grants expanded as they are for our own endpoints
+async fn active_claims(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    token: &str,
+) -> Result<Option<Claims>, ApiError> {
+    let Some(claims) = verified_claims(repositories, services, token).await? else {
+        return Ok(None);
+    };
+
+    let implications = services.implications.get(repositories).await?;
+    Ok(Some(claims.with_implied(&implications)))
+}
+
+/// Checks `token` the way bearer tokens are checked, up to adding implied grants
+async fn verified_claims(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    token: &str,
+) -> Result<Option<Claims>, ApiError> {
+    // Looked up on every use, so they're never stale and skip the checks below
+    if is_personal_access_token(token) {
+        return Ok(authenticate(repositories, services, token).await?);
+    }
+
+    let Ok(claims) = services.jwt.verify(token) else {
+        return Ok(None);
+    };
+
+    let now = Utc::now().timestamp() as u64;
+    if claims.issuer != crate::PRODUCT_IDENTIFIER || claims.issued_at > now || claims.expires < now
+    {
+        return Ok(None);
+    }
+
+    if claims.subject_type == SubjectType::User
+        && is_session_revoked(repositories, services, &claims.session_id).await?
+    {
+        return Ok(None);
+    }
+
+    if !services.revalidate_tokens {
+        return Ok(Some(claims));
+    }
+
+    Ok(revalidate(repositories, claims).await?)
+}
@@ -0,0 +1,134 @@
+use data_encoding::BASE64;
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+
+use crate::models::oauth::{OAuthErrorCode, Scope};
+
+pub mod authorize;
+pub mod client;
+pub mod device;
+pub mod discovery;
+pub mod id_token;
+pub mod introspect;
+pub mod revoke;
+pub mod token;
+pub mod userinfo;
+
+/// Appends `params` to `uri`'s query string
+pub fn with_query(uri: &str, params: &[(&str, &str)]) -> String {
+    let query = params
+        .iter()
+        .map(|(key, value)| format!("{key}={}", utf8_percent_encode(value, NON_ALPHANUMERIC)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if uri.contains('?') { '&' } else { '?' };
+
+    format!("{uri}{separator}{query}")
+}
+
+/// Sends an error back to the client, RFC 6749 section 4.1.2.1
+pub fn error_redirect(
+    redirect_uri: &str,
+    error: OAuthErrorCode,
+    error_description: &str,
+    state: Option<&str>,
+) -> String {
+    let error = error.to_string();
+    let mut params = vec![
+        ("error", error.as_str()),
+        ("error_description", error_description),
+    ];
+    if let Some(state) = state {
+        params.push(("state", state));
+    }
+
+    with_query(redirect_uri, &params)
+}
+
+/// Supported scopes out of a space separated `scope`, in the order asked for. RFC 6749
+/// section 3.3 lets us grant less than was asked, so unknown scopes are dropped.
+pub fn parse_scope(scope: Option<&str>) -> Vec<String> {
+    let mut parsed: Vec<String> = vec![];
+    for scope in scope.unwrap_or_default().split_whitespace() {
+        if scope.parse::<Scope>().is_ok() && !parsed.iter().any(|it| it == scope) {
+            parsed.push(scope.to_string());
+        }
+    }
+
+    parsed
+}
+
+/// Client id and secret from an `Authorization: Basic` header, RFC 6749 section 2.3.1 has both
+/// form encoded before they're joined
+pub fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    let (scheme, credentials) = authorization.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+
+    let credentials = String::from_utf8(BASE64.decode(credentials.trim().as_bytes()).ok()?).ok()?;
+    let (client_id, client_secret) = credentials.split_once(':')?;
+    let decode = |it: &str| {
+        percent_decode_str(&it.replace('+', " "))
+            .decode_utf8()
+            .ok()
+            .map(String::from)
+    };
+
+    Some((decode(client_id)?, decode(client_secret)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::oauth::OAuthErrorCode,
+        services::oauth::{basic_credentials, error_redirect, parse_scope, with_query},
+    };
+
+    #[test]
+    fn test_basic_credentials() {
+        // base64("my-job:s3cr%2Bt")
+        assert_eq!(
+            Some(("my-job".to_string(), "s3cr+t".to_string())),
+            basic_credentials("Basic bXktam9iOnMzY3IlMkJ0")
+        );
+        assert_eq!(None, basic_credentials("Bearer bXktam9iOnMzY3IlMkJ0"));
+        assert_eq!(None, basic_credentials("Basic not-base64"));
+    }
+
+    #[test]
+    fn test_parse_scope() {
+        assert_eq!(
+            vec!["openid", "email"],
+            parse_scope(Some("openid  email offline_access openid"))
+        );
+        assert!(parse_scope(None).is_empty());
+    }
+
+    #[test]
+    fn test_with_query() {
+        assert_eq!(
+            "https://app.example.com/cb?code=abc&state=a%20b",
+            with_query(
+                "https://app.example.com/cb",
+                &[("code", "abc"), ("state", "a b")]
+            )
+        );
+        assert_eq!(
+            "https://app.example.com/cb?tenant=1&code=abc",
+            with_query("https://app.example.com/cb?tenant=1", &[("code", "abc")])
+        );
+    }
+
+    #[test]
+    fn test_error_redirect() {
+        assert_eq!(
+            "https://app.example.com/cb?error=access_denied&error_description=Denied&state=xyz",
+            error_redirect(
+                "https://app.example.com/cb",
+                OAuthErrorCode::AccessDenied,
+                "Denied",
+                Some("xyz")
+            )
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+use serde::Deserialize;
+
+use crate::{
+    api::ApiRepositories,
+    models::oauth::{OAuthError, OAuthErrorCode},
+    services::{
+        ApiServices,
+        auth::session::end_session,
+        core::{jwt::SubjectType, token},
+        oauth::client::{ClientAuthError, authenticate_client},
+    },
+    util::error::ApiError,
+};
+
+#[derive(Object, Deserialize, Debug)]
+pub struct RevocationRequest {
+    pub token: String,
+    /// `access_token` or `refresh_token`, saves a lookup when right
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    /// For confidential clients that can't send HTTP Basic credentials
+    pub client_secret: Option<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum RevokeResponse {
+    /// Also for tokens that are unknown, already revoked or belong to another client
+    #[oai(status = 200)]
+    Ok,
+    /// Includes `unsupported_token_type` for client_credentials tokens, which have no session
+    /// to revoke
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    #[oai(status = 401)]
+    InvalidClient(Json<OAuthError>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// RFC 7009, either kind of token ends the session it belongs to, taking the other tokens
+/// issued within it along
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.revoke", skip(repositories, services, request, basic))]
+pub async fn revoke(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    request: RevocationRequest,
+    basic: Option<(String, String)>,
+) -> RevokeResponse {
+    let application = match authenticate_client(
+        &repositories,
+        &services,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic,
+    )
+    .await
+    {
+        Ok(application) => application,
+        Err(ClientAuthError::Invalid(error)) => return RevokeResponse::Invalid(Json(error)),
+        Err(ClientAuthError::InvalidClient(error)) => {
+            return RevokeResponse::InvalidClient(Json(error));
+        }
+        Err(ClientAuthError::Failed(e)) => return RevokeResponse::Failed(Json(e)),
+    };
+    let client_id = application.application.application_id.as_str();
+
+    let session_id = match token_session(&repositories, &services, client_id, &request).await {
+        Ok(Some(session_id)) => session_id,
+        Ok(None) => return RevokeResponse::Ok,
+        Err(response) => return response,
+    };
+
+    // A token is only the business of the client it was issued to
+    match repositories.session.by_id(&session_id).await {
+        Ok(Some(session)) if session.application_id.as_deref() == Some(client_id) => {}
+        Ok(_) => {
+            tracing::warn!(
+                "Client '{}' tried to revoke another client's token",
+                client_id
+            );
+            return RevokeResponse::Ok;
+        }
+        Err(e) => return RevokeResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    let agent = &format!("oauth.revoke:{}", client_id);
+    match end_session(&repositories, &services, &session_id, agent).await {
+        Ok(()) => RevokeResponse::Ok,
+        Err(e) => RevokeResponse::Failed(Json(ApiError::from(e))),
+    }
+}
+
+/// The session `request.token` belongs to. Refresh tokens are looked up first unless the
+/// client hinted otherwise.
+async fn token_session(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    client_id: &str,
+    request: &RevocationRequest,
+) -> Result<Option<String>, RevokeResponse> {
+    if request.token_type_hint.as_deref() == Some("access_token") {
+        return match access_token_session(services, client_id, &request.token)? {
+            Some(session_id) => Ok(Some(session_id)),
+            None => refresh_token_session(repositories, &request.token).await,
+        };
+    }
+
+    match refresh_token_session(repositories, &request.token).await? {
+        Some(session_id) => Ok(Some(session_id)),
+        None => access_token_session(services, client_id, &request.token),
+    }
+}
+
+async fn refresh_token_session(
+    repositories: &ApiRepositories,
+    token: &str,
+) -> Result<Option<String>, RevokeResponse> {
+    repositories
+        .refresh_token
+        .by_hash(&token::digest(token))
+        .await
+        .map(|refresh_token| refresh_token.map(|it| it.family_id))
+        .map_err(|e| RevokeResponse::Failed(Json(ApiError::from(e))))
+}
+
+/// Expired access tokens fail verification and are left alone, they're no use to anyone
+fn access_token_session(
+    services: &ApiServices,
+    client_id: &str,
+    token: &str,
+) -> Result<Option<String>, RevokeResponse> {
+    let Ok(claims) = services.jwt.verify(token) else {
+        return Ok(None);
+    };
+
+    match claims.subject_type {
+        SubjectType::User => Ok(Some(claims.session_id)),
+        SubjectType::Client if claims.subject == client_id => {
+            Err(RevokeResponse::Invalid(Json(OAuthError::new(
+                OAuthErrorCode::UnsupportedTokenType,
+                "client_credentials tokens can't be revoked, they expire on their own",
+            ))))
+        }
+        SubjectType::Client => Ok(None),
+    }
+}
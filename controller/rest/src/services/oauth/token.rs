@@ -0,0 +1,449 @@
+use chrono::{Duration, Utc};
+use data::dto::{application::ApplicationDetailDto, user::UserDetailDto};
+use poem_openapi::{ApiResponse, Object, payload::Json};
+use serde::Deserialize;
+
+use crate::{
+    api::ApiRepositories,
+    models::{
+        application::Application,
+        oauth::{OAuthError, OAuthErrorCode, Scope},
+        user::User,
+    },
+    services::{
+        ApiServices,
+        auth::{
+            refresh::{RefreshResponse, refresh_tokens},
+            session::{IssuedTokens, end_session, start_session},
+        },
+        core::{jwt::Claims, pkce, token},
+        oauth::{
+            client::{ClientAuthError, authenticate_client, is_confidential},
+            id_token::issue_id_token,
+        },
+    },
+    util::error::ApiError,
+};
+
+/// RFC 8628 section 3.4
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+#[derive(Object, Deserialize, Debug)]
+pub struct TokenRequest {
+    /// `authorization_code`, `refresh_token`, `client_credentials` or
+    /// `urn:ietf:params:oauth:grant-type:device_code`
+    pub grant_type: String,
+    pub client_id: Option<String>,
+    /// For confidential clients that can't send HTTP Basic credentials
+    pub client_secret: Option<String>,
+    pub code: Option<String>,
+    /// Required when it was given to `/oauth/authorize`
+    pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub device_code: Option<String>,
+}
+
+/// RFC 6749 section 5.1
+#[derive(Object, Debug)]
+pub struct TokenResponsePayload {
+    pub access_token: String,
+    /// Always `Bearer`
+    pub token_type: String,
+    /// Seconds until `access_token` expires
+    pub expires_in: u64,
+    /// Not issued for client_credentials, the client just asks again
+    #[oai(skip_serializing_if_is_none)]
+    pub refresh_token: Option<String>,
+    /// Only when the code was issued with the `openid` scope
+    #[oai(skip_serializing_if_is_none)]
+    pub id_token: Option<String>,
+    /// The scopes granted, which may be fewer than were asked for
+    #[oai(skip_serializing_if_is_none)]
+    pub scope: Option<String>,
+}
+impl From<IssuedTokens> for TokenResponsePayload {
+    fn from(issued: IssuedTokens) -> Self {
+        Self {
+            access_token: issued.token,
+            token_type: "Bearer".to_string(),
+            expires_in: issued.claims.expires - issued.claims.issued_at,
+            refresh_token: Some(issued.refresh_token),
+            id_token: None,
+            scope: issued.claims.scope,
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+pub enum TokenResponse {
+    #[oai(status = 200)]
+    Ok(
+        Json<TokenResponsePayload>,
+        #[oai(header = "Cache-Control")] String,
+    ),
+    #[oai(status = 400)]
+    Invalid(Json<OAuthError>),
+    #[oai(status = 401)]
+    InvalidClient(Json<OAuthError>),
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+impl TokenResponse {
+    fn issued(issued: IssuedTokens) -> Self {
+        Self::Ok(Json(issued.into()), "no-store".to_string())
+    }
+
+    fn invalid(error: OAuthErrorCode, error_description: &str) -> Self {
+        Self::Invalid(Json(OAuthError::new(error, error_description)))
+    }
+}
+
+/// `basic` holds the client id and secret from an `Authorization: Basic` header
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.token", skip(repositories, services, request, basic), fields(grant_type = request.grant_type))]
+pub async fn token(
+    repositories: ApiRepositories,
+    services: ApiServices,
+    request: TokenRequest,
+    basic: Option<(String, String)>,
+) -> TokenResponse {
+    let application = match authenticate_client(
+        &repositories,
+        &services,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        basic,
+    )
+    .await
+    {
+        Ok(application) => application,
+        Err(ClientAuthError::Invalid(error)) => return TokenResponse::Invalid(Json(error)),
+        Err(ClientAuthError::InvalidClient(error)) => {
+            return TokenResponse::InvalidClient(Json(error));
+        }
+        Err(ClientAuthError::Failed(e)) => return TokenResponse::Failed(Json(e)),
+    };
+    let client_id = application.application.application_id.as_str();
+
+    match request.grant_type.as_str() {
+        "authorization_code" => exchange_code(&repositories, &services, client_id, &request).await,
+        "refresh_token" => {
+            let Some(refresh_token) = request.refresh_token.as_deref() else {
+                return TokenResponse::invalid(
+                    OAuthErrorCode::InvalidRequest,
+                    "refresh_token is required",
+                );
+            };
+
+            match refresh_tokens(&repositories, &services, refresh_token, Some(client_id)).await {
+                Ok(issued) => TokenResponse::issued(issued),
+                Err(RefreshResponse::Failed(e)) => TokenResponse::Failed(e),
+                Err(_) => TokenResponse::invalid(
+                    OAuthErrorCode::InvalidGrant,
+                    "The refresh token is invalid, expired or was issued to another client",
+                ),
+            }
+        }
+        "client_credentials" => issue_client_token(&services, application),
+        DEVICE_CODE_GRANT_TYPE => {
+            poll_device_code(&repositories, &services, client_id, &request).await
+        }
+        _ => TokenResponse::invalid(
+            OAuthErrorCode::UnsupportedGrantType,
+            "Only authorization_code, refresh_token, client_credentials and device_code are supported",
+        ),
+    }
+}
+
+/// RFC 6749 section 4.4, the application is the subject and carries its own grants
+fn issue_client_token(services: &ApiServices, application: ApplicationDetailDto) -> TokenResponse {
+    // Only a secret proves the caller is the client, and public clients have none
+    if !is_confidential(&application) {
+        return TokenResponse::invalid(
+            OAuthErrorCode::UnauthorizedClient,
+            "Only confidential clients may use client_credentials",
+        );
+    }
+
+    let claims = Claims::for_client(
+        &Application::from(application),
+        services.lifetimes.access_token,
+    );
+    match services.jwt.sign(&claims) {
+        Ok(access_token) => TokenResponse::Ok(
+            Json(TokenResponsePayload {
+                access_token,
+                token_type: "Bearer".to_string(),
+                expires_in: claims.expires - claims.issued_at,
+                refresh_token: None,
+                id_token: None,
+                scope: None,
+            }),
+            "no-store".to_string(),
+        ),
+        Err(e) => TokenResponse::Failed(Json(ApiError::from(e))),
+    }
+}
+
+async fn exchange_code(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    client_id: &str,
+    request: &TokenRequest,
+) -> TokenResponse {
+    let (Some(code), Some(code_verifier)) = (&request.code, &request.code_verifier) else {
+        return TokenResponse::invalid(
+            OAuthErrorCode::InvalidRequest,
+            "code and code_verifier are required",
+        );
+    };
+    let invalid_grant = || {
+        TokenResponse::invalid(
+            OAuthErrorCode::InvalidGrant,
+            "The code is invalid, expired or was issued to another client",
+        )
+    };
+
+    let code = match repositories
+        .authorization_code
+        .by_hash(&token::digest(code))
+        .await
+    {
+        Ok(Some(code)) if code.application_id == client_id => code,
+        Ok(_) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    // RFC 6749 section 4.1.2, a replayed code should revoke whatever it was exchanged for
+    if code.used_at.is_some() {
+        tracing::warn!(
+            "Authorization code reuse detected for user: {}",
+            code.user_id
+        );
+        if let Some(session_id) = &code.session_id {
+            let agent = &format!("oauth.token.reuse:{}", code.user_id);
+            if let Err(e) = end_session(repositories, services, session_id, agent).await {
+                tracing::error!("Failed to revoke session after code reuse: {:?}", e);
+            }
+        }
+        return invalid_grant();
+    }
+
+    // RFC 6749 section 4.1.3, required here whenever it was given to authorize
+    let redirect_uri_matches = match &request.redirect_uri {
+        Some(redirect_uri) => *redirect_uri == code.redirect_uri,
+        None => !code.redirect_uri_explicit,
+    };
+
+    if !code.is_usable()
+        || !redirect_uri_matches
+        || !pkce::verify(code_verifier, &code.code_challenge)
+    {
+        return invalid_grant();
+    }
+
+    let agent = &format!("oauth.token:{}", code.user_id);
+    match repositories
+        .authorization_code
+        .consume(agent, code.authorization_code_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    let user = match repositories.user.by_id(code.user_id).await {
+        Ok(Some(user)) if user.user.enabled => user,
+        Ok(_) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let (session_id, payload) = match issue_user_tokens(
+        repositories,
+        services,
+        user,
+        client_id,
+        &code.amr,
+        &code.scope,
+        code.nonce.as_deref(),
+        agent,
+    )
+    .await
+    {
+        Ok(issued) => issued,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = repositories
+        .authorization_code
+        .set_session(agent, code.authorization_code_id, &session_id)
+        .await
+    {
+        tracing::error!(
+            "Failed to record the session for an authorization code: {:?}",
+            e
+        );
+    }
+
+    TokenResponse::Ok(Json(payload), "no-store".to_string())
+}
+
+/// RFC 8628 section 3.4, answers the device's poll with tokens once the user has approved it
+async fn poll_device_code(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    client_id: &str,
+    request: &TokenRequest,
+) -> TokenResponse {
+    let Some(device_code) = &request.device_code else {
+        return TokenResponse::invalid(OAuthErrorCode::InvalidRequest, "device_code is required");
+    };
+    let invalid_grant = || {
+        TokenResponse::invalid(
+            OAuthErrorCode::InvalidGrant,
+            "The device code is invalid, was already used or was issued to another client",
+        )
+    };
+
+    let code = match repositories
+        .device_code
+        .by_device_code_hash(&token::digest(device_code))
+        .await
+    {
+        Ok(Some(code)) if code.application_id == client_id && code.used_at.is_none() => code,
+        Ok(_) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    if code.is_expired() {
+        return TokenResponse::invalid(
+            OAuthErrorCode::ExpiredToken,
+            "The device code expired, start over",
+        );
+    }
+    if code.denied_at.is_some() {
+        return TokenResponse::invalid(OAuthErrorCode::AccessDenied, "The user denied the request");
+    }
+
+    // Each poll that comes too soon pushes the next one back by another 5 seconds
+    let too_soon = code.last_polled_at.is_some_and(|last_polled_at| {
+        Utc::now() < last_polled_at + Duration::seconds(code.interval_seconds.into())
+    });
+    let interval_seconds = if too_soon {
+        code.interval_seconds + 5
+    } else {
+        code.interval_seconds
+    };
+    if let Err(e) = repositories
+        .device_code
+        .record_poll(code.device_code_id, interval_seconds)
+        .await
+    {
+        return TokenResponse::Failed(Json(ApiError::from(e)));
+    }
+    if too_soon {
+        return TokenResponse::invalid(
+            OAuthErrorCode::SlowDown,
+            &format!("Poll at most every {interval_seconds} seconds"),
+        );
+    }
+
+    let Some(user_id) = code.user_id.filter(|_| code.approved_at.is_some()) else {
+        return TokenResponse::invalid(
+            OAuthErrorCode::AuthorizationPending,
+            "The user hasn't approved the request yet",
+        );
+    };
+
+    let agent = &format!("oauth.token.device:{}", user_id);
+    match repositories
+        .device_code
+        .consume(agent, code.device_code_id)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    }
+
+    let user = match repositories.user.by_id(user_id).await {
+        Ok(Some(user)) if user.user.enabled => user,
+        Ok(_) => return invalid_grant(),
+        Err(e) => return TokenResponse::Failed(Json(ApiError::from(e))),
+    };
+
+    let (session_id, payload) = match issue_user_tokens(
+        repositories,
+        services,
+        user,
+        client_id,
+        &code.amr,
+        &code.scope,
+        None,
+        agent,
+    )
+    .await
+    {
+        Ok(issued) => issued,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = repositories
+        .device_code
+        .set_session(agent, code.device_code_id, &session_id)
+        .await
+    {
+        tracing::error!("Failed to record the session for a device code: {:?}", e);
+    }
+
+    TokenResponse::Ok(Json(payload), "no-store".to_string())
+}
+
+/// Opens a session for `user` through `client_id`, with an ID token when `scope` includes
+/// `openid`. Returns the session's id alongside the response.
+#[allow(clippy::too_many_arguments)]
+async fn issue_user_tokens(
+    repositories: &ApiRepositories,
+    services: &ApiServices,
+    user: UserDetailDto,
+    client_id: &str,
+    amr: &[String],
+    scope: &[String],
+    nonce: Option<&str>,
+    agent: &str,
+) -> Result<(String, TokenResponsePayload), TokenResponse> {
+    let issued = start_session(
+        repositories,
+        services,
+        &user,
+        amr,
+        Some(client_id),
+        scope,
+        agent,
+    )
+    .await
+    .map_err(|e| TokenResponse::Failed(Json(ApiError::from(e))))?;
+    let session_id = issued.claims.session_id.clone();
+
+    if !scope
+        .iter()
+        .any(|scope| *scope == Scope::Openid.to_string())
+    {
+        return Ok((session_id, issued.into()));
+    }
+
+    let id_token = issue_id_token(
+        services,
+        &User::from(user),
+        &issued.claims,
+        client_id,
+        scope,
+        nonce,
+    )
+    .map_err(|e| TokenResponse::Failed(Json(ApiError::from(e))))?;
+    let mut payload = TokenResponsePayload::from(issued);
+    payload.id_token = Some(id_token);
+
+    Ok((session_id, payload))
+}
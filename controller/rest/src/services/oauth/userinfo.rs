@@ -0,0 +1,48 @@
+use poem_openapi::{ApiResponse, payload::Json};
+
+use crate::{
+    api::ApiRepositories,
+    models::{
+        oauth::{Scope, UserInfo},
+        user::User,
+    },
+    services::core::jwt::UserClaims,
+    util::error::ApiError,
+};
+
+#[derive(ApiResponse)]
+pub enum UserInfoResponse {
+    #[oai(status = 200)]
+    Ok(Json<UserInfo>),
+    /// The user has since been deleted or disabled
+    #[oai(status = 401)]
+    Unauthorized,
+    /// The token wasn't issued with the `openid` scope
+    #[oai(status = 403)]
+    Forbidden,
+    #[oai(status = 500)]
+    Failed(Json<ApiError>),
+}
+
+/// OpenID Connect core section 5.3, the claims the token's scopes cover
+#[tracing::instrument(level = tracing::Level::INFO, "services.oauth.userinfo", skip(repositories, claims), fields(user_id = claims.user_id))]
+pub async fn userinfo(repositories: ApiRepositories, claims: UserClaims) -> UserInfoResponse {
+    if !claims.has_scope(&Scope::Openid.to_string()) {
+        return UserInfoResponse::Forbidden;
+    }
+
+    let user = match repositories.user.by_id(claims.user_id).await {
+        Ok(Some(user)) if user.user.enabled => User::from(user),
+        Ok(_) => return UserInfoResponse::Unauthorized,
+        Err(e) => return UserInfoResponse::Failed(Json(ApiError::from(e))),
+    };
+    let scope = claims
+        .scope
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    UserInfoResponse::Ok(Json(UserInfo::new(&user, &scope)))
+}
@@ -0,0 +1,349 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use valuable::Valuable;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq, Serialize, Deserialize, Valuable)]
+pub enum GrantPatternError {
+    #[error("Grant id '{grant_id}' has an empty segment")]
+    EmptySegment { grant_id: String },
+    #[error("Grant id '{grant_id}' mixes '*' with other characters in a segment")]
+    PartialWildcard { grant_id: String },
+    #[error("Grant id '{grant_id}' has '**' before its last segment")]
+    InnerRest { grant_id: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Literal(String),
+    /// `*`, any one segment
+    Any,
+    /// `**`, one or more segments, only ever last
+    Rest,
+}
+
+/// A grant id, or a pattern standing for many. Segments are separated by `.`, a `*` segment
+/// matches any one segment and a trailing `**` matches one or more, so `dev.thmsn.auth.user.*`
+/// matches `dev.thmsn.auth.user.create` and `dev.thmsn.auth.**` matches both.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GrantPattern {
+    segments: Vec<Segment>,
+}
+impl GrantPattern {
+    /// Reads a grant id held by a user without validating it, ids that aren't valid patterns
+    /// only ever match themselves
+    pub fn lenient(grant_id: &str) -> Self {
+        grant_id.parse().unwrap_or_else(|_| Self::literal(grant_id))
+    }
+
+    /// A pattern matching `grant_id` alone, even where it contains `*`
+    fn literal(grant_id: &str) -> Self {
+        Self {
+            segments: vec![Segment::Literal(grant_id.to_string())],
+        }
+    }
+
+    pub fn matches(&self, grant_id: &str) -> bool {
+        self.covers(&Self {
+            segments: grant_id
+                .split('.')
+                .map(|segment| Segment::Literal(segment.to_string()))
+                .collect(),
+        }) || *self == Self::literal(grant_id)
+    }
+
+    /// Whether every grant `other` matches is matched by this pattern too
+    pub fn covers(&self, other: &GrantPattern) -> bool {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match (segment, other.segments.get(i)) {
+                (Segment::Rest, _) => return other.segments.len() > i,
+                (Segment::Any, Some(Segment::Literal(_) | Segment::Any)) => {}
+                (Segment::Literal(it), Some(Segment::Literal(other))) if it == other => {}
+                _ => return false,
+            }
+        }
+
+        other.segments.len() == self.segments.len()
+    }
+
+    /// Whether some grant id is matched by both patterns
+    pub fn overlaps(&self, other: &GrantPattern) -> bool {
+        fn overlaps(a: &[Segment], b: &[Segment]) -> bool {
+            match (a.first(), b.first()) {
+                (None, None) => true,
+                // `**` takes whatever is left of the other, as long as something is
+                (Some(Segment::Rest), rest) | (rest, Some(Segment::Rest)) => rest.is_some(),
+                (Some(Segment::Literal(a_it)), Some(Segment::Literal(b_it))) if a_it != b_it => {
+                    false
+                }
+                (Some(_), Some(_)) => overlaps(&a[1..], &b[1..]),
+                _ => false,
+            }
+        }
+
+        overlaps(&self.segments, &other.segments)
+    }
+}
+impl FromStr for GrantPattern {
+    type Err = GrantPatternError;
+
+    fn from_str(grant_id: &str) -> Result<Self, Self::Err> {
+        let parts = grant_id.split('.').collect::<Vec<_>>();
+        let mut segments = Vec::with_capacity(parts.len());
+
+        for (i, part) in parts.iter().enumerate() {
+            let segment = match *part {
+                "" => {
+                    return Err(GrantPatternError::EmptySegment {
+                        grant_id: grant_id.into(),
+                    });
+                }
+                "*" => Segment::Any,
+                "**" if i + 1 == parts.len() => Segment::Rest,
+                "**" => {
+                    return Err(GrantPatternError::InnerRest {
+                        grant_id: grant_id.into(),
+                    });
+                }
+                part if part.contains('*') => {
+                    return Err(GrantPatternError::PartialWildcard {
+                        grant_id: grant_id.into(),
+                    });
+                }
+                part => Segment::Literal(part.into()),
+            };
+            segments.push(segment);
+        }
+
+        Ok(Self { segments })
+    }
+}
+impl Display for GrantPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(it) => it.as_str(),
+                Segment::Any => "*",
+                Segment::Rest => "**",
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", segments.join("."))
+    }
+}
+
+/// Whether any of `held` covers `grant_id`, itself possibly a pattern
+pub fn covered(held: &[GrantPattern], grant_id: &str) -> bool {
+    let it = GrantPattern::lenient(grant_id);
+    held.iter().any(|pattern| pattern.covers(&it))
+}
+
+/// Drops every grant id covered by another, so a token holding `dev.thmsn.auth.**` doesn't
+/// also list each grant it matches
+pub fn compact<'a>(grant_ids: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut patterns = grant_ids
+        .into_iter()
+        .map(|grant_id| (grant_id, GrantPattern::lenient(grant_id)))
+        .collect::<Vec<_>>();
+    patterns.sort_by_key(|(grant_id, _)| *grant_id);
+    patterns.dedup_by_key(|(grant_id, _)| *grant_id);
+
+    // Distinct patterns never cover each other both ways, so every id dropped has a cover kept
+    patterns
+        .iter()
+        .filter(|(grant_id, pattern)| {
+            !patterns
+                .iter()
+                .any(|(other_id, other)| other_id != grant_id && other.covers(pattern))
+        })
+        .map(|(grant_id, _)| grant_id.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::util::grant_pattern::{GrantPattern, GrantPatternError, compact};
+
+    fn pattern(it: &str) -> GrantPattern {
+        it.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!("dev.thmsn.auth.user.create".parse::<GrantPattern>().is_ok());
+        assert!("dev.thmsn.*.user.create".parse::<GrantPattern>().is_ok());
+        assert!("dev.thmsn.auth.**".parse::<GrantPattern>().is_ok());
+        assert!(matches!(
+            "dev..auth".parse::<GrantPattern>(),
+            Err(GrantPatternError::EmptySegment { .. })
+        ));
+        assert!(matches!(
+            "dev.thmsn.user*".parse::<GrantPattern>(),
+            Err(GrantPatternError::PartialWildcard { .. })
+        ));
+        assert!(matches!(
+            "dev.**.create".parse::<GrantPattern>(),
+            Err(GrantPatternError::InnerRest { .. })
+        ));
+    }
+
+    #[test]
+    fn test_matches() {
+        let any = pattern("dev.thmsn.auth.user.*");
+        assert!(any.matches("dev.thmsn.auth.user.create"));
+        assert!(!any.matches("dev.thmsn.auth.user"));
+        assert!(!any.matches("dev.thmsn.auth.user.grant.update"));
+
+        let rest = pattern("dev.thmsn.auth.**");
+        assert!(rest.matches("dev.thmsn.auth.user.create"));
+        assert!(rest.matches("dev.thmsn.auth.user.grant.update"));
+        assert!(!rest.matches("dev.thmsn.auth"));
+        assert!(!rest.matches("dev.thmsn.other.user.create"));
+    }
+
+    #[test]
+    fn test_covers() {
+        assert!(pattern("a.**").covers(&pattern("a.*")));
+        assert!(pattern("a.**").covers(&pattern("a.b.**")));
+        assert!(pattern("a.*").covers(&pattern("a.b")));
+        assert!(!pattern("a.*").covers(&pattern("a.**")));
+        assert!(!pattern("a.b").covers(&pattern("a.*")));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(pattern("a.*.c").overlaps(&pattern("a.b.*")));
+        assert!(pattern("a.**").overlaps(&pattern("*.b")));
+        assert!(pattern("a.b").overlaps(&pattern("a.b")));
+        assert!(!pattern("a.**").overlaps(&pattern("a")));
+        assert!(!pattern("a.*").overlaps(&pattern("a.b.c")));
+        assert!(!pattern("a.*.c").overlaps(&pattern("a.b.d")));
+    }
+
+    #[test]
+    fn test_lenient() {
+        let it = GrantPattern::lenient("a..*");
+        assert!(it.matches("a..*"));
+        assert!(!it.matches("a..b"));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut it = compact(["a.b", "a.*", "a.b", "c.d", "a.**"]);
+        it.sort();
+        assert_eq!(vec!["a.**", "c.d"], it);
+    }
+
+    fn segment() -> impl Strategy<Value = String> {
+        prop_oneof![
+            4 => "[a-c]",
+            1 => Just("*".to_string()),
+        ]
+    }
+
+    fn pattern_strategy() -> impl Strategy<Value = GrantPattern> {
+        (prop::collection::vec(segment(), 1..5), any::<bool>()).prop_map(|(mut parts, rest)| {
+            if rest {
+                parts.push("**".to_string());
+            }
+            pattern(&parts.join("."))
+        })
+    }
+
+    /// A grant id the pattern matches, filling each wildcard from `fill`
+    fn instance(pattern: &GrantPattern, fill: &[String]) -> String {
+        let mut fill = fill.iter().cycle();
+        pattern
+            .to_string()
+            .split('.')
+            .map(|segment| match segment {
+                "*" => fill.next().unwrap().clone(),
+                "**" => format!("{}.{}", fill.next().unwrap(), fill.next().unwrap()),
+                segment => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    proptest! {
+        #[test]
+        fn prop_roundtrip(it in pattern_strategy()) {
+            prop_assert_eq!(pattern(&it.to_string()), it);
+        }
+
+        #[test]
+        fn prop_covers_itself(it in pattern_strategy()) {
+            prop_assert!(it.covers(&it));
+        }
+
+        #[test]
+        fn prop_matches_instances(
+            it in pattern_strategy(),
+            fill in prop::collection::vec("[a-c]", 1..4),
+        ) {
+            prop_assert!(it.matches(&instance(&it, &fill)));
+        }
+
+        #[test]
+        fn prop_covering_matches_instances(
+            a in pattern_strategy(),
+            b in pattern_strategy(),
+            fill in prop::collection::vec("[a-c]", 1..4),
+        ) {
+            if a.covers(&b) {
+                prop_assert!(a.matches(&instance(&b, &fill)));
+            }
+        }
+
+        #[test]
+        fn prop_overlaps_instances(
+            a in pattern_strategy(),
+            b in pattern_strategy(),
+            fill in prop::collection::vec("[a-c]", 1..4),
+        ) {
+            prop_assert_eq!(a.overlaps(&b), b.overlaps(&a));
+            if a.matches(&instance(&b, &fill)) {
+                prop_assert!(a.overlaps(&b));
+            }
+            if a.covers(&b) {
+                prop_assert!(a.overlaps(&b));
+            }
+        }
+
+        #[test]
+        fn prop_covers_transitive(
+            a in pattern_strategy(),
+            b in pattern_strategy(),
+            c in pattern_strategy(),
+        ) {
+            if a.covers(&b) && b.covers(&c) {
+                prop_assert!(a.covers(&c));
+            }
+        }
+
+        #[test]
+        fn prop_literal_matches_itself_only(
+            a in prop::collection::vec("[a-c]", 1..4),
+            b in prop::collection::vec("[a-c]", 1..4),
+        ) {
+            let (a, b) = (a.join("."), b.join("."));
+            prop_assert_eq!(pattern(&a).matches(&b), a == b);
+        }
+
+        #[test]
+        fn prop_compact_covers_input(them in prop::collection::vec(pattern_strategy(), 0..6)) {
+            let ids = them.iter().map(ToString::to_string).collect::<Vec<_>>();
+            let compacted = compact(ids.iter().map(String::as_str));
+
+            prop_assert!(compacted.iter().all(|it| ids.contains(it)));
+            for it in &them {
+                prop_assert!(compacted.iter().any(|other| pattern(other).covers(it)));
+            }
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use std::collections::HashSet;
 
+use chrono::{DateTime, Duration, Utc};
+use data::dto::user::UserDetailDto;
 use strum::{Display, EnumString};
 
-use crate::services::core::jwt::Claims;
+use crate::{
+    services::core::jwt::Claims,
+    util::grant_pattern::{GrantPattern, covered},
+};
 
 #[derive(EnumString, Display)]
 pub enum Grants {
@@ -16,6 +21,20 @@ pub enum Grants {
     UserGet,
     #[strum(to_string = "dev.thmsn.auth.user.grant.update")]
     UserGrantUpdate,
+    #[strum(to_string = "dev.thmsn.auth.user.session.revoke")]
+    UserSessionRevoke,
+    #[strum(to_string = "dev.thmsn.auth.user.login.list")]
+    UserLoginList,
+    #[strum(to_string = "dev.thmsn.auth.user.unlock")]
+    UserUnlock,
+    #[strum(to_string = "dev.thmsn.auth.user.password.reset")]
+    UserPasswordReset,
+    #[strum(to_string = "dev.thmsn.auth.user.impersonate")]
+    UserImpersonate,
+    #[strum(to_string = "dev.thmsn.auth.user.impersonation.list")]
+    UserImpersonationList,
+    #[strum(to_string = "dev.thmsn.auth.user.role.update")]
+    UserRoleUpdate,
     #[strum(to_string = "dev.thmsn.auth.application.create")]
     ApplicationCreate,
     #[strum(to_string = "dev.thmsn.auth.application.get")]
@@ -26,10 +45,48 @@ pub enum Grants {
     ApplicationGetGrants,
     #[strum(to_string = "dev.thmsn.auth.application.update")]
     ApplicationUpdate,
+    #[strum(to_string = "dev.thmsn.auth.application.secret.rotate")]
+    ApplicationSecretRotate,
+    #[strum(to_string = "dev.thmsn.auth.application.grant.update")]
+    ApplicationGrantUpdate,
     #[strum(to_string = "dev.thmsn.auth.grant.create")]
     GrantCreate,
     #[strum(to_string = "dev.thmsn.auth.grant.get")]
     GrantGet,
+    #[strum(to_string = "dev.thmsn.auth.grant.implication.update")]
+    GrantImplicationUpdate,
+    #[strum(to_string = "dev.thmsn.auth.role.create")]
+    RoleCreate,
+    #[strum(to_string = "dev.thmsn.auth.role.get")]
+    RoleGet,
+    #[strum(to_string = "dev.thmsn.auth.role.list")]
+    RoleList,
+    #[strum(to_string = "dev.thmsn.auth.role.update")]
+    RoleUpdate,
+    #[strum(to_string = "dev.thmsn.auth.role.delete")]
+    RoleDelete,
+    #[strum(to_string = "dev.thmsn.auth.role.grant.update")]
+    RoleGrantUpdate,
+    #[strum(to_string = "dev.thmsn.auth.group.create")]
+    GroupCreate,
+    #[strum(to_string = "dev.thmsn.auth.group.get")]
+    GroupGet,
+    #[strum(to_string = "dev.thmsn.auth.group.list")]
+    GroupList,
+    #[strum(to_string = "dev.thmsn.auth.group.update")]
+    GroupUpdate,
+    #[strum(to_string = "dev.thmsn.auth.group.delete")]
+    GroupDelete,
+    #[strum(to_string = "dev.thmsn.auth.group.grant.update")]
+    GroupGrantUpdate,
+    #[strum(to_string = "dev.thmsn.auth.group.role.update")]
+    GroupRoleUpdate,
+    #[strum(to_string = "dev.thmsn.auth.group.member.update")]
+    GroupMemberUpdate,
+    #[strum(to_string = "dev.thmsn.auth.key.list")]
+    KeyList,
+    #[strum(to_string = "dev.thmsn.auth.key.promote")]
+    KeyPromote,
 }
 
 #[derive(Default, Debug)]
@@ -48,14 +105,21 @@ pub trait HasGrants {
     fn has_grants(&self, grants: &[Self::Grants]) -> bool {
         self.has_grants_pro(grants, HasGrantsMode::default())
     }
+    /// Held grants may be patterns, so `dev.thmsn.auth.user.*` satisfies a check for
+    /// `dev.thmsn.auth.user.create`
     fn has_grants_pro(&self, grants: &[Self::Grants], mode: HasGrantsMode) -> bool {
-        let user_grants = self.get_grants();
+        let user_grants = self
+            .get_grants()
+            .into_iter()
+            .map(GrantPattern::lenient)
+            .collect::<Vec<_>>();
+        let holds = |grant_id: &str| user_grants.iter().any(|it| it.matches(grant_id));
 
         for grant in grants {
             let it = grant.to_string();
             match mode {
-                HasGrantsMode::And if !user_grants.contains(it.as_str()) => return false,
-                HasGrantsMode::Or if user_grants.contains(it.as_str()) => return true,
+                HasGrantsMode::And if !holds(&it) => return false,
+                HasGrantsMode::Or if holds(&it) => return true,
                 _ => {}
             }
         }
@@ -74,11 +138,50 @@ impl HasGrants for Claims {
     }
 }
 
+/// Whether `actor` currently holds every grant `user` does, possibly through a pattern, the bar
+/// for acting on `user`'s account
+pub fn holds_all_of(actor: &UserDetailDto, user: &UserDetailDto) -> bool {
+    let held = actor
+        .effective_grants()
+        .into_iter()
+        .map(|grant| GrantPattern::lenient(&grant.grant_id))
+        .collect::<Vec<_>>();
+
+    user.effective_grants()
+        .into_iter()
+        .all(|grant| covered(&held, &grant.grant_id))
+}
+
+/// When a token for `user` issued at `now` expires, `lifetime` later unless one of its
+/// time-bound grants, of an application `in_scope`, stops counting first
+pub fn token_expiry(
+    user: &UserDetailDto,
+    in_scope: impl Fn(&str) -> bool,
+    now: DateTime<Utc>,
+    lifetime: Duration,
+) -> DateTime<Utc> {
+    user.grants
+        .iter()
+        .filter(|grant| {
+            grant.user_grant.is_current_at(now) && in_scope(&grant.grant.grant.application_id)
+        })
+        .filter_map(|grant| grant.user_grant.valid_until)
+        .fold(now + lifetime, std::cmp::min)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
-    use crate::util::grants::{HasGrants, HasGrantsMode};
+    use chrono::{DateTime, Duration, Utc};
+    use data::dto::{
+        application::ApplicationDto,
+        grant::{GrantDetailDto, GrantDto},
+        user::{UserDetailDto, UserDto},
+        user_grant::{UserGrantDetailDto, UserGrantDto},
+    };
+
+    use crate::util::grants::{HasGrants, HasGrantsMode, holds_all_of, token_expiry};
 
     struct FakeClaims {
         grants: Vec<String>,
@@ -141,4 +244,201 @@ mod tests {
         );
         assert_eq!(false, claims.has_grants_pro(&["d", "e"], HasGrantsMode::Or));
     }
+
+    #[test]
+    fn test_has_grant_wildcard() {
+        let claims = claims!["a.b.*", "c.**"];
+
+        assert_eq!(true, claims.has_grants(&["a.b.c", "c.d", "c.d.e"]));
+        assert_eq!(false, claims.has_grants(&["a.b"]));
+        assert_eq!(false, claims.has_grants(&["a.b.c.d"]));
+        assert_eq!(false, claims.has_grants(&["c"]));
+        assert_eq!(
+            true,
+            claims.has_grants_pro(&["a.c", "a.b.c"], HasGrantsMode::Or)
+        );
+    }
+
+    fn user_grant(
+        application_id: &str,
+        valid_from: Option<DateTime<Utc>>,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> UserGrantDetailDto {
+        let now = Utc::now();
+
+        UserGrantDetailDto {
+            user_grant: UserGrantDto {
+                user_id: 1,
+                grant_id: format!("{application_id}.grant"),
+                enabled: true,
+                enabled_at: Some(now),
+                disabled_at: None,
+                created_by: "test".to_string(),
+                updated_by: "test".to_string(),
+                created_at: now,
+                updated_at: now,
+                valid_from,
+                valid_until,
+            },
+            grant: GrantDetailDto {
+                grant: GrantDto {
+                    grant_id: format!("{application_id}.grant"),
+                    application_id: application_id.to_string(),
+                    display_name: "grant".to_string(),
+                    description: String::new(),
+                    created_by: "test".to_string(),
+                    updated_by: "test".to_string(),
+                    created_at: now,
+                    updated_at: now,
+                },
+                application: ApplicationDto {
+                    application_id: application_id.to_string(),
+                    display_name: application_id.to_string(),
+                    description: String::new(),
+                    created_by: "test".to_string(),
+                    updated_by: "test".to_string(),
+                    created_at: now,
+                    updated_at: now,
+                    client_type: "public".to_string(),
+                    client_secret: None,
+                },
+            },
+        }
+    }
+
+    fn user(grants: Vec<UserGrantDetailDto>) -> UserDetailDto {
+        let now = Utc::now();
+
+        UserDetailDto {
+            user: UserDto {
+                user_id: 1,
+                display_name: "user".to_string(),
+                username: "user".to_string(),
+                password: String::new(),
+                enabled: true,
+                email: None,
+                image_url: None,
+                last_login: None,
+                created_by: "test".to_string(),
+                updated_by: "test".to_string(),
+                created_at: now,
+                updated_at: now,
+                password_change_required: false,
+                email_verified_at: None,
+                password_salt_unique: true,
+            },
+            grants,
+            roles: vec![],
+            groups: vec![],
+        }
+    }
+
+    #[test]
+    fn test_user_grant_window() {
+        let now = Utc::now();
+        let hour = Duration::hours(1);
+
+        assert_eq!(
+            true,
+            user_grant("a", None, None).user_grant.is_current_at(now)
+        );
+        assert_eq!(
+            true,
+            user_grant("a", Some(now), None)
+                .user_grant
+                .is_current_at(now)
+        );
+        assert_eq!(
+            false,
+            user_grant("a", Some(now + hour), None)
+                .user_grant
+                .is_current_at(now)
+        );
+        assert_eq!(
+            false,
+            user_grant("a", None, Some(now))
+                .user_grant
+                .is_current_at(now)
+        );
+        assert_eq!(
+            true,
+            user_grant("a", None, Some(now + hour))
+                .user_grant
+                .is_current_at(now)
+        );
+        assert_eq!(
+            true,
+            user_grant("a", Some(now - hour), Some(now + hour))
+                .user_grant
+                .is_current_at(now)
+        );
+
+        let mut disabled = user_grant("a", None, None);
+        disabled.user_grant.enabled = false;
+        assert_eq!(false, disabled.user_grant.is_current_at(now));
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let now = Utc::now();
+        let hour = Duration::hours(1);
+        let lifetime = Duration::minutes(15);
+        let any = |_: &str| true;
+
+        // Unbounded grants leave the lifetime alone, as do windows closing after it
+        let it = user(vec![user_grant("a", None, None)]);
+        assert_eq!(now + lifetime, token_expiry(&it, any, now, lifetime));
+        let it = user(vec![user_grant("a", None, Some(now + hour))]);
+        assert_eq!(now + lifetime, token_expiry(&it, any, now, lifetime));
+
+        // The first window to close caps it
+        let soon = now + Duration::minutes(5);
+        let it = user(vec![
+            user_grant("a", None, Some(now + Duration::minutes(10))),
+            user_grant("b", None, Some(soon)),
+        ]);
+        assert_eq!(soon, token_expiry(&it, any, now, lifetime));
+
+        // Unless that grant isn't in the token
+        assert_eq!(
+            now + Duration::minutes(10),
+            token_expiry(&it, |application_id| application_id == "a", now, lifetime)
+        );
+
+        // Or doesn't count yet, or anymore
+        let it = user(vec![
+            user_grant("a", Some(now + hour), Some(soon)),
+            user_grant("b", None, Some(now)),
+        ]);
+        assert_eq!(now + lifetime, token_expiry(&it, any, now, lifetime));
+    }
+
+    #[test]
+    fn test_holds_all_of() {
+        let admin = user(vec![
+            user_grant("a", None, None),
+            user_grant("b", None, None),
+        ]);
+        let support = user(vec![user_grant("a", None, None)]);
+
+        assert_eq!(true, holds_all_of(&admin, &support));
+        assert_eq!(false, holds_all_of(&support, &admin));
+        assert_eq!(true, holds_all_of(&support, &user(vec![])));
+
+        // Through a pattern
+        let mut pattern = user_grant("a", None, None);
+        pattern.grant.grant.grant_id = "a.*".to_string();
+        assert_eq!(true, holds_all_of(&user(vec![pattern]), &support));
+
+        // Grants that stopped counting are neither held nor needed
+        let expired = Some(Utc::now() - Duration::hours(1));
+        assert_eq!(
+            false,
+            holds_all_of(&user(vec![user_grant("a", None, expired)]), &support)
+        );
+        assert_eq!(
+            true,
+            holds_all_of(&support, &user(vec![user_grant("b", None, expired)]))
+        );
+    }
 }
@@ -1,2 +1,4 @@
 pub mod error;
+pub mod grant_pattern;
 pub mod grants;
+pub mod request;
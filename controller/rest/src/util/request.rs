@@ -0,0 +1,37 @@
+use poem::Request;
+
+/// Longest user agent we bother storing, anything past this is truncated
+const MAX_USER_AGENT_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+impl RequestContext {
+    /// `trust_forwarded_for` should only be set when running behind a proxy that overwrites
+    /// `X-Forwarded-For`, otherwise clients can claim to be anyone
+    pub fn from_request(req: &Request, trust_forwarded_for: bool) -> Self {
+        let forwarded_for = trust_forwarded_for
+            .then(|| req.header("x-forwarded-for"))
+            .flatten()
+            .and_then(|header| header.split(',').next())
+            .map(|ip| ip.trim().to_string())
+            .filter(|ip| !ip.is_empty());
+
+        let source_ip = forwarded_for.or_else(|| {
+            req.remote_addr()
+                .as_socket_addr()
+                .map(|addr| addr.ip().to_string())
+        });
+
+        let user_agent = req
+            .header("user-agent")
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect());
+
+        Self {
+            source_ip,
+            user_agent,
+        }
+    }
+}
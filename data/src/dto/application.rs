@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use valuable::Valuable;
 
 use crate::{
-    dto::{error::DtoError, grant::GrantDto},
+    dto::{client_grant::ClientGrantDetailDto, error::DtoError, grant::GrantDto},
     impl_try_from_with,
 };
 
@@ -19,6 +19,11 @@ pub struct ApplicationDto {
     pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
     #[valuable(skip)]
     pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    /// `public` or `confidential`, as an OAuth client
+    pub client_type: String,
+    /// Hash of a confidential client's secret
+    #[valuable(skip)]
+    pub client_secret: Option<String>,
 }
 
 impl ApplicationDto {
@@ -30,6 +35,8 @@ impl ApplicationDto {
         updated_by: String,
         created_at: DateTime,
         updated_at: DateTime,
+        client_type: String,
+        client_secret: Option<String>,
     ) -> Result<Self, DtoError> {
         Ok(Self {
             application_id: application_id,
@@ -39,6 +46,8 @@ impl ApplicationDto {
             updated_by: updated_by,
             created_at: created_at.and_utc(),
             updated_at: updated_at.and_utc(),
+            client_type,
+            client_secret,
         })
     }
 }
@@ -56,6 +65,8 @@ impl_try_from_with!(
         updated_by,
         created_at,
         updated_at,
+        client_type,
+        client_secret,
     ]
 );
 
@@ -63,4 +74,8 @@ impl_try_from_with!(
 pub struct ApplicationDetailDto {
     pub application: ApplicationDto,
     pub grants: Vec<GrantDto>,
+    /// Where `/oauth/authorize` may send the user back to, matched exactly
+    pub redirect_uris: Vec<String>,
+    /// Grants the application holds itself, carried by its client_credentials tokens
+    pub client_grants: Vec<ClientGrantDetailDto>,
 }
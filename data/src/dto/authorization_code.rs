@@ -0,0 +1,112 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct AuthorizationCodeDto {
+    pub authorization_code_id: i32,
+    pub user_id: i32,
+    pub application_id: String,
+    #[valuable(skip)]
+    pub code_hash: String,
+    pub redirect_uri: String,
+    /// PKCE S256 challenge the code verifier must hash to
+    #[valuable(skip)]
+    pub code_challenge: String,
+    /// How the user authenticated when they approved the client, carried into the session
+    pub amr: Vec<String>,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    /// The session the code was exchanged for, ended if the code is ever replayed
+    pub session_id: Option<String>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    /// OAuth scopes the user approved
+    pub scope: Vec<String>,
+    /// OpenID Connect nonce, echoed back in the ID token
+    #[valuable(skip)]
+    pub nonce: Option<String>,
+    /// Whether `redirect_uri` was passed to authorize rather than defaulted, making it
+    /// required when the code is exchanged
+    pub redirect_uri_explicit: bool,
+}
+
+impl AuthorizationCodeDto {
+    pub fn from_ordered(
+        authorization_code_id: i32,
+        user_id: i32,
+        application_id: String,
+        code_hash: String,
+        redirect_uri: String,
+        code_challenge: String,
+        amr: String,
+        expires_at: DateTime,
+        used_at: Option<DateTime>,
+        session_id: Option<String>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+        scope: String,
+        nonce: Option<String>,
+        redirect_uri_explicit: i8,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            authorization_code_id,
+            user_id,
+            application_id,
+            code_hash,
+            redirect_uri,
+            code_challenge,
+            amr: amr.split(',').map(String::from).collect(),
+            expires_at: expires_at.and_utc(),
+            used_at: used_at.map(|dt| dt.and_utc()),
+            session_id,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+            scope: scope.split_whitespace().map(String::from).collect(),
+            nonce,
+            redirect_uri_explicit: redirect_uri_explicit != 0,
+        })
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+impl_try_from_with!(
+    AuthorizationCodeDto,
+    authorization_code,
+    from_ordered,
+    DtoError,
+    [
+        authorization_code_id,
+        user_id,
+        application_id,
+        code_hash,
+        redirect_uri,
+        code_challenge,
+        amr,
+        expires_at,
+        used_at,
+        session_id,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+        scope,
+        nonce,
+        redirect_uri_explicit,
+    ]
+);
@@ -0,0 +1,123 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct DeviceCodeDto {
+    pub device_code_id: i32,
+    pub application_id: String,
+    #[valuable(skip)]
+    pub device_code_hash: String,
+    #[valuable(skip)]
+    pub user_code_hash: String,
+    /// OAuth scopes the device asked for
+    pub scope: Vec<String>,
+    /// How long the device must wait between polls, raised each time it polls too soon
+    pub interval_seconds: i32,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub last_polled_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    /// The user who entered the code, once they have approved or denied it
+    pub user_id: Option<i32>,
+    /// How the user authenticated when they approved the device, carried into the session
+    pub amr: Vec<String>,
+    #[valuable(skip)]
+    pub approved_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub denied_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    /// The session the device code was exchanged for
+    pub session_id: Option<String>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl DeviceCodeDto {
+    pub fn from_ordered(
+        device_code_id: i32,
+        application_id: String,
+        device_code_hash: String,
+        user_code_hash: String,
+        scope: String,
+        interval_seconds: i32,
+        expires_at: DateTime,
+        last_polled_at: Option<DateTime>,
+        user_id: Option<i32>,
+        amr: Option<String>,
+        approved_at: Option<DateTime>,
+        denied_at: Option<DateTime>,
+        used_at: Option<DateTime>,
+        session_id: Option<String>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            device_code_id,
+            application_id,
+            device_code_hash,
+            user_code_hash,
+            scope: scope.split_whitespace().map(String::from).collect(),
+            interval_seconds,
+            expires_at: expires_at.and_utc(),
+            last_polled_at: last_polled_at.map(|dt| dt.and_utc()),
+            user_id,
+            amr: amr
+                .map(|amr| amr.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            approved_at: approved_at.map(|dt| dt.and_utc()),
+            denied_at: denied_at.map(|dt| dt.and_utc()),
+            used_at: used_at.map(|dt| dt.and_utc()),
+            session_id,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    /// Still waiting for a user to approve or deny it
+    pub fn is_pending(&self) -> bool {
+        self.approved_at.is_none() && self.denied_at.is_none() && !self.is_expired()
+    }
+}
+
+impl_try_from_with!(
+    DeviceCodeDto,
+    device_code,
+    from_ordered,
+    DtoError,
+    [
+        device_code_id,
+        application_id,
+        device_code_hash,
+        user_code_hash,
+        scope,
+        interval_seconds,
+        expires_at,
+        last_polled_at,
+        user_id,
+        amr,
+        approved_at,
+        denied_at,
+        used_at,
+        session_id,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
@@ -0,0 +1,75 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct EmailVerificationTokenDto {
+    pub email_verification_token_id: i32,
+    pub user_id: i32,
+    pub email: String,
+    #[valuable(skip)]
+    pub token_hash: String,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl EmailVerificationTokenDto {
+    pub fn from_ordered(
+        email_verification_token_id: i32,
+        user_id: i32,
+        email: String,
+        token_hash: String,
+        expires_at: DateTime,
+        used_at: Option<DateTime>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            email_verification_token_id,
+            user_id,
+            email,
+            token_hash,
+            expires_at: expires_at.and_utc(),
+            used_at: used_at.map(|dt| dt.and_utc()),
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+impl_try_from_with!(
+    EmailVerificationTokenDto,
+    email_verification_token,
+    from_ordered,
+    DtoError,
+    [
+        email_verification_token_id,
+        user_id,
+        email,
+        token_hash,
+        expires_at,
+        used_at,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
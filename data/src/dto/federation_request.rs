@@ -0,0 +1,86 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct FederationRequestDto {
+    pub federation_request_id: i32,
+    #[valuable(skip)]
+    pub state_hash: String,
+    pub provider_id: String,
+    #[valuable(skip)]
+    pub nonce: String,
+    #[valuable(skip)]
+    pub code_verifier: String,
+    /// The user to link the upstream identity to, unset when logging in
+    pub user_id: Option<i32>,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl FederationRequestDto {
+    pub fn from_ordered(
+        federation_request_id: i32,
+        state_hash: String,
+        provider_id: String,
+        nonce: String,
+        code_verifier: String,
+        user_id: Option<i32>,
+        expires_at: DateTime,
+        used_at: Option<DateTime>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            federation_request_id,
+            state_hash,
+            provider_id,
+            nonce,
+            code_verifier,
+            user_id,
+            expires_at: expires_at.and_utc(),
+            used_at: used_at.map(|dt| dt.and_utc()),
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+impl_try_from_with!(
+    FederationRequestDto,
+    federation_request,
+    from_ordered,
+    DtoError,
+    [
+        federation_request_id,
+        state_hash,
+        provider_id,
+        nonce,
+        code_verifier,
+        user_id,
+        expires_at,
+        used_at,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
@@ -0,0 +1,56 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, grant::GrantDto},
+    impl_try_from_with,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct GrantImplicationDto {
+    pub grant_implication_id: i32,
+    pub grant_id: String,
+    pub implied_grant_id: String,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl GrantImplicationDto {
+    pub fn from_ordered(
+        grant_implication_id: i32,
+        grant_id: String,
+        implied_grant_id: String,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            grant_implication_id,
+            grant_id,
+            implied_grant_id,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    GrantImplicationDto,
+    grant_implication,
+    from_ordered,
+    DtoError,
+    [
+        grant_implication_id,
+        grant_id,
+        implied_grant_id,
+        created_by,
+        created_at,
+    ]
+);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct GrantImplicationDetailDto {
+    pub implication: GrantImplicationDto,
+    pub implied: GrantDto,
+}
@@ -0,0 +1,67 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, grant::GrantDto, role::RoleDetailDto},
+    impl_try_from_with,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct GroupDto {
+    pub group_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl GroupDto {
+    pub fn from_ordered(
+        group_id: String,
+        display_name: String,
+        description: String,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            group_id,
+            display_name,
+            description,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    GroupDto,
+    group,
+    from_ordered,
+    DtoError,
+    [
+        group_id,
+        display_name,
+        description,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
+
+/// A group with the grants and roles given to its members
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct GroupDetailDto {
+    pub group: GroupDto,
+    pub grants: Vec<GrantDto>,
+    pub roles: Vec<RoleDetailDto>,
+}
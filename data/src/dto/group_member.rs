@@ -0,0 +1,52 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+/// Exactly one of `user_id` and `member_group_id` is set
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct GroupMemberDto {
+    pub group_member_id: i32,
+    pub group_id: String,
+    pub user_id: Option<i32>,
+    pub member_group_id: Option<String>,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl GroupMemberDto {
+    pub fn from_ordered(
+        group_member_id: i32,
+        group_id: String,
+        user_id: Option<i32>,
+        member_group_id: Option<String>,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            group_member_id,
+            group_id,
+            user_id,
+            member_group_id,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    GroupMemberDto,
+    group_member,
+    from_ordered,
+    DtoError,
+    [
+        group_member_id,
+        group_id,
+        user_id,
+        member_group_id,
+        created_by,
+        created_at,
+    ]
+);
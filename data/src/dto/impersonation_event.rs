@@ -0,0 +1,74 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct ImpersonationEventDto {
+    pub impersonation_event_id: i32,
+    /// The user who was impersonated
+    pub user_id: i32,
+    /// `None` once the actor has been deleted
+    pub actor_user_id: Option<i32>,
+    pub actor_username: String,
+    pub session_id: String,
+    pub reason: String,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl ImpersonationEventDto {
+    pub fn from_ordered(
+        impersonation_event_id: i32,
+        user_id: i32,
+        actor_user_id: Option<i32>,
+        actor_username: String,
+        session_id: String,
+        reason: String,
+        expires_at: DateTime,
+        source_ip: Option<String>,
+        user_agent: Option<String>,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            impersonation_event_id,
+            user_id,
+            actor_user_id,
+            actor_username,
+            session_id,
+            reason,
+            expires_at: expires_at.and_utc(),
+            source_ip,
+            user_agent,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    ImpersonationEventDto,
+    impersonation_event,
+    from_ordered,
+    DtoError,
+    [
+        impersonation_event_id,
+        user_id,
+        actor_user_id,
+        actor_username,
+        session_id,
+        reason,
+        expires_at,
+        source_ip,
+        user_agent,
+        created_by,
+        created_at,
+    ]
+);
@@ -0,0 +1,39 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct KeyPromotionDto {
+    pub key_promotion_id: i32,
+    /// The key signing from then on
+    pub kid: String,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl KeyPromotionDto {
+    pub fn from_ordered(
+        key_promotion_id: i32,
+        kid: String,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            key_promotion_id,
+            kid,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    KeyPromotionDto,
+    key_promotion,
+    from_ordered,
+    DtoError,
+    [key_promotion_id, kid, created_by, created_at,]
+);
@@ -0,0 +1,59 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct LoginEventDto {
+    pub login_event_id: i32,
+    pub user_id: Option<i32>,
+    pub username: String,
+    pub outcome: String,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl LoginEventDto {
+    pub fn from_ordered(
+        login_event_id: i32,
+        user_id: Option<i32>,
+        username: String,
+        outcome: String,
+        source_ip: Option<String>,
+        user_agent: Option<String>,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            login_event_id,
+            user_id,
+            username,
+            outcome,
+            source_ip,
+            user_agent,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    LoginEventDto,
+    login_event,
+    from_ordered,
+    DtoError,
+    [
+        login_event_id,
+        user_id,
+        username,
+        outcome,
+        source_ip,
+        user_agent,
+        created_by,
+        created_at,
+    ]
+);
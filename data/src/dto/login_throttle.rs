@@ -0,0 +1,49 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct LoginThrottleDto {
+    pub throttle_key: String,
+    pub failures: i32,
+    #[valuable(skip)]
+    pub locked_until: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub last_failure_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl LoginThrottleDto {
+    pub fn from_ordered(
+        throttle_key: String,
+        failures: i32,
+        locked_until: Option<DateTime>,
+        last_failure_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            throttle_key,
+            failures,
+            locked_until: locked_until.map(|dt| dt.and_utc()),
+            last_failure_at: last_failure_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    LoginThrottleDto,
+    login_throttle,
+    from_ordered,
+    DtoError,
+    [
+        throttle_key,
+        failures,
+        locked_until,
+        last_failure_at,
+        updated_at,
+    ]
+);
@@ -0,0 +1,77 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct MfaChallengeDto {
+    pub mfa_challenge_id: i32,
+    pub user_id: i32,
+    #[valuable(skip)]
+    pub challenge_hash: String,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    /// Replaces a temporary password once the challenge passes
+    #[valuable(skip)]
+    pub new_password_hash: Option<String>,
+}
+
+impl MfaChallengeDto {
+    pub fn from_ordered(
+        mfa_challenge_id: i32,
+        user_id: i32,
+        challenge_hash: String,
+        expires_at: DateTime,
+        used_at: Option<DateTime>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+        new_password_hash: Option<String>,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            mfa_challenge_id,
+            user_id,
+            challenge_hash,
+            expires_at: expires_at.and_utc(),
+            used_at: used_at.map(|dt| dt.and_utc()),
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+            new_password_hash,
+        })
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+impl_try_from_with!(
+    MfaChallengeDto,
+    mfa_challenge,
+    from_ordered,
+    DtoError,
+    [
+        mfa_challenge_id,
+        user_id,
+        challenge_hash,
+        expires_at,
+        used_at,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+        new_password_hash,
+    ]
+);
@@ -1,8 +1,29 @@
 pub mod application;
+pub mod authorization_code;
+pub mod client_grant;
+pub mod device_code;
+pub mod email_verification_token;
 pub mod error;
+pub mod federation_request;
 pub mod grant;
+pub mod grant_implication;
+pub mod group;
+pub mod group_member;
+pub mod impersonation_event;
+pub mod key_promotion;
+pub mod login_event;
+pub mod login_throttle;
+pub mod mfa_challenge;
+pub mod password_reset_token;
+pub mod personal_access_token;
+pub mod refresh_token;
+pub mod role;
+pub mod session;
 pub mod user;
 pub mod user_grant;
+pub mod user_identity;
+pub mod user_role;
+pub mod user_totp;
 
 #[macro_export]
 macro_rules! impl_try_from_with {
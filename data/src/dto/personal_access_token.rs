@@ -0,0 +1,89 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct PersonalAccessTokenDto {
+    pub personal_access_token_id: i32,
+    pub user_id: i32,
+    pub name: String,
+    #[valuable(skip)]
+    pub token_hash: String,
+    /// The most the token may carry, narrowed further to the user's current grants on use
+    pub grants: Vec<String>,
+    #[valuable(skip)]
+    pub expires_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub last_used_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub revoked_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl PersonalAccessTokenDto {
+    pub fn from_ordered(
+        personal_access_token_id: i32,
+        user_id: i32,
+        name: String,
+        token_hash: String,
+        grants: String,
+        expires_at: Option<DateTime>,
+        last_used_at: Option<DateTime>,
+        revoked_at: Option<DateTime>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            personal_access_token_id,
+            user_id,
+            name,
+            token_hash,
+            grants: grants
+                .split(',')
+                .filter(|grant| !grant.is_empty())
+                .map(String::from)
+                .collect(),
+            expires_at: expires_at.map(|dt| dt.and_utc()),
+            last_used_at: last_used_at.map(|dt| dt.and_utc()),
+            revoked_at: revoked_at.map(|dt| dt.and_utc()),
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|it| it > Utc::now())
+    }
+}
+
+impl_try_from_with!(
+    PersonalAccessTokenDto,
+    personal_access_token,
+    from_ordered,
+    DtoError,
+    [
+        personal_access_token_id,
+        user_id,
+        name,
+        token_hash,
+        grants,
+        expires_at,
+        last_used_at,
+        revoked_at,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
@@ -0,0 +1,70 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, grant::GrantDto},
+    impl_try_from_with,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct RoleDto {
+    pub role_id: String,
+    pub application_id: String,
+    pub display_name: String,
+    pub description: String,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl RoleDto {
+    pub fn from_ordered(
+        role_id: String,
+        application_id: String,
+        display_name: String,
+        description: String,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            role_id,
+            application_id,
+            display_name,
+            description,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    RoleDto,
+    role,
+    from_ordered,
+    DtoError,
+    [
+        role_id,
+        application_id,
+        display_name,
+        description,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
+
+/// A role with the grants it bundles, all of which belong to the role's application
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct RoleDetailDto {
+    pub role: RoleDto,
+    pub grants: Vec<GrantDto>,
+}
@@ -0,0 +1,89 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct SessionDto {
+    pub session_id: String,
+    pub user_id: i32,
+    pub last_token_id: String,
+    #[valuable(skip)]
+    pub expires_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub revoked_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub revoked_by: Option<String>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    /// How the user authenticated when the session was opened, see RFC 8176
+    pub amr: Vec<String>,
+    /// The OAuth client the session was opened for, `None` for first party logins
+    pub application_id: Option<String>,
+    /// OAuth scopes granted to the session, empty for first party logins
+    pub scope: Vec<String>,
+}
+
+impl SessionDto {
+    pub fn from_ordered(
+        session_id: String,
+        user_id: i32,
+        last_token_id: String,
+        expires_at: DateTime,
+        revoked_at: Option<DateTime>,
+        revoked_by: Option<String>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+        amr: String,
+        application_id: Option<String>,
+        scope: String,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            session_id,
+            user_id,
+            last_token_id,
+            expires_at: expires_at.and_utc(),
+            revoked_at: revoked_at.map(|dt| dt.and_utc()),
+            revoked_by,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+            amr: amr.split(',').map(String::from).collect(),
+            application_id,
+            scope: scope.split_whitespace().map(String::from).collect(),
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+impl_try_from_with!(
+    SessionDto,
+    session,
+    from_ordered,
+    DtoError,
+    [
+        session_id,
+        user_id,
+        last_token_id,
+        expires_at,
+        revoked_at,
+        revoked_by,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+        amr,
+        application_id,
+        scope,
+    ]
+);
@@ -1,9 +1,14 @@
+use std::collections::HashSet;
+
 use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
 use serde::{Deserialize, Serialize};
 use valuable::Valuable;
 
 use crate::{
-    dto::{error::DtoError, user_grant::UserGrantDetailDto},
+    dto::{
+        error::DtoError, grant::GrantDto, group::GroupDetailDto, user_grant::UserGrantDetailDto,
+        user_role::UserRoleDetailDto,
+    },
     impl_try_from_with,
 };
 
@@ -24,6 +29,12 @@ pub struct UserDto {
     pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
     #[valuable(skip)]
     pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    /// Set by an admin reset, the password must be changed at the next login
+    pub password_change_required: bool,
+    #[valuable(skip)]
+    pub email_verified_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    /// Unset on hashes written before every hash got its own salt, which may share one
+    pub password_salt_unique: bool,
 }
 
 impl UserDto {
@@ -40,6 +51,9 @@ impl UserDto {
         updated_by: String,
         created_at: DateTime,
         updated_at: DateTime,
+        password_change_required: i8,
+        email_verified_at: Option<DateTime>,
+        password_salt_unique: i8,
     ) -> Result<Self, DtoError> {
         Ok(Self {
             user_id,
@@ -54,8 +68,15 @@ impl UserDto {
             updated_by,
             created_at: created_at.and_utc(),
             updated_at: updated_at.and_utc(),
+            password_change_required: password_change_required != 0,
+            email_verified_at: email_verified_at.map(|dt| dt.and_utc()),
+            password_salt_unique: password_salt_unique != 0,
         })
     }
+
+    pub fn is_email_verified(&self) -> bool {
+        self.email.is_some() && self.email_verified_at.is_some()
+    }
 }
 
 impl_try_from_with!(
@@ -76,6 +97,9 @@ impl_try_from_with!(
         updated_by,
         created_at,
         updated_at,
+        password_change_required,
+        email_verified_at,
+        password_salt_unique,
     ]
 );
 
@@ -83,4 +107,28 @@ impl_try_from_with!(
 pub struct UserDetailDto {
     pub user: UserDto,
     pub grants: Vec<UserGrantDetailDto>,
+    pub roles: Vec<UserRoleDetailDto>,
+    /// Every group the user belongs to, directly or through a nested group
+    pub groups: Vec<GroupDetailDto>,
+}
+impl UserDetailDto {
+    /// The grants the user holds, the union of their current grants, their roles' grants and
+    /// those their groups give them
+    pub fn effective_grants(&self) -> Vec<&GrantDto> {
+        let mut seen = HashSet::new();
+        let roles = self
+            .roles
+            .iter()
+            .map(|role| &role.role)
+            .chain(self.groups.iter().flat_map(|group| group.roles.iter()));
+
+        self.grants
+            .iter()
+            .filter(|grant| grant.user_grant.is_current())
+            .map(|grant| &grant.grant.grant)
+            .chain(self.groups.iter().flat_map(|group| group.grants.iter()))
+            .chain(roles.flat_map(|role| role.grants.iter()))
+            .filter(|grant| seen.insert(grant.grant_id.as_str()))
+            .collect()
+    }
 }
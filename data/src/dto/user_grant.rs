@@ -22,6 +22,10 @@ pub struct UserGrantDto {
     pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
     #[valuable(skip)]
     pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub valid_from: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    #[valuable(skip)]
+    pub valid_until: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
 }
 
 impl UserGrantDto {
@@ -35,6 +39,8 @@ impl UserGrantDto {
         updated_by: String,
         created_at: DateTime,
         updated_at: DateTime,
+        valid_from: Option<DateTime>,
+        valid_until: Option<DateTime>,
     ) -> Result<Self, DtoError> {
         Ok(Self {
             user_id,
@@ -46,8 +52,23 @@ impl UserGrantDto {
             updated_by,
             created_at: created_at.and_utc(),
             updated_at: updated_at.and_utc(),
+            valid_from: valid_from.map(|dt| dt.and_utc()),
+            valid_until: valid_until.map(|dt| dt.and_utc()),
         })
     }
+
+    /// Enabled, and within its validity window if it has one
+    pub fn is_current(&self) -> bool {
+        self.is_current_at(Utc::now())
+    }
+
+    /// Enabled, and within its validity window at `now` if it has one. The window includes
+    /// `valid_from` but not `valid_until`.
+    pub fn is_current_at(&self, now: sea_orm::sqlx::types::chrono::DateTime<Utc>) -> bool {
+        self.enabled
+            && self.valid_from.is_none_or(|it| it <= now)
+            && self.valid_until.is_none_or(|it| now < it)
+    }
 }
 
 impl_try_from_with!(
@@ -65,6 +86,8 @@ impl_try_from_with!(
         updated_by,
         created_at,
         updated_at,
+        valid_from,
+        valid_until,
     ]
 );
 
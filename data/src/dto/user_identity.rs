@@ -0,0 +1,71 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct UserIdentityDto {
+    pub user_identity_id: i32,
+    pub user_id: i32,
+    pub provider_id: String,
+    /// The upstream `sub`, only unique within its provider
+    pub subject: String,
+    /// As last reported by the provider, whether or not it was verified
+    pub email: Option<String>,
+    #[valuable(skip)]
+    pub last_login_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl UserIdentityDto {
+    pub fn from_ordered(
+        user_identity_id: i32,
+        user_id: i32,
+        provider_id: String,
+        subject: String,
+        email: Option<String>,
+        last_login_at: Option<DateTime>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            user_identity_id,
+            user_id,
+            provider_id,
+            subject,
+            email,
+            last_login_at: last_login_at.map(|dt| dt.and_utc()),
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    UserIdentityDto,
+    user_identity,
+    from_ordered,
+    DtoError,
+    [
+        user_identity_id,
+        user_id,
+        provider_id,
+        subject,
+        email,
+        last_login_at,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
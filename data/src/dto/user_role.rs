@@ -0,0 +1,50 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, role::RoleDetailDto},
+    impl_try_from_with,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct UserRoleDto {
+    pub user_role_id: i32,
+    pub user_id: i32,
+    pub role_id: String,
+    pub created_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl UserRoleDto {
+    pub fn from_ordered(
+        user_role_id: i32,
+        user_id: i32,
+        role_id: String,
+        created_by: String,
+        created_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            user_role_id,
+            user_id,
+            role_id,
+            created_by,
+            created_at: created_at.and_utc(),
+        })
+    }
+}
+
+impl_try_from_with!(
+    UserRoleDto,
+    user_role,
+    from_ordered,
+    DtoError,
+    [user_role_id, user_id, role_id, created_by, created_at,]
+);
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct UserRoleDetailDto {
+    pub user_role: UserRoleDto,
+    pub role: RoleDetailDto,
+}
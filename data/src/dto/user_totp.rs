@@ -0,0 +1,70 @@
+use sea_orm::{prelude::DateTime, sqlx::types::chrono::Utc};
+use serde::{Deserialize, Serialize};
+use valuable::Valuable;
+
+use crate::{dto::error::DtoError, impl_try_from_with};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Valuable)]
+pub struct UserTotpDto {
+    pub user_totp_id: i32,
+    pub user_id: i32,
+    #[valuable(skip)]
+    pub secret: String,
+    #[valuable(skip)]
+    pub confirmed_at: Option<sea_orm::sqlx::types::chrono::DateTime<Utc>>,
+    pub last_used_step: Option<i64>,
+    pub created_by: String,
+    pub updated_by: String,
+    #[valuable(skip)]
+    pub created_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+    #[valuable(skip)]
+    pub updated_at: sea_orm::sqlx::types::chrono::DateTime<Utc>,
+}
+
+impl UserTotpDto {
+    pub fn from_ordered(
+        user_totp_id: i32,
+        user_id: i32,
+        secret: String,
+        confirmed_at: Option<DateTime>,
+        last_used_step: Option<i64>,
+        created_by: String,
+        updated_by: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    ) -> Result<Self, DtoError> {
+        Ok(Self {
+            user_totp_id,
+            user_id,
+            secret,
+            confirmed_at: confirmed_at.map(|dt| dt.and_utc()),
+            last_used_step,
+            created_by,
+            updated_by,
+            created_at: created_at.and_utc(),
+            updated_at: updated_at.and_utc(),
+        })
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+impl_try_from_with!(
+    UserTotpDto,
+    user_totp,
+    from_ordered,
+    DtoError,
+    [
+        user_totp_id,
+        user_id,
+        secret,
+        confirmed_at,
+        last_used_step,
+        created_by,
+        updated_by,
+        created_at,
+        updated_at,
+    ]
+);
@@ -1,14 +1,17 @@
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, DatabaseConnection, EntityTrait, IntoActiveModel,
-    sqlx::types::chrono::Utc,
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, IntoActiveModel, QueryFilter, QuerySelect, TransactionTrait,
+    sea_query::OnConflict, sqlx::types::chrono::Utc,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::Level;
 use valuable::Valuable;
 
 use crate::{
     dto::{
         application::{ApplicationDetailDto, ApplicationDto},
+        client_grant::{ClientGrantDetailDto, ClientGrantDto},
         error::DtoError,
         grant::GrantDto,
     },
@@ -68,13 +71,77 @@ impl ApplicationRepository {
             .into_iter()
             .map(|grant| GrantDto::try_from(grant))
             .collect::<Result<Vec<_>, _>>()?;
+        let redirect_uris = model::redirect_uri::Entity::find()
+            .select_only()
+            .column(model::redirect_uri::Column::RedirectUri)
+            .filter(model::redirect_uri::Column::ApplicationId.eq(application_id))
+            .into_tuple()
+            .all(&self.conn)
+            .await?;
+        let client_grants = self.client_grants(application_id).await?;
 
         Ok(Some(ApplicationDetailDto {
             application,
             grants,
+            redirect_uris,
+            client_grants,
         }))
     }
 
+    async fn client_grants(
+        &self,
+        application_id: &str,
+    ) -> ApplicationResult<Vec<ClientGrantDetailDto>> {
+        let them = model::client_grant::Entity::find()
+            .find_also_related(model::grant::Entity)
+            .filter(model::client_grant::Column::ApplicationId.eq(application_id))
+            .all(&self.conn)
+            .await?;
+
+        let mut client_grants = Vec::with_capacity(them.len());
+        for (client_grant, grant) in them {
+            let Some(grant) = grant else {
+                continue;
+            };
+
+            client_grants.push(ClientGrantDetailDto {
+                client_grant: ClientGrantDto::try_from(client_grant)?,
+                grant: GrantDto::try_from(grant)?,
+            });
+        }
+
+        Ok(client_grants)
+    }
+
+    async fn replace_redirect_uris<C: ConnectionTrait>(
+        conn: &C,
+        agent: &str,
+        application_id: &str,
+        redirect_uris: &[String],
+    ) -> ApplicationResult<()> {
+        model::redirect_uri::Entity::delete_many()
+            .filter(model::redirect_uri::Column::ApplicationId.eq(application_id))
+            .exec(conn)
+            .await?;
+
+        if redirect_uris.is_empty() {
+            return Ok(());
+        }
+
+        model::redirect_uri::Entity::insert_many(redirect_uris.iter().map(|redirect_uri| {
+            model::redirect_uri::ActiveModel {
+                application_id: Set(application_id.into()),
+                redirect_uri: Set(redirect_uri.clone()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+            }
+        }))
+        .exec(conn)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn list(&self) -> ApplicationResult<Vec<ApplicationDto>> {
         Ok(model::application::Entity::load()
             .all(&self.conn)
@@ -90,7 +157,11 @@ impl ApplicationRepository {
         id: &str,
         display_name: &str,
         description: &str,
+        client_type: &str,
+        redirect_uris: &[String],
     ) -> ApplicationResult<ApplicationDetailDto> {
+        let txn = self.conn.begin().await?;
+
         model::application::Entity::insert(model::application::ActiveModel {
             application_id: Set(id.into()),
             display_name: Set(display_name.into()),
@@ -99,9 +170,14 @@ impl ApplicationRepository {
             updated_by: Set(agent.into()),
             created_at: Set(Utc::now().naive_utc()),
             updated_at: Set(Utc::now().naive_utc()),
+            client_type: Set(client_type.into()),
+            client_secret: Set(None),
         })
-        .exec(&self.conn)
+        .exec(&txn)
         .await?;
+        Self::replace_redirect_uris(&txn, agent, id, redirect_uris).await?;
+
+        txn.commit().await?;
 
         self.by_id(id)
             .await?
@@ -116,8 +192,15 @@ impl ApplicationRepository {
         application_id: &str,
         display_name: Option<&str>,
         description: Option<&str>,
+        client_type: Option<&str>,
+        redirect_uris: Option<&[String]>,
     ) -> ApplicationResult<ApplicationDetailDto> {
-        let has_changes = { description.is_some() || display_name.is_some() };
+        let has_changes = {
+            description.is_some()
+                || display_name.is_some()
+                || client_type.is_some()
+                || redirect_uris.is_some()
+        };
 
         if !has_changes {
             return Err(ApplicationError::NoChangeRequested);
@@ -133,10 +216,16 @@ impl ApplicationRepository {
 
         app.display_name = display_name.into_active_value_ext();
         app.description = description.into_active_value_ext();
+        app.client_type = client_type.into_active_value_ext();
         app.updated_at = Set(Utc::now().naive_utc());
         app.updated_by = Set(agent.into());
 
-        app.update(&self.conn).await?;
+        let txn = self.conn.begin().await?;
+        app.update(&txn).await?;
+        if let Some(redirect_uris) = redirect_uris {
+            Self::replace_redirect_uris(&txn, agent, application_id, redirect_uris).await?;
+        }
+        txn.commit().await?;
 
         self.by_id(&application_id)
             .await?
@@ -144,4 +233,83 @@ impl ApplicationRepository {
                 application_id: application_id.into(),
             })
     }
+
+    /// Replaces the client secret hash, `None` removes it
+    #[tracing::instrument(level = Level::DEBUG, "data.application.set_client_secret", skip(client_secret))]
+    pub async fn set_client_secret(
+        &self,
+        agent: &str,
+        application_id: &str,
+        client_secret: Option<&str>,
+    ) -> ApplicationResult<()> {
+        let mut app = model::application::Entity::find_by_id(application_id.to_string())
+            .one(&self.conn)
+            .await?
+            .ok_or(ApplicationError::ApplicationNotFound {
+                application_id: application_id.into(),
+            })?
+            .into_active_model();
+
+        app.client_secret = Set(client_secret.map(String::from));
+        app.updated_at = Set(Utc::now().naive_utc());
+        app.updated_by = Set(agent.into());
+        app.update(&self.conn).await?;
+
+        Ok(())
+    }
+
+    /// Enables or disables a grant for the application's service principal
+    #[tracing::instrument(level = Level::DEBUG, "data.application.update_client_grant")]
+    pub async fn update_client_grant(
+        &self,
+        agent: &str,
+        application_id: &str,
+        grant_id: &str,
+        enabled: bool,
+    ) -> ApplicationResult<()> {
+        model::application::Entity::find_by_id(application_id.to_string())
+            .one(&self.conn)
+            .await?
+            .ok_or(ApplicationError::ApplicationNotFound {
+                application_id: application_id.into(),
+            })?;
+
+        let now = Utc::now().naive_utc();
+        let (enabled_at, disabled_at) = if enabled {
+            (Some(now), None)
+        } else {
+            (None, Some(now))
+        };
+
+        let on_conflict = OnConflict::columns([
+            model::client_grant::Column::ApplicationId,
+            model::client_grant::Column::GrantId,
+        ])
+        .update_columns([
+            model::client_grant::Column::Enabled,
+            model::client_grant::Column::UpdatedBy,
+            model::client_grant::Column::UpdatedAt,
+            model::client_grant::Column::EnabledAt,
+            model::client_grant::Column::DisabledAt,
+        ])
+        .to_owned();
+
+        model::client_grant::Entity::insert(model::client_grant::ActiveModel {
+            application_id: Set(application_id.into()),
+            grant_id: Set(grant_id.into()),
+            enabled: Set(enabled.into()),
+            enabled_at: Set(enabled_at),
+            disabled_at: Set(disabled_at),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        })
+        .on_conflict(on_conflict)
+        .exec(&self.conn)
+        .await?;
+
+        Ok(())
+    }
 }
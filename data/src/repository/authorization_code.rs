@@ -0,0 +1,183 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{authorization_code::AuthorizationCodeDto, error::DtoError},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum AuthorizationCodeError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No authorization code was found with authorization_code_id={authorization_code_id}")]
+    CodeNotFound { authorization_code_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for AuthorizationCodeError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type AuthorizationCodeResult<T> = Result<T, AuthorizationCodeError>;
+
+#[derive(Clone, Debug)]
+pub struct AuthorizationCodeRepository {
+    conn: DatabaseConnection,
+}
+impl AuthorizationCodeRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.authorization_code.by_id")]
+    pub async fn by_id(
+        &self,
+        authorization_code_id: i32,
+    ) -> AuthorizationCodeResult<Option<AuthorizationCodeDto>> {
+        let Some(code) = model::authorization_code::Entity::find_by_id(authorization_code_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(AuthorizationCodeDto::try_from(code)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.authorization_code.by_hash", skip(code_hash))]
+    pub async fn by_hash(
+        &self,
+        code_hash: &str,
+    ) -> AuthorizationCodeResult<Option<AuthorizationCodeDto>> {
+        let Some(code) = model::authorization_code::Entity::find()
+            .filter(model::authorization_code::Column::CodeHash.eq(code_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(AuthorizationCodeDto::try_from(code)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.authorization_code.create", skip(code_hash, code_challenge, nonce))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        application_id: &str,
+        code_hash: &str,
+        redirect_uri: &str,
+        redirect_uri_explicit: bool,
+        code_challenge: &str,
+        amr: &[String],
+        scope: &[String],
+        nonce: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> AuthorizationCodeResult<AuthorizationCodeDto> {
+        let it =
+            model::authorization_code::Entity::insert(model::authorization_code::ActiveModel {
+                user_id: Set(user_id),
+                application_id: Set(application_id.into()),
+                code_hash: Set(code_hash.into()),
+                redirect_uri: Set(redirect_uri.into()),
+                redirect_uri_explicit: Set(redirect_uri_explicit.into()),
+                code_challenge: Set(code_challenge.into()),
+                amr: Set(amr.join(",")),
+                expires_at: Set(expires_at.naive_utc()),
+                used_at: Set(None),
+                session_id: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                updated_at: Set(Utc::now().naive_utc()),
+                scope: Set(scope.join(" ")),
+                nonce: Set(nonce.map(String::from)),
+                ..Default::default()
+            })
+            .exec(&self.conn)
+            .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(AuthorizationCodeError::CodeNotFound {
+                authorization_code_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks a code as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.authorization_code.consume")]
+    pub async fn consume(
+        &self,
+        agent: &str,
+        authorization_code_id: i32,
+    ) -> AuthorizationCodeResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::authorization_code::Entity::update_many()
+            .col_expr(model::authorization_code::Column::UsedAt, Expr::value(now))
+            .col_expr(
+                model::authorization_code::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::authorization_code::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::authorization_code::Column::AuthorizationCodeId.eq(authorization_code_id),
+            )
+            .filter(model::authorization_code::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Records the session a code was exchanged for
+    #[tracing::instrument(level = Level::DEBUG, "data.authorization_code.set_session")]
+    pub async fn set_session(
+        &self,
+        agent: &str,
+        authorization_code_id: i32,
+        session_id: &str,
+    ) -> AuthorizationCodeResult<()> {
+        let now = Utc::now().naive_utc();
+        model::authorization_code::Entity::update_many()
+            .col_expr(
+                model::authorization_code::Column::SessionId,
+                Expr::value(session_id),
+            )
+            .col_expr(
+                model::authorization_code::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::authorization_code::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::authorization_code::Column::AuthorizationCodeId.eq(authorization_code_id),
+            )
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use sea_orm::{
+    ActiveValue::Set, DatabaseConnection, EntityTrait, sea_query::OnConflict,
+    sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{model, repository::error::RepositoryError};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum ConsentError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+}
+impl<E: Into<RepositoryError>> From<E> for ConsentError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type ConsentResult<T> = Result<T, ConsentError>;
+
+/// Remembers which OAuth clients a user has approved, so they aren't asked again
+#[derive(Clone, Debug)]
+pub struct ConsentRepository {
+    conn: DatabaseConnection,
+}
+impl ConsentRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.consent.exists")]
+    pub async fn exists(&self, user_id: i32, application_id: &str) -> ConsentResult<bool> {
+        Ok(
+            model::consent::Entity::find_by_id((user_id, application_id.to_string()))
+                .one(&self.conn)
+                .await?
+                .is_some(),
+        )
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.consent.grant")]
+    pub async fn grant(
+        &self,
+        agent: &str,
+        user_id: i32,
+        application_id: &str,
+    ) -> ConsentResult<()> {
+        let on_conflict = OnConflict::columns([
+            model::consent::Column::UserId,
+            model::consent::Column::ApplicationId,
+        ])
+        .update_columns([
+            model::consent::Column::UpdatedBy,
+            model::consent::Column::UpdatedAt,
+        ])
+        .to_owned();
+
+        model::consent::Entity::insert(model::consent::ActiveModel {
+            user_id: Set(user_id),
+            application_id: Set(application_id.into()),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+        })
+        .on_conflict(on_conflict)
+        .exec(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+}
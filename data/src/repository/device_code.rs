@@ -0,0 +1,228 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{device_code::DeviceCodeDto, error::DtoError},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum DeviceCodeError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No device code was found with device_code_id={device_code_id}")]
+    CodeNotFound { device_code_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for DeviceCodeError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type DeviceCodeResult<T> = Result<T, DeviceCodeError>;
+
+#[derive(Clone, Debug)]
+pub struct DeviceCodeRepository {
+    conn: DatabaseConnection,
+}
+impl DeviceCodeRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.by_id")]
+    pub async fn by_id(&self, device_code_id: i32) -> DeviceCodeResult<Option<DeviceCodeDto>> {
+        let Some(code) = model::device_code::Entity::find_by_id(device_code_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(DeviceCodeDto::try_from(code)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.by_device_code_hash", skip(device_code_hash))]
+    pub async fn by_device_code_hash(
+        &self,
+        device_code_hash: &str,
+    ) -> DeviceCodeResult<Option<DeviceCodeDto>> {
+        let Some(code) = model::device_code::Entity::find()
+            .filter(model::device_code::Column::DeviceCodeHash.eq(device_code_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(DeviceCodeDto::try_from(code)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.by_user_code_hash", skip(user_code_hash))]
+    pub async fn by_user_code_hash(
+        &self,
+        user_code_hash: &str,
+    ) -> DeviceCodeResult<Option<DeviceCodeDto>> {
+        let Some(code) = model::device_code::Entity::find()
+            .filter(model::device_code::Column::UserCodeHash.eq(user_code_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(DeviceCodeDto::try_from(code)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.create", skip(device_code_hash, user_code_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        application_id: &str,
+        device_code_hash: &str,
+        user_code_hash: &str,
+        scope: &[String],
+        interval_seconds: i32,
+        expires_at: DateTime<Utc>,
+    ) -> DeviceCodeResult<DeviceCodeDto> {
+        let it = model::device_code::Entity::insert(model::device_code::ActiveModel {
+            application_id: Set(application_id.into()),
+            device_code_hash: Set(device_code_hash.into()),
+            user_code_hash: Set(user_code_hash.into()),
+            scope: Set(scope.join(" ")),
+            interval_seconds: Set(interval_seconds),
+            expires_at: Set(expires_at.naive_utc()),
+            last_polled_at: Set(None),
+            user_id: Set(None),
+            amr: Set(None),
+            approved_at: Set(None),
+            denied_at: Set(None),
+            used_at: Set(None),
+            session_id: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(DeviceCodeError::CodeNotFound {
+                device_code_id: it.last_insert_id,
+            })
+    }
+
+    /// Records the user's decision, returns false if the code was already decided or expired
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.decide")]
+    pub async fn decide(
+        &self,
+        agent: &str,
+        device_code_id: i32,
+        user_id: i32,
+        amr: &[String],
+        approve: bool,
+    ) -> DeviceCodeResult<bool> {
+        let now = Utc::now().naive_utc();
+        let decided_at = if approve {
+            model::device_code::Column::ApprovedAt
+        } else {
+            model::device_code::Column::DeniedAt
+        };
+        let it = model::device_code::Entity::update_many()
+            .col_expr(decided_at, Expr::value(now))
+            .col_expr(model::device_code::Column::UserId, Expr::value(user_id))
+            .col_expr(model::device_code::Column::Amr, Expr::value(amr.join(",")))
+            .col_expr(model::device_code::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::device_code::Column::UpdatedAt, Expr::value(now))
+            .filter(model::device_code::Column::DeviceCodeId.eq(device_code_id))
+            .filter(model::device_code::Column::ApprovedAt.is_null())
+            .filter(model::device_code::Column::DeniedAt.is_null())
+            .filter(model::device_code::Column::ExpiresAt.gt(now))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Records a poll from the device along with the interval it must wait before the next,
+    /// deliberately leaving the audit columns alone
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.record_poll")]
+    pub async fn record_poll(
+        &self,
+        device_code_id: i32,
+        interval_seconds: i32,
+    ) -> DeviceCodeResult<()> {
+        model::device_code::Entity::update_many()
+            .col_expr(
+                model::device_code::Column::LastPolledAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .col_expr(
+                model::device_code::Column::IntervalSeconds,
+                Expr::value(interval_seconds),
+            )
+            .filter(model::device_code::Column::DeviceCodeId.eq(device_code_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Marks an approved code as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.consume")]
+    pub async fn consume(&self, agent: &str, device_code_id: i32) -> DeviceCodeResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::device_code::Entity::update_many()
+            .col_expr(model::device_code::Column::UsedAt, Expr::value(now))
+            .col_expr(model::device_code::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::device_code::Column::UpdatedAt, Expr::value(now))
+            .filter(model::device_code::Column::DeviceCodeId.eq(device_code_id))
+            .filter(model::device_code::Column::ApprovedAt.is_not_null())
+            .filter(model::device_code::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Records the session a device code was exchanged for
+    #[tracing::instrument(level = Level::DEBUG, "data.device_code.set_session")]
+    pub async fn set_session(
+        &self,
+        agent: &str,
+        device_code_id: i32,
+        session_id: &str,
+    ) -> DeviceCodeResult<()> {
+        let now = Utc::now().naive_utc();
+        model::device_code::Entity::update_many()
+            .col_expr(
+                model::device_code::Column::SessionId,
+                Expr::value(session_id),
+            )
+            .col_expr(model::device_code::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::device_code::Column::UpdatedAt, Expr::value(now))
+            .filter(model::device_code::Column::DeviceCodeId.eq(device_code_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
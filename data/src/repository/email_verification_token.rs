@@ -0,0 +1,175 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{email_verification_token::EmailVerificationTokenDto, error::DtoError},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum EmailVerificationTokenError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error(
+        "No email verification token was found with email_verification_token_id={email_verification_token_id}"
+    )]
+    TokenNotFound { email_verification_token_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for EmailVerificationTokenError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type EmailVerificationTokenResult<T> = Result<T, EmailVerificationTokenError>;
+
+#[derive(Clone, Debug)]
+pub struct EmailVerificationTokenRepository {
+    conn: DatabaseConnection,
+}
+impl EmailVerificationTokenRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.email_verification_token.by_id")]
+    pub async fn by_id(
+        &self,
+        email_verification_token_id: i32,
+    ) -> EmailVerificationTokenResult<Option<EmailVerificationTokenDto>> {
+        let Some(token) =
+            model::email_verification_token::Entity::find_by_id(email_verification_token_id)
+                .one(&self.conn)
+                .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(EmailVerificationTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.email_verification_token.by_hash", skip(token_hash))]
+    pub async fn by_hash(
+        &self,
+        token_hash: &str,
+    ) -> EmailVerificationTokenResult<Option<EmailVerificationTokenDto>> {
+        let Some(token) = model::email_verification_token::Entity::find()
+            .filter(model::email_verification_token::Column::TokenHash.eq(token_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(EmailVerificationTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.email_verification_token.create", skip(token_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        email: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> EmailVerificationTokenResult<EmailVerificationTokenDto> {
+        let it = model::email_verification_token::Entity::insert(
+            model::email_verification_token::ActiveModel {
+                user_id: Set(user_id),
+                email: Set(email.into()),
+                token_hash: Set(token_hash.into()),
+                expires_at: Set(expires_at.naive_utc()),
+                used_at: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                updated_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            },
+        )
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(EmailVerificationTokenError::TokenNotFound {
+                email_verification_token_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks a token as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.email_verification_token.consume")]
+    pub async fn consume(
+        &self,
+        agent: &str,
+        email_verification_token_id: i32,
+    ) -> EmailVerificationTokenResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::email_verification_token::Entity::update_many()
+            .col_expr(
+                model::email_verification_token::Column::UsedAt,
+                Expr::value(now),
+            )
+            .col_expr(
+                model::email_verification_token::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::email_verification_token::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::email_verification_token::Column::EmailVerificationTokenId
+                    .eq(email_verification_token_id),
+            )
+            .filter(model::email_verification_token::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Uses up every outstanding token belonging to a user, returning how many there were
+    #[tracing::instrument(level = Level::DEBUG, "data.email_verification_token.invalidate_user")]
+    pub async fn invalidate_user(
+        &self,
+        agent: &str,
+        user_id: i32,
+    ) -> EmailVerificationTokenResult<u64> {
+        let now = Utc::now().naive_utc();
+        let it = model::email_verification_token::Entity::update_many()
+            .col_expr(
+                model::email_verification_token::Column::UsedAt,
+                Expr::value(now),
+            )
+            .col_expr(
+                model::email_verification_token::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::email_verification_token::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(model::email_verification_token::Column::UserId.eq(user_id))
+            .filter(model::email_verification_token::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected)
+    }
+}
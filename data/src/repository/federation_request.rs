@@ -0,0 +1,142 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, federation_request::FederationRequestDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum FederationRequestError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No federation request was found with federation_request_id={federation_request_id}")]
+    RequestNotFound { federation_request_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for FederationRequestError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type FederationRequestResult<T> = Result<T, FederationRequestError>;
+
+#[derive(Clone, Debug)]
+pub struct FederationRequestRepository {
+    conn: DatabaseConnection,
+}
+impl FederationRequestRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.federation_request.by_id")]
+    pub async fn by_id(
+        &self,
+        federation_request_id: i32,
+    ) -> FederationRequestResult<Option<FederationRequestDto>> {
+        let Some(request) = model::federation_request::Entity::find_by_id(federation_request_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(FederationRequestDto::try_from(request)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.federation_request.by_state_hash", skip(state_hash))]
+    pub async fn by_state_hash(
+        &self,
+        state_hash: &str,
+    ) -> FederationRequestResult<Option<FederationRequestDto>> {
+        let Some(request) = model::federation_request::Entity::find()
+            .filter(model::federation_request::Column::StateHash.eq(state_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(FederationRequestDto::try_from(request)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.federation_request.create", skip(state_hash, nonce, code_verifier))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        state_hash: &str,
+        provider_id: &str,
+        nonce: &str,
+        code_verifier: &str,
+        user_id: Option<i32>,
+        expires_at: DateTime<Utc>,
+    ) -> FederationRequestResult<FederationRequestDto> {
+        let it =
+            model::federation_request::Entity::insert(model::federation_request::ActiveModel {
+                state_hash: Set(state_hash.into()),
+                provider_id: Set(provider_id.into()),
+                nonce: Set(nonce.into()),
+                code_verifier: Set(code_verifier.into()),
+                user_id: Set(user_id),
+                expires_at: Set(expires_at.naive_utc()),
+                used_at: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                updated_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .exec(&self.conn)
+            .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(FederationRequestError::RequestNotFound {
+                federation_request_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks a request as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.federation_request.consume")]
+    pub async fn consume(
+        &self,
+        agent: &str,
+        federation_request_id: i32,
+    ) -> FederationRequestResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::federation_request::Entity::update_many()
+            .col_expr(model::federation_request::Column::UsedAt, Expr::value(now))
+            .col_expr(
+                model::federation_request::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::federation_request::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::federation_request::Column::FederationRequestId.eq(federation_request_id),
+            )
+            .filter(model::federation_request::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::{HashMap, HashSet};
+
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, DatabaseConnection, EntityTrait, IntoActiveModel,
-    TransactionTrait, sqlx::types::chrono::Utc,
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, IntoActiveModel, IsolationLevel, QueryFilter, TransactionTrait,
+    sea_query::OnConflict, sqlx::types::chrono::Utc,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -12,9 +15,10 @@ use crate::{
         application::ApplicationDto,
         error::DtoError,
         grant::{GrantDetailDto, GrantDto},
+        grant_implication::{GrantImplicationDetailDto, GrantImplicationDto},
     },
     model,
-    repository::error::RepositoryError,
+    repository::{error::RepositoryError, reachable},
 };
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
@@ -30,6 +34,11 @@ pub enum GrantError {
     ApplicationNotFound { application_id: String },
     #[error("No grant was found with id={grant_id}")]
     GrantNotFound { grant_id: String },
+    #[error("Grant {implied_grant_id} already implies grant {grant_id}")]
+    Cycle {
+        grant_id: String,
+        implied_grant_id: String,
+    },
 }
 impl<E: Into<RepositoryError>> From<E> for GrantError {
     fn from(value: E) -> Self {
@@ -75,6 +84,20 @@ impl GrantRepository {
         Ok(grants)
     }
 
+    /// Every grant belonging to an application other than `application_id`
+    #[tracing::instrument(level = Level::DEBUG, "data.grant.outside_application")]
+    pub async fn outside_application(&self, application_id: &str) -> GrantResult<Vec<GrantDto>> {
+        let them = model::grant::Entity::find()
+            .filter(model::grant::Column::ApplicationId.ne(application_id))
+            .all(&self.conn)
+            .await?;
+
+        Ok(them
+            .into_iter()
+            .map(GrantDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
     #[tracing::instrument(level = Level::DEBUG, "data.grant.by_id")]
     pub async fn by_id(&self, grant_id: &str) -> GrantResult<Option<GrantDetailDto>> {
         let it = model::grant::Entity::find_by_id(grant_id)
@@ -143,4 +166,147 @@ impl GrantRepository {
             .await?
             .ok_or(GrantError::GrantNotFound { grant_id })
     }
+
+    /// Pairs each implication with the grant it implies
+    async fn implication_details<C: ConnectionTrait>(
+        conn: &C,
+        implications: Vec<model::grant_implication::Model>,
+    ) -> GrantResult<Vec<GrantImplicationDetailDto>> {
+        let implied_ids = implications
+            .iter()
+            .map(|implication| implication.implied_grant_id.clone())
+            .collect::<HashSet<_>>();
+        let grants = model::grant::Entity::find()
+            .filter(model::grant::Column::GrantId.is_in(implied_ids))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|grant| (grant.grant_id.clone(), grant))
+            .collect::<HashMap<_, _>>();
+
+        let mut details = Vec::with_capacity(implications.len());
+        for implication in implications {
+            let Some(implied) = grants.get(&implication.implied_grant_id) else {
+                continue;
+            };
+
+            details.push(GrantImplicationDetailDto {
+                implication: GrantImplicationDto::try_from(implication)?,
+                implied: GrantDto::try_from(implied.clone())?,
+            });
+        }
+
+        Ok(details)
+    }
+
+    /// Every implication, which together form the graph tokens are expanded with
+    #[tracing::instrument(level = Level::DEBUG, "data.grant.implications")]
+    pub async fn implications(&self) -> GrantResult<Vec<GrantImplicationDetailDto>> {
+        let them = model::grant_implication::Entity::find()
+            .all(&self.conn)
+            .await?;
+
+        Self::implication_details(&self.conn, them).await
+    }
+
+    /// The grants `grant_id` implies directly
+    #[tracing::instrument(level = Level::DEBUG, "data.grant.implications_of")]
+    pub async fn implications_of(
+        &self,
+        grant_id: &str,
+    ) -> GrantResult<Vec<GrantImplicationDetailDto>> {
+        model::grant::Entity::find_by_id(grant_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(GrantError::GrantNotFound {
+                grant_id: grant_id.into(),
+            })?;
+
+        let them = model::grant_implication::Entity::find()
+            .filter(model::grant_implication::Column::GrantId.eq(grant_id))
+            .all(&self.conn)
+            .await?;
+
+        Self::implication_details(&self.conn, them).await
+    }
+
+    /// Makes `grant_id` imply `implied_grant_id` or stops it doing so. Refused where
+    /// `implied_grant_id` already implies `grant_id`, however indirectly.
+    #[tracing::instrument(level = Level::DEBUG, "data.grant.update_implication")]
+    pub async fn update_implication(
+        &self,
+        agent: &str,
+        grant_id: &str,
+        implied_grant_id: &str,
+        enabled: bool,
+    ) -> GrantResult<()> {
+        // Serializable so the implications read by the cycle check stay locked until the new
+        // one is in, otherwise two concurrent changes could each close half of a cycle
+        let txn = self
+            .conn
+            .begin_with_config(Some(IsolationLevel::Serializable), None)
+            .await?;
+
+        let mut grant = model::grant::Entity::find_by_id(grant_id)
+            .one(&txn)
+            .await?
+            .ok_or(GrantError::GrantNotFound {
+                grant_id: grant_id.into(),
+            })?
+            .into_active_model();
+        model::grant::Entity::find_by_id(implied_grant_id)
+            .one(&txn)
+            .await?
+            .ok_or(GrantError::GrantNotFound {
+                grant_id: implied_grant_id.into(),
+            })?;
+
+        if enabled {
+            let implied = reachable::<_, model::grant_implication::Entity>(
+                &txn,
+                model::grant_implication::Column::GrantId,
+                model::grant_implication::Column::ImpliedGrantId,
+                vec![implied_grant_id.into()],
+            )
+            .await?;
+            if implied.contains(grant_id) {
+                return Err(GrantError::Cycle {
+                    grant_id: grant_id.into(),
+                    implied_grant_id: implied_grant_id.into(),
+                });
+            }
+
+            let on_conflict = OnConflict::columns([
+                model::grant_implication::Column::GrantId,
+                model::grant_implication::Column::ImpliedGrantId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::grant_implication::Entity::insert(model::grant_implication::ActiveModel {
+                grant_id: Set(grant_id.into()),
+                implied_grant_id: Set(implied_grant_id.into()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::grant_implication::Entity::delete_many()
+                .filter(model::grant_implication::Column::GrantId.eq(grant_id))
+                .filter(model::grant_implication::Column::ImpliedGrantId.eq(implied_grant_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        grant.updated_by = Set(agent.into());
+        grant.updated_at = Set(Utc::now().naive_utc());
+        grant.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
 }
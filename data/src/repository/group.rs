@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, IntoActiveModel, IsolationLevel, QueryFilter, QuerySelect, TransactionTrait,
+    sea_query::OnConflict, sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{
+        error::DtoError,
+        grant::GrantDto,
+        group::{GroupDetailDto, GroupDto},
+        group_member::GroupMemberDto,
+    },
+    model,
+    repository::{error::RepositoryError, reachable, role::RoleRepository},
+    util::IntoActiveValueExt,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum GroupError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No group was found with id={group_id}")]
+    GroupNotFound { group_id: String },
+    #[error("No grant was found with id={grant_id}")]
+    GrantNotFound { grant_id: String },
+    #[error("No role was found with id={role_id}")]
+    RoleNotFound { role_id: String },
+    #[error("No user was found with user_id={user_id}")]
+    UserNotFound { user_id: i32 },
+    #[error("Group {member_group_id} already contains group {group_id}")]
+    Cycle {
+        group_id: String,
+        member_group_id: String,
+    },
+    #[error("Called update with no changes")]
+    NoChangeRequested,
+}
+impl<E: Into<RepositoryError>> From<E> for GroupError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type GroupResult<T> = Result<T, GroupError>;
+
+#[derive(Clone, Debug)]
+pub struct GroupRepository {
+    conn: DatabaseConnection,
+}
+impl GroupRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Loads the grants and roles of each group, generic over the error so the user repository
+    /// can share it
+    pub(crate) async fn details<C: ConnectionTrait, E: From<DbErr> + From<DtoError>>(
+        conn: &C,
+        groups: Vec<model::group::Model>,
+    ) -> Result<Vec<GroupDetailDto>, E> {
+        let group_ids = groups
+            .iter()
+            .map(|group| group.group_id.clone())
+            .collect::<Vec<_>>();
+
+        let them = model::group_grant::Entity::find()
+            .find_also_related(model::grant::Entity)
+            .filter(model::group_grant::Column::GroupId.is_in(group_ids.clone()))
+            .all(conn)
+            .await?;
+        let mut grants: HashMap<String, Vec<GrantDto>> = HashMap::new();
+        for (group_grant, grant) in them {
+            let Some(grant) = grant else {
+                continue;
+            };
+
+            grants
+                .entry(group_grant.group_id)
+                .or_default()
+                .push(GrantDto::try_from(grant)?);
+        }
+
+        let them = model::group_role::Entity::find()
+            .find_also_related(model::role::Entity)
+            .filter(model::group_role::Column::GroupId.is_in(group_ids))
+            .all(conn)
+            .await?;
+        let (group_roles, roles): (Vec<_>, Vec<_>) = them
+            .into_iter()
+            .filter_map(|(group_role, role)| role.map(|role| (group_role.group_id, role)))
+            .unzip();
+        let mut roles_by_group: HashMap<String, Vec<_>> = HashMap::new();
+        for (group_id, role) in group_roles
+            .into_iter()
+            .zip(RoleRepository::details::<C, E>(conn, roles).await?)
+        {
+            roles_by_group.entry(group_id).or_default().push(role);
+        }
+
+        let mut details = Vec::with_capacity(groups.len());
+        for group in groups {
+            details.push(GroupDetailDto {
+                grants: grants.remove(&group.group_id).unwrap_or_default(),
+                roles: roles_by_group.remove(&group.group_id).unwrap_or_default(),
+                group: GroupDto::try_from(group)?,
+            });
+        }
+
+        Ok(details)
+    }
+
+    /// `group_ids` and every group they are nested in, however deep
+    async fn ancestors<C: ConnectionTrait>(
+        conn: &C,
+        group_ids: Vec<String>,
+    ) -> Result<HashSet<String>, DbErr> {
+        reachable::<_, model::group_member::Entity>(
+            conn,
+            model::group_member::Column::MemberGroupId,
+            model::group_member::Column::GroupId,
+            group_ids,
+        )
+        .await
+    }
+
+    /// Every group the user belongs to, directly or through a nested group
+    pub(crate) async fn resolved<C: ConnectionTrait, E: From<DbErr> + From<DtoError>>(
+        conn: &C,
+        user_id: i32,
+    ) -> Result<Vec<GroupDetailDto>, E> {
+        let direct: Vec<String> = model::group_member::Entity::find()
+            .select_only()
+            .column(model::group_member::Column::GroupId)
+            .filter(model::group_member::Column::UserId.eq(user_id))
+            .into_tuple()
+            .all(conn)
+            .await?;
+        if direct.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let group_ids = Self::ancestors(conn, direct).await?;
+        let groups = model::group::Entity::find()
+            .filter(model::group::Column::GroupId.is_in(group_ids))
+            .all(conn)
+            .await?;
+
+        Self::details(conn, groups).await
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.group.by_id")]
+    pub async fn by_id(&self, group_id: &str) -> GroupResult<Option<GroupDetailDto>> {
+        let Some(group) = model::group::Entity::find_by_id(group_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Self::details::<_, GroupError>(&self.conn, vec![group])
+            .await?
+            .pop())
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.group.list")]
+    pub async fn list(&self) -> GroupResult<Vec<GroupDto>> {
+        Ok(model::group::Entity::find()
+            .all(&self.conn)
+            .await?
+            .into_iter()
+            .map(GroupDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// The group's direct members, users and groups alike
+    #[tracing::instrument(level = Level::DEBUG, "data.group.members")]
+    pub async fn members(&self, group_id: &str) -> GroupResult<Vec<GroupMemberDto>> {
+        Ok(model::group_member::Entity::find()
+            .filter(model::group_member::Column::GroupId.eq(group_id))
+            .all(&self.conn)
+            .await?
+            .into_iter()
+            .map(GroupMemberDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.group.create")]
+    pub async fn create(
+        &self,
+        agent: &str,
+        group_id: &str,
+        display_name: &str,
+        description: &str,
+    ) -> GroupResult<GroupDetailDto> {
+        model::group::Entity::insert(model::group::ActiveModel {
+            group_id: Set(group_id.into()),
+            display_name: Set(display_name.into()),
+            description: Set(description.into()),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(group_id)
+            .await?
+            .ok_or(GroupError::GroupNotFound {
+                group_id: group_id.into(),
+            })
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.group.update")]
+    pub async fn update(
+        &self,
+        agent: &str,
+        group_id: &str,
+        display_name: Option<&str>,
+        description: Option<&str>,
+    ) -> GroupResult<GroupDetailDto> {
+        if display_name.is_none() && description.is_none() {
+            return Err(GroupError::NoChangeRequested);
+        }
+
+        let mut group = model::group::Entity::find_by_id(group_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(GroupError::GroupNotFound {
+                group_id: group_id.into(),
+            })?
+            .into_active_model();
+
+        group.display_name = display_name.into_active_value_ext();
+        group.description = description.into_active_value_ext();
+        group.updated_by = Set(agent.into());
+        group.updated_at = Set(Utc::now().naive_utc());
+
+        group.update(&self.conn).await?;
+
+        self.by_id(group_id)
+            .await?
+            .ok_or(GroupError::GroupNotFound {
+                group_id: group_id.into(),
+            })
+    }
+
+    /// Deleting a group takes what it gave away from its members, nested groups included
+    #[tracing::instrument(level = Level::DEBUG, "data.group.delete")]
+    pub async fn delete(&self, group_id: &str) -> GroupResult<bool> {
+        let it = model::group::Entity::delete_by_id(group_id)
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    async fn touch<C: ConnectionTrait>(
+        conn: &C,
+        agent: &str,
+        group_id: &str,
+    ) -> GroupResult<model::group::Model> {
+        let mut group = model::group::Entity::find_by_id(group_id)
+            .one(conn)
+            .await?
+            .ok_or(GroupError::GroupNotFound {
+                group_id: group_id.into(),
+            })?
+            .into_active_model();
+
+        group.updated_by = Set(agent.into());
+        group.updated_at = Set(Utc::now().naive_utc());
+
+        Ok(group.update(conn).await?)
+    }
+
+    /// Gives the group's members a grant, of any application, or takes it away
+    #[tracing::instrument(level = Level::DEBUG, "data.group.update_grant")]
+    pub async fn update_grant(
+        &self,
+        agent: &str,
+        group_id: &str,
+        grant_id: &str,
+        enabled: bool,
+    ) -> GroupResult<()> {
+        let txn = self.conn.begin().await?;
+
+        Self::touch(&txn, agent, group_id).await?;
+        model::grant::Entity::find_by_id(grant_id)
+            .one(&txn)
+            .await?
+            .ok_or(GroupError::GrantNotFound {
+                grant_id: grant_id.into(),
+            })?;
+
+        if enabled {
+            let on_conflict = OnConflict::columns([
+                model::group_grant::Column::GroupId,
+                model::group_grant::Column::GrantId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::group_grant::Entity::insert(model::group_grant::ActiveModel {
+                group_id: Set(group_id.into()),
+                grant_id: Set(grant_id.into()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::group_grant::Entity::delete_many()
+                .filter(model::group_grant::Column::GroupId.eq(group_id))
+                .filter(model::group_grant::Column::GrantId.eq(grant_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Gives the group's members a role or takes it away
+    #[tracing::instrument(level = Level::DEBUG, "data.group.update_role")]
+    pub async fn update_role(
+        &self,
+        agent: &str,
+        group_id: &str,
+        role_id: &str,
+        enabled: bool,
+    ) -> GroupResult<()> {
+        let txn = self.conn.begin().await?;
+
+        Self::touch(&txn, agent, group_id).await?;
+        model::role::Entity::find_by_id(role_id)
+            .one(&txn)
+            .await?
+            .ok_or(GroupError::RoleNotFound {
+                role_id: role_id.into(),
+            })?;
+
+        if enabled {
+            let on_conflict = OnConflict::columns([
+                model::group_role::Column::GroupId,
+                model::group_role::Column::RoleId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::group_role::Entity::insert(model::group_role::ActiveModel {
+                group_id: Set(group_id.into()),
+                role_id: Set(role_id.into()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::group_role::Entity::delete_many()
+                .filter(model::group_role::Column::GroupId.eq(group_id))
+                .filter(model::group_role::Column::RoleId.eq(role_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Adds the user to the group or removes them
+    #[tracing::instrument(level = Level::DEBUG, "data.group.update_user")]
+    pub async fn update_user(
+        &self,
+        agent: &str,
+        group_id: &str,
+        user_id: i32,
+        enabled: bool,
+    ) -> GroupResult<()> {
+        let txn = self.conn.begin().await?;
+
+        Self::touch(&txn, agent, group_id).await?;
+        model::user::Entity::find_by_id(user_id)
+            .one(&txn)
+            .await?
+            .ok_or(GroupError::UserNotFound { user_id })?;
+
+        if enabled {
+            let on_conflict = OnConflict::columns([
+                model::group_member::Column::GroupId,
+                model::group_member::Column::UserId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::group_member::Entity::insert(model::group_member::ActiveModel {
+                group_id: Set(group_id.into()),
+                user_id: Set(Some(user_id)),
+                member_group_id: Set(None),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::group_member::Entity::delete_many()
+                .filter(model::group_member::Column::GroupId.eq(group_id))
+                .filter(model::group_member::Column::UserId.eq(user_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Nests `member_group_id` in the group, its members then inherit the group's grants and
+    /// roles, or takes it out again. Refused when the group is already nested in the member.
+    #[tracing::instrument(level = Level::DEBUG, "data.group.update_member_group")]
+    pub async fn update_member_group(
+        &self,
+        agent: &str,
+        group_id: &str,
+        member_group_id: &str,
+        enabled: bool,
+    ) -> GroupResult<()> {
+        // Serializable so the memberships read by the cycle check stay locked until the new one
+        // is in, otherwise two concurrent changes could each close half of a cycle
+        let txn = self
+            .conn
+            .begin_with_config(Some(IsolationLevel::Serializable), None)
+            .await?;
+
+        Self::touch(&txn, agent, group_id).await?;
+        model::group::Entity::find_by_id(member_group_id)
+            .one(&txn)
+            .await?
+            .ok_or(GroupError::GroupNotFound {
+                group_id: member_group_id.into(),
+            })?;
+
+        if enabled {
+            if Self::ancestors(&txn, vec![group_id.into()])
+                .await?
+                .contains(member_group_id)
+            {
+                return Err(GroupError::Cycle {
+                    group_id: group_id.into(),
+                    member_group_id: member_group_id.into(),
+                });
+            }
+
+            let on_conflict = OnConflict::columns([
+                model::group_member::Column::GroupId,
+                model::group_member::Column::MemberGroupId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::group_member::Entity::insert(model::group_member::ActiveModel {
+                group_id: Set(group_id.into()),
+                user_id: Set(None),
+                member_group_id: Set(Some(member_group_id.into())),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::group_member::Entity::delete_many()
+                .filter(model::group_member::Column::GroupId.eq(group_id))
+                .filter(model::group_member::Column::MemberGroupId.eq(member_group_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, impersonation_event::ImpersonationEventDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum ImpersonationEventError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+}
+impl<E: Into<RepositoryError>> From<E> for ImpersonationEventError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type ImpersonationEventResult<T> = Result<T, ImpersonationEventError>;
+
+#[derive(Clone, Debug)]
+pub struct ImpersonationEventRepository {
+    conn: DatabaseConnection,
+}
+impl ImpersonationEventRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.impersonation_event.create")]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        actor_user_id: i32,
+        actor_username: &str,
+        session_id: &str,
+        reason: &str,
+        expires_at: DateTime<Utc>,
+        source_ip: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> ImpersonationEventResult<()> {
+        model::impersonation_event::Entity::insert(model::impersonation_event::ActiveModel {
+            user_id: Set(user_id),
+            actor_user_id: Set(Some(actor_user_id)),
+            actor_username: Set(actor_username.into()),
+            session_id: Set(session_id.into()),
+            reason: Set(reason.into()),
+            expires_at: Set(expires_at.naive_utc()),
+            source_ip: Set(source_ip.map(str::to_string)),
+            user_agent: Set(user_agent.map(str::to_string)),
+            created_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Times `user_id` was impersonated, most recent first
+    #[tracing::instrument(level = Level::DEBUG, "data.impersonation_event.by_user")]
+    pub async fn by_user(
+        &self,
+        user_id: i32,
+        limit: u64,
+    ) -> ImpersonationEventResult<Vec<ImpersonationEventDto>> {
+        let them = model::impersonation_event::Entity::find()
+            .filter(model::impersonation_event::Column::UserId.eq(user_id))
+            .order_by_desc(model::impersonation_event::Column::CreatedAt)
+            .limit(limit)
+            .all(&self.conn)
+            .await?;
+
+        Ok(them
+            .into_iter()
+            .map(ImpersonationEventDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm::{
+    ActiveValue::Set, DatabaseConnection, EntityTrait, QueryOrder, sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, key_promotion::KeyPromotionDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum KeyPromotionError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("Key promotion {key_promotion_id} not found")]
+    PromotionNotFound { key_promotion_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for KeyPromotionError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type KeyPromotionResult<T> = Result<T, KeyPromotionError>;
+
+#[derive(Clone, Debug)]
+pub struct KeyPromotionRepository {
+    conn: DatabaseConnection,
+}
+impl KeyPromotionRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.key_promotion.create")]
+    pub async fn create(&self, agent: &str, kid: &str) -> KeyPromotionResult<KeyPromotionDto> {
+        let it = model::key_promotion::Entity::insert(model::key_promotion::ActiveModel {
+            kid: Set(kid.into()),
+            created_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        let Some(promotion) = model::key_promotion::Entity::find_by_id(it.last_insert_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Err(KeyPromotionError::PromotionNotFound {
+                key_promotion_id: it.last_insert_id,
+            });
+        };
+
+        Ok(KeyPromotionDto::try_from(promotion)?)
+    }
+
+    /// The most recent promotion, naming the key every instance should sign with
+    #[tracing::instrument(level = Level::DEBUG, "data.key_promotion.latest")]
+    pub async fn latest(&self) -> KeyPromotionResult<Option<KeyPromotionDto>> {
+        let Some(promotion) = model::key_promotion::Entity::find()
+            .order_by_desc(model::key_promotion::Column::CreatedAt)
+            .order_by_desc(model::key_promotion::Column::KeyPromotionId)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(KeyPromotionDto::try_from(promotion)?))
+    }
+}
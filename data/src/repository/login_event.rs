@@ -0,0 +1,103 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, login_event::LoginEventDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum LoginEventError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+}
+impl<E: Into<RepositoryError>> From<E> for LoginEventError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type LoginEventResult<T> = Result<T, LoginEventError>;
+
+#[derive(Clone, Debug)]
+pub struct LoginEventRepository {
+    conn: DatabaseConnection,
+}
+impl LoginEventRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.login_event.create")]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: Option<i32>,
+        username: &str,
+        outcome: &str,
+        source_ip: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> LoginEventResult<()> {
+        model::login_event::Entity::insert(model::login_event::ActiveModel {
+            user_id: Set(user_id),
+            username: Set(username.into()),
+            outcome: Set(outcome.into()),
+            source_ip: Set(source_ip.map(str::to_string)),
+            user_agent: Set(user_agent.map(str::to_string)),
+            created_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Most recent first
+    #[tracing::instrument(level = Level::DEBUG, "data.login_event.by_user")]
+    pub async fn by_user(
+        &self,
+        user_id: i32,
+        outcome: Option<&str>,
+        from: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: u64,
+    ) -> LoginEventResult<Vec<LoginEventDto>> {
+        let conditions = Condition::all()
+            .add_option(outcome.map(|outcome| model::login_event::Column::Outcome.eq(outcome)))
+            .add_option(
+                from.map(|from| model::login_event::Column::CreatedAt.gte(from.naive_utc())),
+            )
+            .add_option(
+                until.map(|until| model::login_event::Column::CreatedAt.lt(until.naive_utc())),
+            );
+
+        let them = model::login_event::Entity::find()
+            .filter(model::login_event::Column::UserId.eq(user_id))
+            .filter(conditions)
+            .order_by_desc(model::login_event::Column::CreatedAt)
+            .limit(limit)
+            .all(&self.conn)
+            .await?;
+
+        Ok(them
+            .into_iter()
+            .map(LoginEventDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
@@ -0,0 +1,128 @@
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, TransactionTrait,
+    sea_query::{Expr, ExprTrait, OnConflict},
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, login_throttle::LoginThrottleDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum LoginThrottleError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+}
+impl<E: Into<RepositoryError>> From<E> for LoginThrottleError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type LoginThrottleResult<T> = Result<T, LoginThrottleError>;
+
+#[derive(Clone, Debug)]
+pub struct LoginThrottleRepository {
+    conn: DatabaseConnection,
+}
+impl LoginThrottleRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.login_throttle.by_key")]
+    pub async fn by_key(
+        &self,
+        throttle_key: &str,
+    ) -> LoginThrottleResult<Option<LoginThrottleDto>> {
+        let Some(throttle) = model::login_throttle::Entity::find_by_id(throttle_key)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(LoginThrottleDto::try_from(throttle)?))
+    }
+
+    /// Counts a failure against `throttle_key` and locks it for as long as `lockout_for` asks.
+    ///
+    /// The increment happens in the database and the row stays locked until the lockout is
+    /// written, so concurrent failures each see their own count. A counter whose last failure
+    /// is older than `forget_before` starts over.
+    #[tracing::instrument(level = Level::DEBUG, "data.login_throttle.record_failure", skip(lockout_for))]
+    pub async fn record_failure(
+        &self,
+        throttle_key: &str,
+        forget_before: DateTime<Utc>,
+        lockout_for: impl FnOnce(i32) -> Option<DateTime<Utc>>,
+    ) -> LoginThrottleResult<LoginThrottleDto> {
+        let txn = self.conn.begin().await?;
+
+        let now = Utc::now().naive_utc();
+        // MySQL applies these in order, so the condition still sees the previous failure time
+        let on_conflict = OnConflict::column(model::login_throttle::Column::ThrottleKey)
+            .value(
+                model::login_throttle::Column::Failures,
+                Expr::case(
+                    Expr::col(model::login_throttle::Column::LastFailureAt)
+                        .lt(forget_before.naive_utc()),
+                    1,
+                )
+                .finally(Expr::col(model::login_throttle::Column::Failures).add(1)),
+            )
+            .update_columns([
+                model::login_throttle::Column::LastFailureAt,
+                model::login_throttle::Column::UpdatedAt,
+            ])
+            .to_owned();
+
+        model::login_throttle::Entity::insert(model::login_throttle::ActiveModel {
+            throttle_key: Set(throttle_key.into()),
+            failures: Set(1),
+            locked_until: Set(None),
+            last_failure_at: Set(now),
+            updated_at: Set(now),
+        })
+        .on_conflict(on_conflict)
+        .exec(&txn)
+        .await?;
+
+        let throttle = model::login_throttle::Entity::find_by_id(throttle_key)
+            .one(&txn)
+            .await?
+            .ok_or(DbErr::RecordNotFound(throttle_key.into()))?;
+
+        let locked_until = lockout_for(throttle.failures).map(|dt| dt.naive_utc());
+        let mut throttle = throttle.into_active_model();
+        throttle.locked_until = Set(locked_until);
+        let throttle = throttle.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(LoginThrottleDto::try_from(throttle)?)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.login_throttle.delete")]
+    pub async fn delete(&self, throttle_key: &str) -> LoginThrottleResult<()> {
+        model::login_throttle::Entity::delete_by_id(throttle_key)
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, mfa_challenge::MfaChallengeDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum MfaChallengeError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No MFA challenge was found with mfa_challenge_id={mfa_challenge_id}")]
+    ChallengeNotFound { mfa_challenge_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for MfaChallengeError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type MfaChallengeResult<T> = Result<T, MfaChallengeError>;
+
+#[derive(Clone, Debug)]
+pub struct MfaChallengeRepository {
+    conn: DatabaseConnection,
+}
+impl MfaChallengeRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.mfa_challenge.by_id")]
+    pub async fn by_id(
+        &self,
+        mfa_challenge_id: i32,
+    ) -> MfaChallengeResult<Option<MfaChallengeDto>> {
+        let Some(challenge) = model::mfa_challenge::Entity::find_by_id(mfa_challenge_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(MfaChallengeDto::try_from(challenge)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.mfa_challenge.by_hash", skip(challenge_hash))]
+    pub async fn by_hash(
+        &self,
+        challenge_hash: &str,
+    ) -> MfaChallengeResult<Option<MfaChallengeDto>> {
+        let Some(challenge) = model::mfa_challenge::Entity::find()
+            .filter(model::mfa_challenge::Column::ChallengeHash.eq(challenge_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(MfaChallengeDto::try_from(challenge)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.mfa_challenge.create", skip(challenge_hash, new_password_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        challenge_hash: &str,
+        expires_at: DateTime<Utc>,
+        new_password_hash: Option<&str>,
+    ) -> MfaChallengeResult<MfaChallengeDto> {
+        let it = model::mfa_challenge::Entity::insert(model::mfa_challenge::ActiveModel {
+            user_id: Set(user_id),
+            challenge_hash: Set(challenge_hash.into()),
+            expires_at: Set(expires_at.naive_utc()),
+            used_at: Set(None),
+            new_password_hash: Set(new_password_hash.map(Into::into)),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(MfaChallengeError::ChallengeNotFound {
+                mfa_challenge_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks a challenge as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.mfa_challenge.consume")]
+    pub async fn consume(&self, agent: &str, mfa_challenge_id: i32) -> MfaChallengeResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::mfa_challenge::Entity::update_many()
+            .col_expr(model::mfa_challenge::Column::UsedAt, Expr::value(now))
+            .col_expr(model::mfa_challenge::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::mfa_challenge::Column::UpdatedAt, Expr::value(now))
+            .filter(model::mfa_challenge::Column::MfaChallengeId.eq(mfa_challenge_id))
+            .filter(model::mfa_challenge::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+}
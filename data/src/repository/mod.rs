@@ -1,14 +1,68 @@
-use sea_orm::{Database, DatabaseConnection};
+use std::collections::HashSet;
+
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, Database, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QuerySelect,
+};
 
 use crate::repository::error::RepositoryError;
 
 pub mod application;
+pub mod authorization_code;
+pub mod consent;
+pub mod device_code;
+pub mod email_verification_token;
 pub mod error;
+pub mod federation_request;
 pub mod grant;
+pub mod group;
+pub mod impersonation_event;
+pub mod key_promotion;
+pub mod login_event;
+pub mod login_throttle;
+pub mod mfa_challenge;
+pub mod password_reset_token;
+pub mod personal_access_token;
+pub mod recovery_code;
+pub mod refresh_token;
+pub mod role;
+pub mod session;
 pub mod user;
+pub mod user_identity;
+pub mod user_totp;
 
 pub async fn connect(connection_string: &str) -> Result<DatabaseConnection, RepositoryError> {
     Database::connect(connection_string)
         .await
         .map_err(RepositoryError::from)
 }
+
+/// `start` and every id reachable from it by following rows of `E` from their `from` column to
+/// their `to` column, however deep. The visited set keeps a cycle, which should never have been
+/// let in, from looping forever.
+pub(crate) async fn reachable<C: ConnectionTrait, E: EntityTrait>(
+    conn: &C,
+    from: E::Column,
+    to: E::Column,
+    start: Vec<String>,
+) -> Result<HashSet<String>, DbErr> {
+    let mut seen = start.iter().cloned().collect::<HashSet<_>>();
+    let mut frontier = start;
+
+    while !frontier.is_empty() {
+        let next: Vec<String> = E::find()
+            .select_only()
+            .column(to)
+            .filter(from.is_in(frontier))
+            .into_tuple()
+            .all(conn)
+            .await?;
+
+        frontier = next
+            .into_iter()
+            .filter(|id| seen.insert(id.clone()))
+            .collect();
+    }
+
+    Ok(seen)
+}
@@ -0,0 +1,189 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, password_reset_token::PasswordResetTokenDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum PasswordResetTokenError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error(
+        "No password reset token was found with password_reset_token_id={password_reset_token_id}"
+    )]
+    TokenNotFound { password_reset_token_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for PasswordResetTokenError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type PasswordResetTokenResult<T> = Result<T, PasswordResetTokenError>;
+
+#[derive(Clone, Debug)]
+pub struct PasswordResetTokenRepository {
+    conn: DatabaseConnection,
+}
+impl PasswordResetTokenRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.by_id")]
+    pub async fn by_id(
+        &self,
+        password_reset_token_id: i32,
+    ) -> PasswordResetTokenResult<Option<PasswordResetTokenDto>> {
+        let Some(token) = model::password_reset_token::Entity::find_by_id(password_reset_token_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(PasswordResetTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.by_hash", skip(token_hash))]
+    pub async fn by_hash(
+        &self,
+        token_hash: &str,
+    ) -> PasswordResetTokenResult<Option<PasswordResetTokenDto>> {
+        let Some(token) = model::password_reset_token::Entity::find()
+            .filter(model::password_reset_token::Column::TokenHash.eq(token_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(PasswordResetTokenDto::try_from(token)?))
+    }
+
+    /// The most recently issued token for a user, used or not
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.latest_for_user")]
+    pub async fn latest_for_user(
+        &self,
+        user_id: i32,
+    ) -> PasswordResetTokenResult<Option<PasswordResetTokenDto>> {
+        let Some(token) = model::password_reset_token::Entity::find()
+            .filter(model::password_reset_token::Column::UserId.eq(user_id))
+            .order_by_desc(model::password_reset_token::Column::CreatedAt)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(PasswordResetTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.create", skip(token_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> PasswordResetTokenResult<PasswordResetTokenDto> {
+        let it =
+            model::password_reset_token::Entity::insert(model::password_reset_token::ActiveModel {
+                user_id: Set(user_id),
+                token_hash: Set(token_hash.into()),
+                expires_at: Set(expires_at.naive_utc()),
+                used_at: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                updated_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .exec(&self.conn)
+            .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(PasswordResetTokenError::TokenNotFound {
+                password_reset_token_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks a token as used, returns false if it was already used
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.consume")]
+    pub async fn consume(
+        &self,
+        agent: &str,
+        password_reset_token_id: i32,
+    ) -> PasswordResetTokenResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::password_reset_token::Entity::update_many()
+            .col_expr(
+                model::password_reset_token::Column::UsedAt,
+                Expr::value(now),
+            )
+            .col_expr(
+                model::password_reset_token::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::password_reset_token::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::password_reset_token::Column::PasswordResetTokenId
+                    .eq(password_reset_token_id),
+            )
+            .filter(model::password_reset_token::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Uses up every outstanding token belonging to a user, returning how many there were
+    #[tracing::instrument(level = Level::DEBUG, "data.password_reset_token.invalidate_user")]
+    pub async fn invalidate_user(
+        &self,
+        agent: &str,
+        user_id: i32,
+    ) -> PasswordResetTokenResult<u64> {
+        let now = Utc::now().naive_utc();
+        let it = model::password_reset_token::Entity::update_many()
+            .col_expr(
+                model::password_reset_token::Column::UsedAt,
+                Expr::value(now),
+            )
+            .col_expr(
+                model::password_reset_token::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::password_reset_token::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(model::password_reset_token::Column::UserId.eq(user_id))
+            .filter(model::password_reset_token::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected)
+    }
+}
@@ -0,0 +1,187 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, personal_access_token::PersonalAccessTokenDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum PersonalAccessTokenError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error(
+        "No personal access token was found with personal_access_token_id={personal_access_token_id}"
+    )]
+    TokenNotFound { personal_access_token_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for PersonalAccessTokenError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type PersonalAccessTokenResult<T> = Result<T, PersonalAccessTokenError>;
+
+#[derive(Clone, Debug)]
+pub struct PersonalAccessTokenRepository {
+    conn: DatabaseConnection,
+}
+impl PersonalAccessTokenRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.by_id")]
+    pub async fn by_id(
+        &self,
+        personal_access_token_id: i32,
+    ) -> PersonalAccessTokenResult<Option<PersonalAccessTokenDto>> {
+        let Some(token) =
+            model::personal_access_token::Entity::find_by_id(personal_access_token_id)
+                .one(&self.conn)
+                .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(PersonalAccessTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.by_hash", skip(token_hash))]
+    pub async fn by_hash(
+        &self,
+        token_hash: &str,
+    ) -> PersonalAccessTokenResult<Option<PersonalAccessTokenDto>> {
+        let Some(token) = model::personal_access_token::Entity::find()
+            .filter(model::personal_access_token::Column::TokenHash.eq(token_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(PersonalAccessTokenDto::try_from(token)?))
+    }
+
+    /// Most recent first, revoked tokens included
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.by_user")]
+    pub async fn by_user(
+        &self,
+        user_id: i32,
+    ) -> PersonalAccessTokenResult<Vec<PersonalAccessTokenDto>> {
+        let them = model::personal_access_token::Entity::find()
+            .filter(model::personal_access_token::Column::UserId.eq(user_id))
+            .order_by_desc(model::personal_access_token::Column::CreatedAt)
+            .all(&self.conn)
+            .await?;
+
+        Ok(them
+            .into_iter()
+            .map(PersonalAccessTokenDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.create", skip(token_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        grants: &[String],
+        expires_at: Option<DateTime<Utc>>,
+    ) -> PersonalAccessTokenResult<PersonalAccessTokenDto> {
+        let it = model::personal_access_token::Entity::insert(
+            model::personal_access_token::ActiveModel {
+                user_id: Set(user_id),
+                name: Set(name.into()),
+                token_hash: Set(token_hash.into()),
+                grants: Set(grants.join(",")),
+                expires_at: Set(expires_at.map(|it| it.naive_utc())),
+                last_used_at: Set(None),
+                revoked_at: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                updated_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            },
+        )
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(PersonalAccessTokenError::TokenNotFound {
+                personal_access_token_id: it.last_insert_id,
+            })
+    }
+
+    /// Records that the token was just used, deliberately leaving the audit columns alone
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.touch")]
+    pub async fn touch(&self, personal_access_token_id: i32) -> PersonalAccessTokenResult<()> {
+        model::personal_access_token::Entity::update_many()
+            .col_expr(
+                model::personal_access_token::Column::LastUsedAt,
+                Expr::value(Utc::now().naive_utc()),
+            )
+            .filter(
+                model::personal_access_token::Column::PersonalAccessTokenId
+                    .eq(personal_access_token_id),
+            )
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Revokes one of `user_id`'s tokens, returns false if there was no such live token
+    #[tracing::instrument(level = Level::DEBUG, "data.personal_access_token.revoke")]
+    pub async fn revoke(
+        &self,
+        agent: &str,
+        user_id: i32,
+        personal_access_token_id: i32,
+    ) -> PersonalAccessTokenResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::personal_access_token::Entity::update_many()
+            .col_expr(
+                model::personal_access_token::Column::RevokedAt,
+                Expr::value(now),
+            )
+            .col_expr(
+                model::personal_access_token::Column::UpdatedBy,
+                Expr::value(agent),
+            )
+            .col_expr(
+                model::personal_access_token::Column::UpdatedAt,
+                Expr::value(now),
+            )
+            .filter(
+                model::personal_access_token::Column::PersonalAccessTokenId
+                    .eq(personal_access_token_id),
+            )
+            .filter(model::personal_access_token::Column::UserId.eq(user_id))
+            .filter(model::personal_access_token::Column::RevokedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+}
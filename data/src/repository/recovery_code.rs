@@ -0,0 +1,112 @@
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    TransactionTrait, sea_query::Expr, sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{model, repository::error::RepositoryError};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum RecoveryCodeError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+}
+impl<E: Into<RepositoryError>> From<E> for RecoveryCodeError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type RecoveryCodeResult<T> = Result<T, RecoveryCodeError>;
+
+#[derive(Clone, Debug)]
+pub struct RecoveryCodeRepository {
+    conn: DatabaseConnection,
+}
+impl RecoveryCodeRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Discards the user's existing codes, used or not, in favour of `code_hashes`
+    #[tracing::instrument(level = Level::DEBUG, "data.recovery_code.replace", skip(code_hashes))]
+    pub async fn replace(
+        &self,
+        agent: &str,
+        user_id: i32,
+        code_hashes: &[String],
+    ) -> RecoveryCodeResult<()> {
+        let txn = self.conn.begin().await?;
+
+        model::recovery_code::Entity::delete_many()
+            .filter(model::recovery_code::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+        model::recovery_code::Entity::insert_many(code_hashes.iter().map(|code_hash| {
+            model::recovery_code::ActiveModel {
+                user_id: Set(user_id),
+                code_hash: Set(code_hash.clone()),
+                used_at: Set(None),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            }
+        }))
+        .exec(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Marks a code as used, returns false if it doesn't exist, belongs to someone else, or was
+    /// already used
+    #[tracing::instrument(level = Level::DEBUG, "data.recovery_code.redeem", skip(code_hash))]
+    pub async fn redeem(
+        &self,
+        agent: &str,
+        user_id: i32,
+        code_hash: &str,
+    ) -> RecoveryCodeResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::recovery_code::Entity::update_many()
+            .col_expr(model::recovery_code::Column::UsedAt, Expr::value(now))
+            .col_expr(model::recovery_code::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::recovery_code::Column::UpdatedAt, Expr::value(now))
+            .filter(model::recovery_code::Column::UserId.eq(user_id))
+            .filter(model::recovery_code::Column::CodeHash.eq(code_hash))
+            .filter(model::recovery_code::Column::UsedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.recovery_code.remaining")]
+    pub async fn remaining(&self, user_id: i32) -> RecoveryCodeResult<u64> {
+        Ok(model::recovery_code::Entity::find()
+            .filter(model::recovery_code::Column::UserId.eq(user_id))
+            .filter(model::recovery_code::Column::UsedAt.is_null())
+            .count(&self.conn)
+            .await?)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.recovery_code.delete_user")]
+    pub async fn delete_user(&self, user_id: i32) -> RecoveryCodeResult<()> {
+        model::recovery_code::Entity::delete_many()
+            .filter(model::recovery_code::Column::UserId.eq(user_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use sea_orm::{
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, refresh_token::RefreshTokenDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum RefreshTokenError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No refresh token was found with refresh_token_id={refresh_token_id}")]
+    TokenNotFound { refresh_token_id: i32 },
+    #[error("Refresh token with refresh_token_id={refresh_token_id} was already used or revoked")]
+    TokenAlreadyUsed { refresh_token_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for RefreshTokenError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type RefreshTokenResult<T> = Result<T, RefreshTokenError>;
+
+#[derive(Clone, Debug)]
+pub struct RefreshTokenRepository {
+    conn: DatabaseConnection,
+}
+impl RefreshTokenRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.by_id")]
+    pub async fn by_id(
+        &self,
+        refresh_token_id: i32,
+    ) -> RefreshTokenResult<Option<RefreshTokenDto>> {
+        let Some(token) = model::refresh_token::Entity::find_by_id(refresh_token_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(RefreshTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.by_hash", skip(token_hash))]
+    pub async fn by_hash(&self, token_hash: &str) -> RefreshTokenResult<Option<RefreshTokenDto>> {
+        let Some(token) = model::refresh_token::Entity::find()
+            .filter(model::refresh_token::Column::TokenHash.eq(token_hash))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(RefreshTokenDto::try_from(token)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.create", skip(token_hash))]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        family_id: &str,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> RefreshTokenResult<RefreshTokenDto> {
+        let it = model::refresh_token::Entity::insert(model::refresh_token::ActiveModel {
+            user_id: Set(user_id),
+            family_id: Set(family_id.into()),
+            token_hash: Set(token_hash.into()),
+            expires_at: Set(expires_at.naive_utc()),
+            used_at: Set(None),
+            revoked_at: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(RefreshTokenError::TokenNotFound {
+                refresh_token_id: it.last_insert_id,
+            })
+    }
+
+    /// Marks `previous` as used and issues its successor in the same family.
+    ///
+    /// The "mark as used" update is conditional on the token still being live, so two
+    /// concurrent rotations of the same token can't both succeed; the loser gets
+    /// [`RefreshTokenError::TokenAlreadyUsed`] and should be treated as a replay.
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.rotate", skip(token_hash))]
+    pub async fn rotate(
+        &self,
+        agent: &str,
+        previous: &RefreshTokenDto,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> RefreshTokenResult<RefreshTokenDto> {
+        let txn = self.conn.begin().await?;
+
+        let now = Utc::now().naive_utc();
+        let updated = model::refresh_token::Entity::update_many()
+            .col_expr(model::refresh_token::Column::UsedAt, Expr::value(now))
+            .col_expr(model::refresh_token::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::refresh_token::Column::UpdatedAt, Expr::value(now))
+            .filter(model::refresh_token::Column::RefreshTokenId.eq(previous.refresh_token_id))
+            .filter(model::refresh_token::Column::UsedAt.is_null())
+            .filter(model::refresh_token::Column::RevokedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        if updated.rows_affected == 0 {
+            txn.rollback().await?;
+            return Err(RefreshTokenError::TokenAlreadyUsed {
+                refresh_token_id: previous.refresh_token_id,
+            });
+        }
+
+        let it = model::refresh_token::Entity::insert(model::refresh_token::ActiveModel {
+            user_id: Set(previous.user_id),
+            family_id: Set(previous.family_id.clone()),
+            token_hash: Set(token_hash.into()),
+            expires_at: Set(expires_at.naive_utc()),
+            used_at: Set(None),
+            revoked_at: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(now),
+            updated_at: Set(now),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        self.by_id(it.last_insert_id)
+            .await?
+            .ok_or(RefreshTokenError::TokenNotFound {
+                refresh_token_id: it.last_insert_id,
+            })
+    }
+
+    /// Revokes every live token in a family, returning how many were revoked
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.revoke_family")]
+    pub async fn revoke_family(&self, agent: &str, family_id: &str) -> RefreshTokenResult<u64> {
+        let now = Utc::now().naive_utc();
+        let it = model::refresh_token::Entity::update_many()
+            .col_expr(model::refresh_token::Column::RevokedAt, Expr::value(now))
+            .col_expr(model::refresh_token::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::refresh_token::Column::UpdatedAt, Expr::value(now))
+            .filter(model::refresh_token::Column::FamilyId.eq(family_id))
+            .filter(model::refresh_token::Column::RevokedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected)
+    }
+
+    /// Revokes every live token belonging to a user, other than those in the `except` family,
+    /// returning how many were revoked
+    #[tracing::instrument(level = Level::DEBUG, "data.refresh_token.revoke_user")]
+    pub async fn revoke_user(
+        &self,
+        agent: &str,
+        user_id: i32,
+        except: Option<&str>,
+    ) -> RefreshTokenResult<u64> {
+        let now = Utc::now().naive_utc();
+        let mut update = model::refresh_token::Entity::update_many()
+            .col_expr(model::refresh_token::Column::RevokedAt, Expr::value(now))
+            .col_expr(model::refresh_token::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::refresh_token::Column::UpdatedAt, Expr::value(now))
+            .filter(model::refresh_token::Column::UserId.eq(user_id))
+            .filter(model::refresh_token::Column::RevokedAt.is_null());
+        if let Some(except) = except {
+            update = update.filter(model::refresh_token::Column::FamilyId.ne(except));
+        }
+        let it = update.exec(&self.conn).await?;
+
+        Ok(it.rows_affected)
+    }
+}
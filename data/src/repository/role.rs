@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, IntoActiveModel, QueryFilter, TransactionTrait, sea_query::OnConflict,
+    sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{
+        error::DtoError,
+        grant::GrantDto,
+        role::{RoleDetailDto, RoleDto},
+        user_role::{UserRoleDetailDto, UserRoleDto},
+    },
+    model,
+    repository::error::RepositoryError,
+    util::IntoActiveValueExt,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum RoleError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No application was found with id={application_id}")]
+    ApplicationNotFound { application_id: String },
+    #[error("No role was found with id={role_id}")]
+    RoleNotFound { role_id: String },
+    #[error("No grant was found with id={grant_id}")]
+    GrantNotFound { grant_id: String },
+    #[error("No user was found with user_id={user_id}")]
+    UserNotFound { user_id: i32 },
+    #[error("Grant {grant_id} does not belong to application {application_id}")]
+    GrantOutsideApplication {
+        grant_id: String,
+        application_id: String,
+    },
+    #[error("Called update with no changes")]
+    NoChangeRequested,
+}
+impl<E: Into<RepositoryError>> From<E> for RoleError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type RoleResult<T> = Result<T, RoleError>;
+
+#[derive(Clone, Debug)]
+pub struct RoleRepository {
+    conn: DatabaseConnection,
+}
+impl RoleRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Loads the grants of each role, generic over the error so the user repository can share it
+    pub(crate) async fn details<C: ConnectionTrait, E: From<DbErr> + From<DtoError>>(
+        conn: &C,
+        roles: Vec<model::role::Model>,
+    ) -> Result<Vec<RoleDetailDto>, E> {
+        let role_ids = roles
+            .iter()
+            .map(|role| role.role_id.clone())
+            .collect::<Vec<_>>();
+        let them = model::role_grant::Entity::find()
+            .find_also_related(model::grant::Entity)
+            .filter(model::role_grant::Column::RoleId.is_in(role_ids))
+            .all(conn)
+            .await?;
+
+        let mut grants: HashMap<String, Vec<GrantDto>> = HashMap::new();
+        for (role_grant, grant) in them {
+            let Some(grant) = grant else {
+                continue;
+            };
+
+            grants
+                .entry(role_grant.role_id)
+                .or_default()
+                .push(GrantDto::try_from(grant)?);
+        }
+
+        let mut details = Vec::with_capacity(roles.len());
+        for role in roles {
+            let grants = grants.remove(&role.role_id).unwrap_or_default();
+            details.push(RoleDetailDto {
+                role: RoleDto::try_from(role)?,
+                grants,
+            });
+        }
+
+        Ok(details)
+    }
+
+    /// The roles assigned to a user, with their grants
+    pub(crate) async fn assigned<C: ConnectionTrait, E: From<DbErr> + From<DtoError>>(
+        conn: &C,
+        user_id: i32,
+    ) -> Result<Vec<UserRoleDetailDto>, E> {
+        let them = model::user_role::Entity::find()
+            .find_also_related(model::role::Entity)
+            .filter(model::user_role::Column::UserId.eq(user_id))
+            .all(conn)
+            .await?;
+
+        let mut user_roles = Vec::with_capacity(them.len());
+        let mut roles = Vec::with_capacity(them.len());
+        for (user_role, role) in them {
+            let Some(role) = role else {
+                continue;
+            };
+
+            user_roles.push(UserRoleDto::try_from(user_role)?);
+            roles.push(role);
+        }
+
+        let roles = Self::details::<C, E>(conn, roles).await?;
+
+        Ok(user_roles
+            .into_iter()
+            .zip(roles)
+            .map(|(user_role, role)| UserRoleDetailDto { user_role, role })
+            .collect())
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.role.by_id")]
+    pub async fn by_id(&self, role_id: &str) -> RoleResult<Option<RoleDetailDto>> {
+        let Some(role) = model::role::Entity::find_by_id(role_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Self::details::<_, RoleError>(&self.conn, vec![role])
+            .await?
+            .pop())
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.role.by_application")]
+    pub async fn by_application(&self, application_id: &str) -> RoleResult<Vec<RoleDetailDto>> {
+        model::application::Entity::find_by_id(application_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(RoleError::ApplicationNotFound {
+                application_id: application_id.into(),
+            })?;
+
+        let roles = model::role::Entity::find()
+            .filter(model::role::Column::ApplicationId.eq(application_id))
+            .all(&self.conn)
+            .await?;
+
+        Self::details(&self.conn, roles).await
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.role.create")]
+    pub async fn create(
+        &self,
+        agent: &str,
+        role_id: &str,
+        application_id: &str,
+        display_name: &str,
+        description: &str,
+    ) -> RoleResult<RoleDetailDto> {
+        model::application::Entity::find_by_id(application_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(RoleError::ApplicationNotFound {
+                application_id: application_id.into(),
+            })?;
+
+        model::role::Entity::insert(model::role::ActiveModel {
+            role_id: Set(role_id.into()),
+            application_id: Set(application_id.into()),
+            display_name: Set(display_name.into()),
+            description: Set(description.into()),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(role_id).await?.ok_or(RoleError::RoleNotFound {
+            role_id: role_id.into(),
+        })
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.role.update")]
+    pub async fn update(
+        &self,
+        agent: &str,
+        role_id: &str,
+        display_name: Option<&str>,
+        description: Option<&str>,
+    ) -> RoleResult<RoleDetailDto> {
+        if display_name.is_none() && description.is_none() {
+            return Err(RoleError::NoChangeRequested);
+        }
+
+        let mut role = model::role::Entity::find_by_id(role_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(RoleError::RoleNotFound {
+                role_id: role_id.into(),
+            })?
+            .into_active_model();
+
+        role.display_name = display_name.into_active_value_ext();
+        role.description = description.into_active_value_ext();
+        role.updated_by = Set(agent.into());
+        role.updated_at = Set(Utc::now().naive_utc());
+
+        role.update(&self.conn).await?;
+
+        self.by_id(role_id).await?.ok_or(RoleError::RoleNotFound {
+            role_id: role_id.into(),
+        })
+    }
+
+    /// Deleting a role takes its grants away from every user it was assigned to
+    #[tracing::instrument(level = Level::DEBUG, "data.role.delete")]
+    pub async fn delete(&self, role_id: &str) -> RoleResult<bool> {
+        let it = model::role::Entity::delete_by_id(role_id)
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Adds a grant to the role or removes it, only grants of the role's application qualify
+    #[tracing::instrument(level = Level::DEBUG, "data.role.update_grant")]
+    pub async fn update_grant(
+        &self,
+        agent: &str,
+        role_id: &str,
+        grant_id: &str,
+        enabled: bool,
+    ) -> RoleResult<()> {
+        let txn = self.conn.begin().await?;
+
+        let role = model::role::Entity::find_by_id(role_id)
+            .one(&txn)
+            .await?
+            .ok_or(RoleError::RoleNotFound {
+                role_id: role_id.into(),
+            })?;
+        let grant = model::grant::Entity::find_by_id(grant_id)
+            .one(&txn)
+            .await?
+            .ok_or(RoleError::GrantNotFound {
+                grant_id: grant_id.into(),
+            })?;
+
+        if grant.application_id != role.application_id {
+            return Err(RoleError::GrantOutsideApplication {
+                grant_id: grant_id.into(),
+                application_id: role.application_id,
+            });
+        }
+
+        if enabled {
+            let on_conflict = OnConflict::columns([
+                model::role_grant::Column::RoleId,
+                model::role_grant::Column::GrantId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::role_grant::Entity::insert(model::role_grant::ActiveModel {
+                role_id: Set(role_id.into()),
+                grant_id: Set(grant_id.into()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::role_grant::Entity::delete_many()
+                .filter(model::role_grant::Column::RoleId.eq(role_id))
+                .filter(model::role_grant::Column::GrantId.eq(grant_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        let mut role = role.into_active_model();
+        role.updated_by = Set(agent.into());
+        role.updated_at = Set(Utc::now().naive_utc());
+        role.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Assigns the role to the user or takes it away
+    #[tracing::instrument(level = Level::DEBUG, "data.role.update_user")]
+    pub async fn update_user(
+        &self,
+        agent: &str,
+        user_id: i32,
+        role_id: &str,
+        enabled: bool,
+    ) -> RoleResult<()> {
+        let txn = self.conn.begin().await?;
+
+        model::role::Entity::find_by_id(role_id)
+            .one(&txn)
+            .await?
+            .ok_or(RoleError::RoleNotFound {
+                role_id: role_id.into(),
+            })?;
+        let mut user = model::user::Entity::find_by_id(user_id)
+            .one(&txn)
+            .await?
+            .ok_or(RoleError::UserNotFound { user_id })?
+            .into_active_model();
+
+        if enabled {
+            let on_conflict = OnConflict::columns([
+                model::user_role::Column::UserId,
+                model::user_role::Column::RoleId,
+            ])
+            .do_nothing()
+            .to_owned();
+
+            model::user_role::Entity::insert(model::user_role::ActiveModel {
+                user_id: Set(user_id),
+                role_id: Set(role_id.into()),
+                created_by: Set(agent.into()),
+                created_at: Set(Utc::now().naive_utc()),
+                ..Default::default()
+            })
+            .on_conflict(on_conflict)
+            .exec_without_returning(&txn)
+            .await?;
+        } else {
+            model::user_role::Entity::delete_many()
+                .filter(model::user_role::Column::UserId.eq(user_id))
+                .filter(model::user_role::Column::RoleId.eq(role_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        user.updated_by = Set(agent.into());
+        user.updated_at = Set(Utc::now().naive_utc());
+        user.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
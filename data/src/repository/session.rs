@@ -0,0 +1,183 @@
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QuerySelect,
+    TransactionTrait,
+    sea_query::Expr,
+    sqlx::types::chrono::{DateTime, Utc},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, session::SessionDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum SessionError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No session was found with session_id={session_id}")]
+    SessionNotFound { session_id: String },
+}
+impl<E: Into<RepositoryError>> From<E> for SessionError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type SessionResult<T> = Result<T, SessionError>;
+
+#[derive(Clone, Debug)]
+pub struct SessionRepository {
+    conn: DatabaseConnection,
+}
+impl SessionRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.session.by_id")]
+    pub async fn by_id(&self, session_id: &str) -> SessionResult<Option<SessionDto>> {
+        let Some(session) = model::session::Entity::find_by_id(session_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(SessionDto::try_from(session)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.session.create")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &self,
+        agent: &str,
+        session_id: &str,
+        user_id: i32,
+        token_id: &str,
+        amr: &[String],
+        application_id: Option<&str>,
+        scope: &[String],
+        expires_at: DateTime<Utc>,
+    ) -> SessionResult<SessionDto> {
+        model::session::Entity::insert(model::session::ActiveModel {
+            session_id: Set(session_id.into()),
+            user_id: Set(user_id),
+            last_token_id: Set(token_id.into()),
+            expires_at: Set(expires_at.naive_utc()),
+            revoked_at: Set(None),
+            revoked_by: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            amr: Set(amr.join(",")),
+            application_id: Set(application_id.map(String::from)),
+            scope: Set(scope.join(" ")),
+        })
+        .exec(&self.conn)
+        .await?;
+
+        self.by_id(session_id)
+            .await?
+            .ok_or(SessionError::SessionNotFound {
+                session_id: session_id.into(),
+            })
+    }
+
+    /// Records a newly issued token against the session and pushes out its expiry
+    #[tracing::instrument(level = Level::DEBUG, "data.session.record_token")]
+    pub async fn record_token(
+        &self,
+        agent: &str,
+        session_id: &str,
+        token_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> SessionResult<SessionDto> {
+        let mut session = model::session::Entity::find_by_id(session_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(SessionError::SessionNotFound {
+                session_id: session_id.into(),
+            })?
+            .into_active_model();
+
+        session.last_token_id = Set(token_id.into());
+        session.expires_at = Set(expires_at.naive_utc());
+        session.updated_by = Set(agent.into());
+        session.updated_at = Set(Utc::now().naive_utc());
+
+        session.update(&self.conn).await?;
+
+        self.by_id(session_id)
+            .await?
+            .ok_or(SessionError::SessionNotFound {
+                session_id: session_id.into(),
+            })
+    }
+
+    /// Revokes a single session, returns false if it was already revoked
+    #[tracing::instrument(level = Level::DEBUG, "data.session.revoke")]
+    pub async fn revoke(&self, agent: &str, session_id: &str) -> SessionResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::session::Entity::update_many()
+            .col_expr(model::session::Column::RevokedAt, Expr::value(now))
+            .col_expr(model::session::Column::RevokedBy, Expr::value(agent))
+            .col_expr(model::session::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::session::Column::UpdatedAt, Expr::value(now))
+            .filter(model::session::Column::SessionId.eq(session_id))
+            .filter(model::session::Column::RevokedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Revokes every live session belonging to a user, other than `except`, returning the ids
+    /// of the sessions revoked
+    #[tracing::instrument(level = Level::DEBUG, "data.session.revoke_user")]
+    pub async fn revoke_user(
+        &self,
+        agent: &str,
+        user_id: i32,
+        except: Option<&str>,
+    ) -> SessionResult<Vec<String>> {
+        let txn = self.conn.begin().await?;
+
+        let mut query = model::session::Entity::find()
+            .select_only()
+            .column(model::session::Column::SessionId)
+            .filter(model::session::Column::UserId.eq(user_id))
+            .filter(model::session::Column::RevokedAt.is_null());
+        if let Some(except) = except {
+            query = query.filter(model::session::Column::SessionId.ne(except));
+        }
+        let session_ids: Vec<String> = query.into_tuple().all(&txn).await?;
+
+        let now = Utc::now().naive_utc();
+        model::session::Entity::update_many()
+            .col_expr(model::session::Column::RevokedAt, Expr::value(now))
+            .col_expr(model::session::Column::RevokedBy, Expr::value(agent))
+            .col_expr(model::session::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::session::Column::UpdatedAt, Expr::value(now))
+            .filter(model::session::Column::SessionId.is_in(session_ids.clone()))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(session_ids)
+    }
+}
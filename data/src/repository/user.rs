@@ -6,8 +6,9 @@ use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{self, NotSet, Set},
     ColumnTrait, Condition, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter,
-    sea_query::OnConflict,
-    sqlx::types::chrono::Utc,
+    QuerySelect, TransactionTrait,
+    sea_query::{Expr, OnConflict},
+    sqlx::types::chrono::{DateTime, Utc},
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -22,7 +23,7 @@ use crate::{
         user::{UserDetailDto, UserDto},
     },
     model,
-    repository::error::RepositoryError,
+    repository::{error::RepositoryError, group::GroupRepository, role::RoleRepository},
 };
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
@@ -40,6 +41,10 @@ pub enum UserError {
     UserNotFound { user_id: i32 },
     #[error("Called update with no changes")]
     NoChangeRequested,
+    #[error("Grant {grant_id} would be valid until before it is valid from")]
+    InvalidValidity { grant_id: String },
+    #[error("Grant {grant_id} would be enabled past its valid_until")]
+    ValidityExpired { grant_id: String },
 }
 impl<E: Into<RepositoryError>> From<E> for UserError {
     fn from(value: E) -> Self {
@@ -85,7 +90,15 @@ impl UserRepository {
             })
         }
 
-        Ok(UserDetailDto { user, grants })
+        let roles = RoleRepository::assigned::<_, UserError>(&self.conn, user.user_id).await?;
+        let groups = GroupRepository::resolved::<_, UserError>(&self.conn, user.user_id).await?;
+
+        Ok(UserDetailDto {
+            user,
+            grants,
+            roles,
+            groups,
+        })
     }
 
     #[tracing::instrument(level=Level::DEBUG, "data.user.by_id")]
@@ -116,6 +129,23 @@ impl UserRepository {
         Ok(Some(self.populate_user(user).await?))
     }
 
+    /// Only matches an email the user has verified, unverified addresses can't be used to log in
+    #[tracing::instrument(level = Level::DEBUG, "data.user.by_verified_email")]
+    pub async fn by_verified_email(&self, email: &str) -> UserResult<Option<UserDetailDto>> {
+        let Some(user) = model::user::Entity::find()
+            .filter(model::user::Column::Email.eq(email))
+            .filter(model::user::Column::EmailVerifiedAt.is_not_null())
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let user = UserDto::try_from(user)?;
+
+        Ok(Some(self.populate_user(user).await?))
+    }
+
     #[tracing::instrument(level=Level::DEBUG, "data.user.list")]
     pub async fn list(&self, enabled: Option<bool>) -> UserResult<Vec<UserDto>> {
         // TODO: Do I care about pagination?
@@ -149,6 +179,7 @@ impl UserRepository {
             image_url: image_url.into_active_value_opt_ext(),
             username: ActiveValue::Set(username.into()),
             password: ActiveValue::Set(password.into()),
+            password_salt_unique: ActiveValue::Set(true.into()),
             created_by: ActiveValue::Set(agent.into()),
             updated_by: ActiveValue::Set(agent.into()),
             created_at: Set(Utc::now().naive_utc()),
@@ -188,17 +219,24 @@ impl UserRepository {
             return Err(UserError::NoChangeRequested);
         }
 
-        let mut user: model::user::ActiveModel = model::user::Entity::find_by_id(user_id)
+        let user = model::user::Entity::find_by_id(user_id)
             .one(&self.conn)
             .await?
-            .ok_or(UserError::UserNotFound { user_id: user_id })?
-            .into_active_model();
+            .ok_or(UserError::UserNotFound { user_id: user_id })?;
+        let email_changed = email.is_some_and(|email| user.email.as_deref() != Some(email));
+        let mut user: model::user::ActiveModel = user.into_active_model();
 
         user.enabled = enabled.into_active_value_ext();
         user.display_name = display_name.into_active_value_ext();
         user.password = password.into_active_value_ext();
+        if password.is_some() {
+            user.password_salt_unique = Set(true.into());
+        }
         user.email = email.into_active_value_opt_ext();
         user.image_url = image_url.into_active_value_opt_ext();
+        if email_changed {
+            user.email_verified_at = Set(None);
+        }
         user.updated_by = ActiveValue::Set(agent.into());
         user.updated_at = ActiveValue::Set(Utc::now().naive_utc());
 
@@ -209,6 +247,55 @@ impl UserRepository {
             .ok_or(UserError::UserNotFound { user_id: user_id })
     }
 
+    /// Replaces the password hash, `change_required` forces a change at the next login
+    #[tracing::instrument(level = Level::DEBUG, "data.user.set_password", skip(password))]
+    pub async fn set_password(
+        &self,
+        agent: &str,
+        user_id: i32,
+        password: &str,
+        change_required: bool,
+    ) -> UserResult<UserDetailDto> {
+        let mut user = model::user::Entity::find_by_id(user_id)
+            .one(&self.conn)
+            .await?
+            .ok_or(UserError::UserNotFound { user_id })?
+            .into_active_model();
+
+        user.password = Set(password.into());
+        user.password_salt_unique = Set(true.into());
+        user.password_change_required = Set(change_required.into());
+        user.updated_by = Set(agent.into());
+        user.updated_at = Set(Utc::now().naive_utc());
+
+        user.update(&self.conn).await?;
+
+        self.by_id(user_id)
+            .await?
+            .ok_or(UserError::UserNotFound { user_id })
+    }
+
+    /// Marks `email` as verified, returns false if the user's email has since changed
+    #[tracing::instrument(level = Level::DEBUG, "data.user.set_email_verified")]
+    pub async fn set_email_verified(
+        &self,
+        agent: &str,
+        user_id: i32,
+        email: &str,
+    ) -> UserResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::user::Entity::update_many()
+            .col_expr(model::user::Column::EmailVerifiedAt, Expr::value(now))
+            .col_expr(model::user::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::user::Column::UpdatedAt, Expr::value(now))
+            .filter(model::user::Column::UserId.eq(user_id))
+            .filter(model::user::Column::Email.eq(email))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
     // TODO: This is like 4 queries, kinda stupid
     #[tracing::instrument(level = Level::DEBUG, "data.user.set_last_login")]
     pub async fn set_last_login(&self, user_id: i32) -> UserResult<UserDetailDto> {
@@ -242,23 +329,57 @@ impl UserRepository {
         Ok(())
     }
 
+    /// `valid_from` and `valid_until` are left as they are when `None`, and cleared when
+    /// `Some(None)`
     pub async fn update_grant(
         &self,
         agent: &str,
         user_id: i32,
         grant_id: &str,
         enabled: bool,
+        valid_from: Option<Option<DateTime<Utc>>>,
+        valid_until: Option<Option<DateTime<Utc>>>,
     ) -> UserResult<()> {
-        let model = model::user_grant::Entity::find_by_id((user_id, grant_id.into()))
-            .one(&self.conn)
-            .await?;
+        let txn = self.conn.begin().await?;
 
+        // Locked first so concurrent updates of the user's grants can't each keep the other's end
+        // of a window and together store one that fails the checks below
         let mut user = model::user::Entity::find_by_id(user_id)
-            .one(&self.conn)
+            .lock_exclusive()
+            .one(&txn)
             .await?
             .ok_or(UserError::UserNotFound { user_id })?
             .into_active_model();
 
+        let model = model::user_grant::Entity::find_by_id((user_id, grant_id.into()))
+            .one(&txn)
+            .await?;
+
+        // Checked against whichever end is kept, too
+        let from = valid_from.unwrap_or_else(|| {
+            model
+                .as_ref()
+                .and_then(|it| it.valid_from.map(|dt| dt.and_utc()))
+        });
+        let until = valid_until.unwrap_or_else(|| {
+            model
+                .as_ref()
+                .and_then(|it| it.valid_until.map(|dt| dt.and_utc()))
+        });
+        if let (Some(from), Some(until)) = (from, until) {
+            if until <= from {
+                return Err(UserError::InvalidValidity {
+                    grant_id: grant_id.into(),
+                });
+            }
+        }
+        // The sweeper would only disable it again
+        if enabled && until.is_some_and(|until| until <= Utc::now()) {
+            return Err(UserError::ValidityExpired {
+                grant_id: grant_id.into(),
+            });
+        }
+
         let mut model = match model {
             Some(model) => model.into_active_model(),
             None => model::user_grant::ActiveModel {
@@ -271,6 +392,8 @@ impl UserRepository {
                 enabled: NotSet,
                 updated_by: NotSet,
                 updated_at: NotSet,
+                valid_from: NotSet,
+                valid_until: NotSet,
             },
         };
 
@@ -286,29 +409,60 @@ impl UserRepository {
             model.enabled_at = Set(None);
         }
 
-        let on_conflict = OnConflict::columns([
-            model::user_grant::Column::UserId,
-            model::user_grant::Column::GrantId,
-        ])
-        .update_columns([
+        let mut columns = vec![
             model::user_grant::Column::Enabled,
             model::user_grant::Column::UpdatedBy,
             model::user_grant::Column::UpdatedAt,
             model::user_grant::Column::EnabledAt,
             model::user_grant::Column::DisabledAt,
+        ];
+        if let Some(valid_from) = valid_from {
+            model.valid_from = Set(valid_from.map(|it| it.naive_utc()));
+            columns.push(model::user_grant::Column::ValidFrom);
+        }
+        if let Some(valid_until) = valid_until {
+            model.valid_until = Set(valid_until.map(|it| it.naive_utc()));
+            columns.push(model::user_grant::Column::ValidUntil);
+        }
+
+        let on_conflict = OnConflict::columns([
+            model::user_grant::Column::UserId,
+            model::user_grant::Column::GrantId,
         ])
+        .update_columns(columns)
         .to_owned();
 
         crate::model::user_grant::Entity::insert(model)
             .on_conflict(on_conflict)
-            .exec(&self.conn)
+            .exec(&txn)
             .await?;
 
         user.updated_by = Set(agent.into());
         user.updated_at = Set(Utc::now().naive_utc());
 
-        user.update(&self.conn).await?;
+        user.update(&txn).await?;
+
+        txn.commit().await?;
 
         Ok(())
     }
+
+    /// Disables every enabled grant whose validity window has closed, recording `agent` as who
+    /// did it. Returns how many were disabled.
+    #[tracing::instrument(level = Level::DEBUG, "data.user.expire_grants")]
+    pub async fn expire_grants(&self, agent: &str) -> UserResult<u64> {
+        let now = Utc::now().naive_utc();
+
+        let it = model::user_grant::Entity::update_many()
+            .col_expr(model::user_grant::Column::Enabled, Expr::value(false))
+            .col_expr(model::user_grant::Column::DisabledAt, Expr::value(now))
+            .col_expr(model::user_grant::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::user_grant::Column::UpdatedAt, Expr::value(now))
+            .filter(model::user_grant::Column::Enabled.eq(true))
+            .filter(model::user_grant::Column::ValidUntil.lte(now))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected)
+    }
 }
@@ -0,0 +1,254 @@
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, SqlErr, TransactionTrait, sea_query::Expr, sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, user_identity::UserIdentityDto},
+    model,
+    repository::error::RepositoryError,
+    util::IntoActiveValueExt,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum UserIdentityError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No user identity was found with user_identity_id={user_identity_id}")]
+    IdentityNotFound { user_identity_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for UserIdentityError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type UserIdentityResult<T> = Result<T, UserIdentityError>;
+
+#[derive(Clone, Debug)]
+pub struct UserIdentityRepository {
+    conn: DatabaseConnection,
+}
+impl UserIdentityRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.by_id")]
+    pub async fn by_id(
+        &self,
+        user_identity_id: i32,
+    ) -> UserIdentityResult<Option<UserIdentityDto>> {
+        let Some(identity) = model::user_identity::Entity::find_by_id(user_identity_id)
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(UserIdentityDto::try_from(identity)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.by_subject")]
+    pub async fn by_subject(
+        &self,
+        provider_id: &str,
+        subject: &str,
+    ) -> UserIdentityResult<Option<UserIdentityDto>> {
+        let Some(identity) = model::user_identity::Entity::find()
+            .filter(model::user_identity::Column::ProviderId.eq(provider_id))
+            .filter(model::user_identity::Column::Subject.eq(subject))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(UserIdentityDto::try_from(identity)?))
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.by_user")]
+    pub async fn by_user(&self, user_id: i32) -> UserIdentityResult<Vec<UserIdentityDto>> {
+        let them = model::user_identity::Entity::find()
+            .filter(model::user_identity::Column::UserId.eq(user_id))
+            .order_by_asc(model::user_identity::Column::CreatedAt)
+            .all(&self.conn)
+            .await?;
+
+        Ok(them
+            .into_iter()
+            .map(UserIdentityDto::try_from)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    async fn insert<C: ConnectionTrait>(
+        conn: &C,
+        agent: &str,
+        user_id: i32,
+        provider_id: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> Result<i32, DbErr> {
+        let it = model::user_identity::Entity::insert(model::user_identity::ActiveModel {
+            user_id: Set(user_id),
+            provider_id: Set(provider_id.into()),
+            subject: Set(subject.into()),
+            email: Set(email.map(String::from)),
+            last_login_at: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(conn)
+        .await?;
+
+        Ok(it.last_insert_id)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.create")]
+    pub async fn create(
+        &self,
+        agent: &str,
+        user_id: i32,
+        provider_id: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> UserIdentityResult<UserIdentityDto> {
+        let user_identity_id =
+            Self::insert(&self.conn, agent, user_id, provider_id, subject, email).await?;
+
+        self.by_id(user_identity_id)
+            .await?
+            .ok_or(UserIdentityError::IdentityNotFound { user_identity_id })
+    }
+
+    /// Creates a user for an upstream identity together with the identity and the user's
+    /// `grant_ids`, all or nothing. `verified_email` is stored as already verified.
+    ///
+    /// Returns `None` when a unique constraint got in the way, usually a concurrent first login
+    /// through the same identity, in which case nothing was created.
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.provision", skip(password))]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn provision(
+        &self,
+        agent: &str,
+        username: &str,
+        password: &str,
+        display_name: Option<&str>,
+        verified_email: Option<&str>,
+        image_url: Option<&str>,
+        grant_ids: &[String],
+        provider_id: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> UserIdentityResult<Option<UserIdentityDto>> {
+        let txn = self.conn.begin().await?;
+
+        let inserted = async {
+            let now = Utc::now().naive_utc();
+            let user = model::user::Entity::insert(model::user::ActiveModel {
+                display_name: display_name.into_active_value_ext(),
+                email: verified_email.into_active_value_opt_ext(),
+                email_verified_at: Set(verified_email.map(|_| now)),
+                image_url: image_url.into_active_value_opt_ext(),
+                username: Set(username.into()),
+                password: Set(password.into()),
+                password_salt_unique: Set(true.into()),
+                created_by: Set(agent.into()),
+                updated_by: Set(agent.into()),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?;
+            let user_id = user.last_insert_id;
+
+            if !grant_ids.is_empty() {
+                model::user_grant::Entity::insert_many(grant_ids.iter().map(|grant_id| {
+                    model::user_grant::ActiveModel {
+                        user_id: Set(user_id),
+                        grant_id: Set(grant_id.clone()),
+                        enabled: Set(true.into()),
+                        enabled_at: Set(Some(now)),
+                        disabled_at: Set(None),
+                        valid_from: Set(None),
+                        valid_until: Set(None),
+                        created_by: Set(agent.into()),
+                        updated_by: Set(agent.into()),
+                        created_at: Set(now),
+                        updated_at: Set(now),
+                    }
+                }))
+                .exec(&txn)
+                .await?;
+            }
+
+            Self::insert(&txn, agent, user_id, provider_id, subject, email).await
+        }
+        .await;
+
+        let user_identity_id = match inserted {
+            Ok(user_identity_id) => user_identity_id,
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                txn.rollback().await?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        txn.commit().await?;
+
+        Ok(Some(self.by_id(user_identity_id).await?.ok_or(
+            UserIdentityError::IdentityNotFound { user_identity_id },
+        )?))
+    }
+
+    /// Records a login through the identity along with the email the provider now reports
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.record_login")]
+    pub async fn record_login(
+        &self,
+        agent: &str,
+        user_identity_id: i32,
+        email: Option<&str>,
+    ) -> UserIdentityResult<()> {
+        let now = Utc::now().naive_utc();
+        model::user_identity::Entity::update_many()
+            .col_expr(model::user_identity::Column::LastLoginAt, Expr::value(now))
+            .col_expr(
+                model::user_identity::Column::Email,
+                Expr::value(email.map(String::from)),
+            )
+            .col_expr(model::user_identity::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::user_identity::Column::UpdatedAt, Expr::value(now))
+            .filter(model::user_identity::Column::UserIdentityId.eq(user_identity_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unlinks one of `user_id`'s identities, returns false if there was no such identity
+    #[tracing::instrument(level = Level::DEBUG, "data.user_identity.delete")]
+    pub async fn delete(&self, user_id: i32, user_identity_id: i32) -> UserIdentityResult<bool> {
+        let it = model::user_identity::Entity::delete_many()
+            .filter(model::user_identity::Column::UserIdentityId.eq(user_identity_id))
+            .filter(model::user_identity::Column::UserId.eq(user_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+}
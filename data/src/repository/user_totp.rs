@@ -0,0 +1,156 @@
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter,
+    TransactionTrait, sea_query::Expr, sqlx::types::chrono::Utc,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::Level;
+use valuable::Valuable;
+
+use crate::{
+    dto::{error::DtoError, user_totp::UserTotpDto},
+    model,
+    repository::error::RepositoryError,
+};
+
+#[derive(Debug, Error, Clone, Serialize, Deserialize, Valuable)]
+pub enum UserTotpError {
+    #[error(transparent)]
+    Database { inner_error: RepositoryError },
+    #[error(transparent)]
+    Dto {
+        #[from]
+        inner_error: DtoError,
+    },
+    #[error("No TOTP enrollment was found for user_id={user_id}")]
+    TotpNotFound { user_id: i32 },
+    #[error("User with user_id={user_id} already has TOTP enabled")]
+    AlreadyEnrolled { user_id: i32 },
+}
+impl<E: Into<RepositoryError>> From<E> for UserTotpError {
+    fn from(value: E) -> Self {
+        Self::Database {
+            inner_error: value.into(),
+        }
+    }
+}
+pub type UserTotpResult<T> = Result<T, UserTotpError>;
+
+#[derive(Clone, Debug)]
+pub struct UserTotpRepository {
+    conn: DatabaseConnection,
+}
+impl UserTotpRepository {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_totp.by_user")]
+    pub async fn by_user(&self, user_id: i32) -> UserTotpResult<Option<UserTotpDto>> {
+        let Some(totp) = model::user_totp::Entity::find()
+            .filter(model::user_totp::Column::UserId.eq(user_id))
+            .one(&self.conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(UserTotpDto::try_from(totp)?))
+    }
+
+    /// Stores a new, unconfirmed secret for the user, replacing any earlier unconfirmed one
+    #[tracing::instrument(level = Level::DEBUG, "data.user_totp.enroll", skip(secret))]
+    pub async fn enroll(
+        &self,
+        agent: &str,
+        user_id: i32,
+        secret: &str,
+    ) -> UserTotpResult<UserTotpDto> {
+        let txn = self.conn.begin().await?;
+
+        let existing = model::user_totp::Entity::find()
+            .filter(model::user_totp::Column::UserId.eq(user_id))
+            .one(&txn)
+            .await?;
+        if let Some(existing) = existing {
+            if existing.confirmed_at.is_some() {
+                txn.rollback().await?;
+                return Err(UserTotpError::AlreadyEnrolled { user_id });
+            }
+
+            model::user_totp::Entity::delete_by_id(existing.user_totp_id)
+                .exec(&txn)
+                .await?;
+        }
+
+        model::user_totp::Entity::insert(model::user_totp::ActiveModel {
+            user_id: Set(user_id),
+            secret: Set(secret.into()),
+            confirmed_at: Set(None),
+            last_used_step: Set(None),
+            created_by: Set(agent.into()),
+            updated_by: Set(agent.into()),
+            created_at: Set(Utc::now().naive_utc()),
+            updated_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        self.by_user(user_id)
+            .await?
+            .ok_or(UserTotpError::TotpNotFound { user_id })
+    }
+
+    /// Marks the user's secret as confirmed, returns false if it was already confirmed
+    #[tracing::instrument(level = Level::DEBUG, "data.user_totp.confirm")]
+    pub async fn confirm(&self, agent: &str, user_id: i32, step: i64) -> UserTotpResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::user_totp::Entity::update_many()
+            .col_expr(model::user_totp::Column::ConfirmedAt, Expr::value(now))
+            .col_expr(model::user_totp::Column::LastUsedStep, Expr::value(step))
+            .col_expr(model::user_totp::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::user_totp::Column::UpdatedAt, Expr::value(now))
+            .filter(model::user_totp::Column::UserId.eq(user_id))
+            .filter(model::user_totp::Column::ConfirmedAt.is_null())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    /// Records `step` as used, returns false if it, or a later step, was already used.
+    ///
+    /// Conditional on the stored step so a code can't be replayed, even by two concurrent logins.
+    #[tracing::instrument(level = Level::DEBUG, "data.user_totp.use_step")]
+    pub async fn use_step(&self, agent: &str, user_id: i32, step: i64) -> UserTotpResult<bool> {
+        let now = Utc::now().naive_utc();
+        let it = model::user_totp::Entity::update_many()
+            .col_expr(model::user_totp::Column::LastUsedStep, Expr::value(step))
+            .col_expr(model::user_totp::Column::UpdatedBy, Expr::value(agent))
+            .col_expr(model::user_totp::Column::UpdatedAt, Expr::value(now))
+            .filter(model::user_totp::Column::UserId.eq(user_id))
+            .filter(model::user_totp::Column::ConfirmedAt.is_not_null())
+            .filter(
+                Condition::any()
+                    .add(model::user_totp::Column::LastUsedStep.is_null())
+                    .add(model::user_totp::Column::LastUsedStep.lt(step)),
+            )
+            .exec(&self.conn)
+            .await?;
+
+        Ok(it.rows_affected > 0)
+    }
+
+    #[tracing::instrument(level = Level::DEBUG, "data.user_totp.delete")]
+    pub async fn delete(&self, user_id: i32) -> UserTotpResult<()> {
+        model::user_totp::Entity::delete_many()
+            .filter(model::user_totp::Column::UserId.eq(user_id))
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,12 +1,60 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_init;
+mod m20261017_000001_refresh_token;
+mod m20261017_000002_session;
+mod m20261017_000003_login_event;
+mod m20261017_000004_login_throttle;
+mod m20261017_000005_mfa;
+mod m20261017_000006_password_change_required;
+mod m20261017_000007_password_reset_token;
+mod m20261017_000008_email_verification;
+mod m20261017_000009_oauth_client;
+mod m20261017_000010_client_credentials;
+mod m20261017_000011_openid;
+mod m20261017_000012_personal_access_token;
+mod m20261017_000013_device_code;
+mod m20261017_000014_federation;
+mod m20261017_000015_impersonation_event;
+mod m20261017_000016_role;
+mod m20261017_000017_group;
+mod m20261017_000018_grant_implication;
+mod m20261017_000019_user_grant_validity;
+mod m20261017_000020_password_salt_unique;
+mod m20261017_000021_mfa_challenge_password;
+mod m20261017_000022_authorization_code_redirect_uri_explicit;
+mod m20261017_000023_key_promotion;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20220101_000001_init::Migration)]
+        vec![
+            Box::new(m20220101_000001_init::Migration),
+            Box::new(m20261017_000001_refresh_token::Migration),
+            Box::new(m20261017_000002_session::Migration),
+            Box::new(m20261017_000003_login_event::Migration),
+            Box::new(m20261017_000004_login_throttle::Migration),
+            Box::new(m20261017_000005_mfa::Migration),
+            Box::new(m20261017_000006_password_change_required::Migration),
+            Box::new(m20261017_000007_password_reset_token::Migration),
+            Box::new(m20261017_000008_email_verification::Migration),
+            Box::new(m20261017_000009_oauth_client::Migration),
+            Box::new(m20261017_000010_client_credentials::Migration),
+            Box::new(m20261017_000011_openid::Migration),
+            Box::new(m20261017_000012_personal_access_token::Migration),
+            Box::new(m20261017_000013_device_code::Migration),
+            Box::new(m20261017_000014_federation::Migration),
+            Box::new(m20261017_000015_impersonation_event::Migration),
+            Box::new(m20261017_000016_role::Migration),
+            Box::new(m20261017_000017_group::Migration),
+            Box::new(m20261017_000018_grant_implication::Migration),
+            Box::new(m20261017_000019_user_grant_validity::Migration),
+            Box::new(m20261017_000020_password_salt_unique::Migration),
+            Box::new(m20261017_000021_mfa_challenge_password::Migration),
+            Box::new(m20261017_000022_authorization_code_redirect_uri_explicit::Migration),
+            Box::new(m20261017_000023_key_promotion::Migration),
+        ]
     }
 }
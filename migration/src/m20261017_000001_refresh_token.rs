@@ -0,0 +1,77 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefreshToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RefreshToken::RefreshTokenId))
+                    .col(integer(RefreshToken::UserId).not_null())
+                    .col(string(RefreshToken::FamilyId).not_null())
+                    .col(string(RefreshToken::TokenHash).not_null().unique_key())
+                    .col(date_time(RefreshToken::ExpiresAt).not_null())
+                    .col(date_time_null(RefreshToken::UsedAt).default(None as Option<DateTime>))
+                    .col(date_time_null(RefreshToken::RevokedAt).default(None as Option<DateTime>))
+                    .col(string(RefreshToken::CreatedBy).not_null())
+                    .col(string(RefreshToken::UpdatedBy).not_null())
+                    .col(date_time(RefreshToken::CreatedAt).not_null())
+                    .col(date_time(RefreshToken::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RefreshToken::Table, RefreshToken::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refresh_token_family_id")
+                    .table(RefreshToken::Table)
+                    .col(RefreshToken::FamilyId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RefreshToken::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum RefreshToken {
+    Table,
+    RefreshTokenId,
+    UserId,
+    FamilyId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    RevokedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Session::Table)
+                    .if_not_exists()
+                    .col(string(Session::SessionId).primary_key().not_null())
+                    .col(integer(Session::UserId).not_null())
+                    .col(string(Session::LastTokenId).not_null())
+                    .col(date_time(Session::ExpiresAt).not_null())
+                    .col(date_time_null(Session::RevokedAt).default(None as Option<DateTime>))
+                    .col(string_null(Session::RevokedBy).default(None as Option<String>))
+                    .col(string(Session::CreatedBy).not_null())
+                    .col(string(Session::UpdatedBy).not_null())
+                    .col(date_time(Session::CreatedAt).not_null())
+                    .col(date_time(Session::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Session::Table, Session::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Session::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    SessionId,
+    UserId,
+    LastTokenId,
+    ExpiresAt,
+    RevokedAt,
+    RevokedBy,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(LoginEvent::LoginEventId))
+                    .col(integer_null(LoginEvent::UserId).default(None as Option<i32>))
+                    .col(string(LoginEvent::Username).not_null())
+                    .col(string(LoginEvent::Outcome).not_null())
+                    .col(string_null(LoginEvent::SourceIp).default(None as Option<String>))
+                    .col(string_null(LoginEvent::UserAgent).default(None as Option<String>))
+                    .col(string(LoginEvent::CreatedBy).not_null())
+                    .col(date_time(LoginEvent::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(LoginEvent::Table, LoginEvent::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_event_user_id_created_at")
+                    .table(LoginEvent::Table)
+                    .col(LoginEvent::UserId)
+                    .col(LoginEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginEvent::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum LoginEvent {
+    Table,
+    LoginEventId,
+    UserId,
+    Username,
+    Outcome,
+    SourceIp,
+    UserAgent,
+    CreatedBy,
+    CreatedAt,
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginThrottle::Table)
+                    .if_not_exists()
+                    .col(string(LoginThrottle::ThrottleKey).primary_key().not_null())
+                    .col(integer(LoginThrottle::Failures).not_null().default(0))
+                    .col(
+                        date_time_null(LoginThrottle::LockedUntil)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(date_time(LoginThrottle::LastFailureAt).not_null())
+                    .col(date_time(LoginThrottle::UpdatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginThrottle::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginThrottle {
+    Table,
+    ThrottleKey,
+    Failures,
+    LockedUntil,
+    LastFailureAt,
+    UpdatedAt,
+}
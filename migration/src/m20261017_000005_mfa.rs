@@ -0,0 +1,167 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserTotp::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserTotp::UserTotpId))
+                    .col(integer(UserTotp::UserId).not_null().unique_key())
+                    .col(string(UserTotp::Secret).not_null())
+                    .col(date_time_null(UserTotp::ConfirmedAt).default(None as Option<DateTime>))
+                    .col(big_integer_null(UserTotp::LastUsedStep).default(None as Option<i64>))
+                    .col(string(UserTotp::CreatedBy).not_null())
+                    .col(string(UserTotp::UpdatedBy).not_null())
+                    .col(date_time(UserTotp::CreatedAt).not_null())
+                    .col(date_time(UserTotp::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserTotp::Table, UserTotp::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RecoveryCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RecoveryCode::RecoveryCodeId))
+                    .col(integer(RecoveryCode::UserId).not_null())
+                    .col(string(RecoveryCode::CodeHash).not_null().unique_key())
+                    .col(date_time_null(RecoveryCode::UsedAt).default(None as Option<DateTime>))
+                    .col(string(RecoveryCode::CreatedBy).not_null())
+                    .col(string(RecoveryCode::UpdatedBy).not_null())
+                    .col(date_time(RecoveryCode::CreatedAt).not_null())
+                    .col(date_time(RecoveryCode::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RecoveryCode::Table, RecoveryCode::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MfaChallenge::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MfaChallenge::MfaChallengeId))
+                    .col(integer(MfaChallenge::UserId).not_null())
+                    .col(string(MfaChallenge::ChallengeHash).not_null().unique_key())
+                    .col(date_time(MfaChallenge::ExpiresAt).not_null())
+                    .col(date_time_null(MfaChallenge::UsedAt).default(None as Option<DateTime>))
+                    .col(string(MfaChallenge::CreatedBy).not_null())
+                    .col(string(MfaChallenge::UpdatedBy).not_null())
+                    .col(date_time(MfaChallenge::CreatedAt).not_null())
+                    .col(date_time(MfaChallenge::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MfaChallenge::Table, MfaChallenge::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Carried over on refresh so the amr claim survives for the life of the session
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .add_column(string(Session::Amr).not_null().default("pwd"))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .drop_column(Session::Amr)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(MfaChallenge::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(RecoveryCode::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserTotp::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    Amr,
+}
+
+#[derive(DeriveIden)]
+enum UserTotp {
+    Table,
+    UserTotpId,
+    UserId,
+    Secret,
+    ConfirmedAt,
+    LastUsedStep,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RecoveryCode {
+    Table,
+    RecoveryCodeId,
+    UserId,
+    CodeHash,
+    UsedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MfaChallenge {
+    Table,
+    MfaChallengeId,
+    UserId,
+    ChallengeHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordResetToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PasswordResetToken::PasswordResetTokenId))
+                    .col(integer(PasswordResetToken::UserId).not_null())
+                    .col(
+                        string(PasswordResetToken::TokenHash)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(date_time(PasswordResetToken::ExpiresAt).not_null())
+                    .col(
+                        date_time_null(PasswordResetToken::UsedAt)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(string(PasswordResetToken::CreatedBy).not_null())
+                    .col(string(PasswordResetToken::UpdatedBy).not_null())
+                    .col(date_time(PasswordResetToken::CreatedAt).not_null())
+                    .col(date_time(PasswordResetToken::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PasswordResetToken::Table, PasswordResetToken::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordResetToken::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum PasswordResetToken {
+    Table,
+    PasswordResetTokenId,
+    UserId,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
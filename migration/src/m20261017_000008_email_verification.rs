@@ -0,0 +1,120 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        date_time_null(User::EmailVerifiedAt).default(None as Option<DateTime>),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Fails if two users already share an email, those have to be resolved by hand first
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_email")
+                    .table(User::Table)
+                    .col(User::Email)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailVerificationToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(EmailVerificationToken::EmailVerificationTokenId))
+                    .col(integer(EmailVerificationToken::UserId).not_null())
+                    .col(string(EmailVerificationToken::Email).not_null())
+                    .col(
+                        string(EmailVerificationToken::TokenHash)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(date_time(EmailVerificationToken::ExpiresAt).not_null())
+                    .col(
+                        date_time_null(EmailVerificationToken::UsedAt)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(string(EmailVerificationToken::CreatedBy).not_null())
+                    .col(string(EmailVerificationToken::UpdatedBy).not_null())
+                    .col(date_time(EmailVerificationToken::CreatedAt).not_null())
+                    .col(date_time(EmailVerificationToken::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                EmailVerificationToken::Table,
+                                EmailVerificationToken::UserId,
+                            )
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(EmailVerificationToken::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_user_email")
+                    .table(User::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::EmailVerifiedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+    Email,
+    EmailVerifiedAt,
+}
+
+#[derive(DeriveIden)]
+enum EmailVerificationToken {
+    Table,
+    EmailVerificationTokenId,
+    UserId,
+    Email,
+    TokenHash,
+    ExpiresAt,
+    UsedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,221 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .add_column(string(Application::ClientType).not_null().default("public"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RedirectUri::Table)
+                    .if_not_exists()
+                    .col(string(RedirectUri::ApplicationId).not_null())
+                    .col(string(RedirectUri::RedirectUri).not_null())
+                    .col(string(RedirectUri::CreatedBy).not_null())
+                    .col(date_time(RedirectUri::CreatedAt).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(RedirectUri::ApplicationId)
+                            .col(RedirectUri::RedirectUri),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RedirectUri::Table, RedirectUri::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthorizationCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AuthorizationCode::AuthorizationCodeId))
+                    .col(integer(AuthorizationCode::UserId).not_null())
+                    .col(string(AuthorizationCode::ApplicationId).not_null())
+                    .col(string(AuthorizationCode::CodeHash).not_null().unique_key())
+                    .col(string(AuthorizationCode::RedirectUri).not_null())
+                    .col(string(AuthorizationCode::CodeChallenge).not_null())
+                    .col(string(AuthorizationCode::Amr).not_null())
+                    .col(date_time(AuthorizationCode::ExpiresAt).not_null())
+                    .col(
+                        date_time_null(AuthorizationCode::UsedAt).default(None as Option<DateTime>),
+                    )
+                    .col(string_null(AuthorizationCode::SessionId).default(None as Option<String>))
+                    .col(string(AuthorizationCode::CreatedBy).not_null())
+                    .col(string(AuthorizationCode::UpdatedBy).not_null())
+                    .col(date_time(AuthorizationCode::CreatedAt).not_null())
+                    .col(date_time(AuthorizationCode::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AuthorizationCode::Table, AuthorizationCode::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(AuthorizationCode::Table, AuthorizationCode::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Consent::Table)
+                    .if_not_exists()
+                    .col(integer(Consent::UserId).not_null())
+                    .col(string(Consent::ApplicationId).not_null())
+                    .col(string(Consent::CreatedBy).not_null())
+                    .col(string(Consent::UpdatedBy).not_null())
+                    .col(date_time(Consent::CreatedAt).not_null())
+                    .col(date_time(Consent::UpdatedAt).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(Consent::UserId)
+                            .col(Consent::ApplicationId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Consent::Table, Consent::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Consent::Table, Consent::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // OAuth sessions only ever carry the client's own grants, refreshes included
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .add_column(string_null(Session::ApplicationId).default(None as Option<String>))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_session_application_id")
+                            .from_tbl(Session::Table)
+                            .from_col(Session::ApplicationId)
+                            .to_tbl(Application::Table)
+                            .to_col(Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .drop_foreign_key("fk_session_application_id")
+                    .drop_column(Session::ApplicationId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Consent::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AuthorizationCode::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(RedirectUri::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .drop_column(Application::ClientType)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Application {
+    Table,
+    ApplicationId,
+    ClientType,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    ApplicationId,
+}
+
+#[derive(DeriveIden)]
+enum RedirectUri {
+    Table,
+    ApplicationId,
+    RedirectUri,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum AuthorizationCode {
+    Table,
+    AuthorizationCodeId,
+    UserId,
+    ApplicationId,
+    CodeHash,
+    RedirectUri,
+    CodeChallenge,
+    Amr,
+    ExpiresAt,
+    UsedAt,
+    SessionId,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Consent {
+    Table,
+    UserId,
+    ApplicationId,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
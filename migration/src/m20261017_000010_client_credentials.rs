@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .add_column(
+                        string_null(Application::ClientSecret).default(None as Option<String>),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Grants held by the application itself, as the subject of client_credentials tokens.
+        // A surrogate key, as application already relates to grant through grant.application_id
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClientGrant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ClientGrant::ClientGrantId))
+                    .col(string(ClientGrant::ApplicationId).not_null())
+                    .col(string(ClientGrant::GrantId).not_null())
+                    .col(boolean(ClientGrant::Enabled).not_null().default(true))
+                    .col(date_time_null(ClientGrant::EnabledAt).default(None as Option<DateTime>))
+                    .col(date_time_null(ClientGrant::DisabledAt).default(None as Option<DateTime>))
+                    .col(string(ClientGrant::CreatedBy).not_null())
+                    .col(string(ClientGrant::UpdatedBy).not_null())
+                    .col(date_time(ClientGrant::CreatedAt).not_null())
+                    .col(date_time(ClientGrant::UpdatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_client_grant_application_grant")
+                            .unique()
+                            .col(ClientGrant::ApplicationId)
+                            .col(ClientGrant::GrantId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClientGrant::Table, ClientGrant::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ClientGrant::Table, ClientGrant::GrantId)
+                            .to(Grant::Table, Grant::GrantId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClientGrant::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Application::Table)
+                    .drop_column(Application::ClientSecret)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Application {
+    Table,
+    ApplicationId,
+    ClientSecret,
+}
+
+#[derive(DeriveIden)]
+enum Grant {
+    Table,
+    GrantId,
+}
+
+#[derive(DeriveIden)]
+enum ClientGrant {
+    Table,
+    ClientGrantId,
+    ApplicationId,
+    GrantId,
+    Enabled,
+    EnabledAt,
+    DisabledAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuthorizationCode::Table)
+                    .add_column(string(AuthorizationCode::Scope).not_null().default(""))
+                    .add_column(
+                        string_null(AuthorizationCode::Nonce).default(None as Option<String>),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Space separated, carried into every access token of the session so refreshes keep it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .add_column(string(Session::Scope).not_null().default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .drop_column(Session::Scope)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuthorizationCode::Table)
+                    .drop_column(AuthorizationCode::Nonce)
+                    .drop_column(AuthorizationCode::Scope)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuthorizationCode {
+    Table,
+    Scope,
+    Nonce,
+}
+
+#[derive(DeriveIden)]
+enum Session {
+    Table,
+    Scope,
+}
@@ -0,0 +1,83 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PersonalAccessToken::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PersonalAccessToken::PersonalAccessTokenId))
+                    .col(integer(PersonalAccessToken::UserId).not_null())
+                    .col(string(PersonalAccessToken::Name).not_null())
+                    .col(
+                        string(PersonalAccessToken::TokenHash)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    // Comma separated, a subset of the user's grants when the token was created
+                    .col(text(PersonalAccessToken::Grants).not_null())
+                    .col(
+                        date_time_null(PersonalAccessToken::ExpiresAt)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(
+                        date_time_null(PersonalAccessToken::LastUsedAt)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(
+                        date_time_null(PersonalAccessToken::RevokedAt)
+                            .default(None as Option<DateTime>),
+                    )
+                    .col(string(PersonalAccessToken::CreatedBy).not_null())
+                    .col(string(PersonalAccessToken::UpdatedBy).not_null())
+                    .col(date_time(PersonalAccessToken::CreatedAt).not_null())
+                    .col(date_time(PersonalAccessToken::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(PersonalAccessToken::Table, PersonalAccessToken::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PersonalAccessToken::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum PersonalAccessToken {
+    Table,
+    PersonalAccessTokenId,
+    UserId,
+    Name,
+    TokenHash,
+    Grants,
+    ExpiresAt,
+    LastUsedAt,
+    RevokedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
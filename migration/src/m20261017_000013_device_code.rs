@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // RFC 8628, the user is unknown until they enter the user code and decide
+        manager
+            .create_table(
+                Table::create()
+                    .table(DeviceCode::Table)
+                    .if_not_exists()
+                    .col(pk_auto(DeviceCode::DeviceCodeId))
+                    .col(string(DeviceCode::ApplicationId).not_null())
+                    .col(string(DeviceCode::DeviceCodeHash).not_null().unique_key())
+                    .col(string(DeviceCode::UserCodeHash).not_null().unique_key())
+                    .col(string(DeviceCode::Scope).not_null().default(""))
+                    .col(integer(DeviceCode::IntervalSeconds).not_null())
+                    .col(date_time(DeviceCode::ExpiresAt).not_null())
+                    .col(date_time_null(DeviceCode::LastPolledAt).default(None as Option<DateTime>))
+                    .col(integer_null(DeviceCode::UserId).default(None as Option<i32>))
+                    .col(string_null(DeviceCode::Amr).default(None as Option<String>))
+                    .col(date_time_null(DeviceCode::ApprovedAt).default(None as Option<DateTime>))
+                    .col(date_time_null(DeviceCode::DeniedAt).default(None as Option<DateTime>))
+                    .col(date_time_null(DeviceCode::UsedAt).default(None as Option<DateTime>))
+                    .col(string_null(DeviceCode::SessionId).default(None as Option<String>))
+                    .col(string(DeviceCode::CreatedBy).not_null())
+                    .col(string(DeviceCode::UpdatedBy).not_null())
+                    .col(date_time(DeviceCode::CreatedAt).not_null())
+                    .col(date_time(DeviceCode::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(DeviceCode::Table, DeviceCode::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(DeviceCode::Table, DeviceCode::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DeviceCode::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Application {
+    Table,
+    ApplicationId,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum DeviceCode {
+    Table,
+    DeviceCodeId,
+    ApplicationId,
+    DeviceCodeHash,
+    UserCodeHash,
+    Scope,
+    IntervalSeconds,
+    ExpiresAt,
+    LastPolledAt,
+    UserId,
+    Amr,
+    ApprovedAt,
+    DeniedAt,
+    UsedAt,
+    SessionId,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,126 @@
+use sea_orm_migration::{prelude::*, schema::*, sea_orm::prelude::DateTime};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // An upstream account, `subject` is only unique within its provider
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentity::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserIdentity::UserIdentityId))
+                    .col(integer(UserIdentity::UserId).not_null())
+                    .col(string(UserIdentity::ProviderId).not_null())
+                    .col(string(UserIdentity::Subject).not_null())
+                    .col(string_null(UserIdentity::Email).default(None as Option<String>))
+                    .col(
+                        date_time_null(UserIdentity::LastLoginAt).default(None as Option<DateTime>),
+                    )
+                    .col(string(UserIdentity::CreatedBy).not_null())
+                    .col(string(UserIdentity::UpdatedBy).not_null())
+                    .col(date_time(UserIdentity::CreatedAt).not_null())
+                    .col(date_time(UserIdentity::UpdatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_user_identity_provider_subject")
+                            .unique()
+                            .col(UserIdentity::ProviderId)
+                            .col(UserIdentity::Subject),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserIdentity::Table, UserIdentity::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A login or link in progress at an upstream provider, looked up by the state it returns
+        // with. `user_id` is set when linking to an existing user.
+        manager
+            .create_table(
+                Table::create()
+                    .table(FederationRequest::Table)
+                    .if_not_exists()
+                    .col(pk_auto(FederationRequest::FederationRequestId))
+                    .col(string(FederationRequest::StateHash).not_null().unique_key())
+                    .col(string(FederationRequest::ProviderId).not_null())
+                    .col(string(FederationRequest::Nonce).not_null())
+                    .col(string(FederationRequest::CodeVerifier).not_null())
+                    .col(integer_null(FederationRequest::UserId).default(None as Option<i32>))
+                    .col(date_time(FederationRequest::ExpiresAt).not_null())
+                    .col(
+                        date_time_null(FederationRequest::UsedAt).default(None as Option<DateTime>),
+                    )
+                    .col(string(FederationRequest::CreatedBy).not_null())
+                    .col(string(FederationRequest::UpdatedBy).not_null())
+                    .col(date_time(FederationRequest::CreatedAt).not_null())
+                    .col(date_time(FederationRequest::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(FederationRequest::Table, FederationRequest::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FederationRequest::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(UserIdentity::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum UserIdentity {
+    Table,
+    UserIdentityId,
+    UserId,
+    ProviderId,
+    Subject,
+    Email,
+    LastLoginAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum FederationRequest {
+    Table,
+    FederationRequestId,
+    StateHash,
+    ProviderId,
+    Nonce,
+    CodeVerifier,
+    UserId,
+    ExpiresAt,
+    UsedAt,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Append only. Rows outlive a deleted actor, who is then only known by username
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImpersonationEvent::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ImpersonationEvent::ImpersonationEventId))
+                    .col(integer(ImpersonationEvent::UserId).not_null())
+                    .col(integer_null(ImpersonationEvent::ActorUserId).default(None as Option<i32>))
+                    .col(string(ImpersonationEvent::ActorUsername).not_null())
+                    .col(string(ImpersonationEvent::SessionId).not_null())
+                    .col(string(ImpersonationEvent::Reason).not_null())
+                    .col(date_time(ImpersonationEvent::ExpiresAt).not_null())
+                    .col(string_null(ImpersonationEvent::SourceIp).default(None as Option<String>))
+                    .col(string_null(ImpersonationEvent::UserAgent).default(None as Option<String>))
+                    .col(string(ImpersonationEvent::CreatedBy).not_null())
+                    .col(date_time(ImpersonationEvent::CreatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ImpersonationEvent::Table, ImpersonationEvent::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(ImpersonationEvent::Table, ImpersonationEvent::ActorUserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_impersonation_event_user_id_created_at")
+                    .table(ImpersonationEvent::Table)
+                    .col(ImpersonationEvent::UserId)
+                    .col(ImpersonationEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImpersonationEvent::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum ImpersonationEvent {
+    Table,
+    ImpersonationEventId,
+    UserId,
+    ActorUserId,
+    ActorUsername,
+    SessionId,
+    Reason,
+    ExpiresAt,
+    SourceIp,
+    UserAgent,
+    CreatedBy,
+    CreatedAt,
+}
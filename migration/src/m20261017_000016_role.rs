@@ -0,0 +1,168 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A named bundle of an application's grants
+        manager
+            .create_table(
+                Table::create()
+                    .table(Role::Table)
+                    .if_not_exists()
+                    .col(string(Role::RoleId).primary_key().not_null())
+                    .col(string(Role::ApplicationId).not_null())
+                    .col(string(Role::DisplayName).not_null())
+                    .col(string(Role::Description).not_null())
+                    .col(string(Role::CreatedBy).not_null())
+                    .col(string(Role::UpdatedBy).not_null())
+                    .col(date_time(Role::CreatedAt).not_null())
+                    .col(date_time(Role::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Role::Table, Role::ApplicationId)
+                            .to(Application::Table, Application::ApplicationId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Surrogate keys on both join tables, as role and grant already relate through
+        // application
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoleGrant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RoleGrant::RoleGrantId))
+                    .col(string(RoleGrant::RoleId).not_null())
+                    .col(string(RoleGrant::GrantId).not_null())
+                    .col(string(RoleGrant::CreatedBy).not_null())
+                    .col(date_time(RoleGrant::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_role_grant_role_grant")
+                            .unique()
+                            .col(RoleGrant::RoleId)
+                            .col(RoleGrant::GrantId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RoleGrant::Table, RoleGrant::RoleId)
+                            .to(Role::Table, Role::RoleId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RoleGrant::Table, RoleGrant::GrantId)
+                            .to(Grant::Table, Grant::GrantId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRole::Table)
+                    .if_not_exists()
+                    .col(pk_auto(UserRole::UserRoleId))
+                    .col(integer(UserRole::UserId).not_null())
+                    .col(string(UserRole::RoleId).not_null())
+                    .col(string(UserRole::CreatedBy).not_null())
+                    .col(date_time(UserRole::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_user_role_user_role")
+                            .unique()
+                            .col(UserRole::UserId)
+                            .col(UserRole::RoleId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserRole::Table, UserRole::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(UserRole::Table, UserRole::RoleId)
+                            .to(Role::Table, Role::RoleId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserRole::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(RoleGrant::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Role::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Application {
+    Table,
+    ApplicationId,
+}
+
+#[derive(DeriveIden)]
+enum Grant {
+    Table,
+    GrantId,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Role {
+    Table,
+    RoleId,
+    ApplicationId,
+    DisplayName,
+    Description,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum RoleGrant {
+    Table,
+    RoleGrantId,
+    RoleId,
+    GrantId,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserRole {
+    Table,
+    UserRoleId,
+    UserId,
+    RoleId,
+    CreatedBy,
+    CreatedAt,
+}
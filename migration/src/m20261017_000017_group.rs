@@ -0,0 +1,220 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Group::Table)
+                    .if_not_exists()
+                    .col(string(Group::GroupId).primary_key().not_null())
+                    .col(string(Group::DisplayName).not_null())
+                    .col(string(Group::Description).not_null())
+                    .col(string(Group::CreatedBy).not_null())
+                    .col(string(Group::UpdatedBy).not_null())
+                    .col(date_time(Group::CreatedAt).not_null())
+                    .col(date_time(Group::UpdatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Each member is either a user or another group, whose own members then inherit this
+        // group's grants and roles. Cycles are refused when a group is added.
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupMember::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GroupMember::GroupMemberId))
+                    .col(string(GroupMember::GroupId).not_null())
+                    .col(integer_null(GroupMember::UserId).default(None as Option<i32>))
+                    .col(string_null(GroupMember::MemberGroupId).default(None as Option<String>))
+                    .col(string(GroupMember::CreatedBy).not_null())
+                    .col(date_time(GroupMember::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_group_member_group_user")
+                            .unique()
+                            .col(GroupMember::GroupId)
+                            .col(GroupMember::UserId),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_group_member_group_member_group")
+                            .unique()
+                            .col(GroupMember::GroupId)
+                            .col(GroupMember::MemberGroupId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupMember::Table, GroupMember::GroupId)
+                            .to(Group::Table, Group::GroupId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupMember::Table, GroupMember::UserId)
+                            .to(User::Table, User::UserId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupMember::Table, GroupMember::MemberGroupId)
+                            .to(Group::Table, Group::GroupId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupGrant::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GroupGrant::GroupGrantId))
+                    .col(string(GroupGrant::GroupId).not_null())
+                    .col(string(GroupGrant::GrantId).not_null())
+                    .col(string(GroupGrant::CreatedBy).not_null())
+                    .col(date_time(GroupGrant::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_group_grant_group_grant")
+                            .unique()
+                            .col(GroupGrant::GroupId)
+                            .col(GroupGrant::GrantId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupGrant::Table, GroupGrant::GroupId)
+                            .to(Group::Table, Group::GroupId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupGrant::Table, GroupGrant::GrantId)
+                            .to(Grant::Table, Grant::GrantId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupRole::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GroupRole::GroupRoleId))
+                    .col(string(GroupRole::GroupId).not_null())
+                    .col(string(GroupRole::RoleId).not_null())
+                    .col(string(GroupRole::CreatedBy).not_null())
+                    .col(date_time(GroupRole::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_group_role_group_role")
+                            .unique()
+                            .col(GroupRole::GroupId)
+                            .col(GroupRole::RoleId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupRole::Table, GroupRole::GroupId)
+                            .to(Group::Table, Group::GroupId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GroupRole::Table, GroupRole::RoleId)
+                            .to(Role::Table, Role::RoleId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GroupRole::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupGrant::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupMember::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Group::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Grant {
+    Table,
+    GrantId,
+}
+
+#[derive(DeriveIden)]
+enum Role {
+    Table,
+    RoleId,
+}
+
+#[derive(DeriveIden)]
+enum Group {
+    Table,
+    GroupId,
+    DisplayName,
+    Description,
+    CreatedBy,
+    UpdatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum GroupMember {
+    Table,
+    GroupMemberId,
+    GroupId,
+    UserId,
+    MemberGroupId,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum GroupGrant {
+    Table,
+    GroupGrantId,
+    GroupId,
+    GrantId,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum GroupRole {
+    Table,
+    GroupRoleId,
+    GroupId,
+    RoleId,
+    CreatedBy,
+    CreatedAt,
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Holding grant_id implies holding implied_grant_id, and whatever that implies in turn.
+        // Cycles are refused when an implication is added.
+        manager
+            .create_table(
+                Table::create()
+                    .table(GrantImplication::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GrantImplication::GrantImplicationId))
+                    .col(string(GrantImplication::GrantId).not_null())
+                    .col(string(GrantImplication::ImpliedGrantId).not_null())
+                    .col(string(GrantImplication::CreatedBy).not_null())
+                    .col(date_time(GrantImplication::CreatedAt).not_null())
+                    .index(
+                        Index::create()
+                            .name("idx_grant_implication_grant_implied_grant")
+                            .unique()
+                            .col(GrantImplication::GrantId)
+                            .col(GrantImplication::ImpliedGrantId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GrantImplication::Table, GrantImplication::GrantId)
+                            .to(Grant::Table, Grant::GrantId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(GrantImplication::Table, GrantImplication::ImpliedGrantId)
+                            .to(Grant::Table, Grant::GrantId)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GrantImplication::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Grant {
+    Table,
+    GrantId,
+}
+
+#[derive(DeriveIden)]
+enum GrantImplication {
+    Table,
+    GrantImplicationId,
+    GrantId,
+    ImpliedGrantId,
+    CreatedBy,
+    CreatedAt,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Unlike enabled_at/disabled_at, which only record history, these bound when an enabled
+        // grant counts. Grants past valid_until are disabled by the sweeper.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserGrant::Table)
+                    .add_column(date_time_null(UserGrant::ValidFrom))
+                    .add_column(date_time_null(UserGrant::ValidUntil))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserGrant::Table)
+                    .drop_column(UserGrant::ValidFrom)
+                    .drop_column(UserGrant::ValidUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserGrant {
+    Table,
+    ValidFrom,
+    ValidUntil,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Hash of the password replacing a temporary one, only stored once the challenge passes
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MfaChallenge::Table)
+                    .add_column(
+                        string_null(MfaChallenge::NewPasswordHash).default(None as Option<String>),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MfaChallenge::Table)
+                    .drop_column(MfaChallenge::NewPasswordHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum MfaChallenge {
+    Table,
+    NewPasswordHash,
+}
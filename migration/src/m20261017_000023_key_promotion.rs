@@ -0,0 +1,52 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Append only, the latest row names the key every instance signs with
+        manager
+            .create_table(
+                Table::create()
+                    .table(KeyPromotion::Table)
+                    .if_not_exists()
+                    .col(pk_auto(KeyPromotion::KeyPromotionId))
+                    .col(string(KeyPromotion::Kid).not_null())
+                    .col(string(KeyPromotion::CreatedBy).not_null())
+                    .col(date_time(KeyPromotion::CreatedAt).not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_key_promotion_created_at")
+                    .table(KeyPromotion::Table)
+                    .col(KeyPromotion::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(KeyPromotion::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum KeyPromotion {
+    Table,
+    KeyPromotionId,
+    Kid,
+    CreatedBy,
+    CreatedAt,
+}
@@ -61,6 +61,41 @@ async fn main() -> anyhow::Result<()> {
             "Modify User Grants".to_string(),
             "Ability to assign or revoke permissions for users".to_string(),
         ),
+        (
+            "dev.thmsn.auth.user.session.revoke".to_string(),
+            "Revoke User Sessions".to_string(),
+            "Ability to log a user out of every active session".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.login.list".to_string(),
+            "View Login History".to_string(),
+            "Ability to view a user's successful and failed logins".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.unlock".to_string(),
+            "Unlock User".to_string(),
+            "Ability to clear a user's failed login lockout".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.password.reset".to_string(),
+            "Reset User Password".to_string(),
+            "Ability to set a temporary password a user must change at next login".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.impersonate".to_string(),
+            "Impersonate User".to_string(),
+            "Ability to act as a user holding no grants beyond one's own, read only".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.impersonation.list".to_string(),
+            "View Impersonations".to_string(),
+            "Ability to view who impersonated a user and why".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.user.role.update".to_string(),
+            "Modify User Roles".to_string(),
+            "Ability to assign or take away roles for users".to_string(),
+        ),
         // app management
         (
             "dev.thmsn.auth.application.create".to_string(),
@@ -82,6 +117,17 @@ async fn main() -> anyhow::Result<()> {
             "View Application Grants".to_string(),
             "Ability to retrieve permissions assigned to an application".to_string(),
         ),
+        (
+            "dev.thmsn.auth.application.secret.rotate".to_string(),
+            "Rotate Client Secret".to_string(),
+            "Ability to generate or remove an application's client secret".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.application.grant.update".to_string(),
+            "Modify Application Grants".to_string(),
+            "Ability to assign or revoke permissions for an application's service principal"
+                .to_string(),
+        ),
         // grant management
         (
             "dev.thmsn.auth.grant.create".to_string(),
@@ -93,6 +139,94 @@ async fn main() -> anyhow::Result<()> {
             "View Grant".to_string(),
             "Ability to retrieve individual permission details".to_string(),
         ),
+        (
+            "dev.thmsn.auth.grant.implication.update".to_string(),
+            "Update Grant Implications".to_string(),
+            "Ability to make one permission imply others".to_string(),
+        ),
+        // role management
+        (
+            "dev.thmsn.auth.role.create".to_string(),
+            "Create Role".to_string(),
+            "Ability to define new roles bundling an application's permissions".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.role.get".to_string(),
+            "View Role".to_string(),
+            "Ability to retrieve individual role details".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.role.list".to_string(),
+            "List Roles".to_string(),
+            "Ability to view the roles of an application".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.role.update".to_string(),
+            "Update Role".to_string(),
+            "Ability to rename or describe roles".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.role.delete".to_string(),
+            "Delete Role".to_string(),
+            "Ability to delete roles, taking them away from their users".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.role.grant.update".to_string(),
+            "Modify Role Grants".to_string(),
+            "Ability to add or remove the permissions of a role".to_string(),
+        ),
+        // group management
+        (
+            "dev.thmsn.auth.group.create".to_string(),
+            "Create Group".to_string(),
+            "Ability to define new groups of users".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.get".to_string(),
+            "View Group".to_string(),
+            "Ability to retrieve individual group details and members".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.list".to_string(),
+            "List Groups".to_string(),
+            "Ability to view all groups".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.update".to_string(),
+            "Update Group".to_string(),
+            "Ability to rename or describe groups".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.delete".to_string(),
+            "Delete Group".to_string(),
+            "Ability to delete groups, taking away what they gave their members".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.grant.update".to_string(),
+            "Modify Group Grants".to_string(),
+            "Ability to give or take away permissions for a group's members".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.role.update".to_string(),
+            "Modify Group Roles".to_string(),
+            "Ability to give or take away roles for a group's members".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.group.member.update".to_string(),
+            "Modify Group Members".to_string(),
+            "Ability to add or remove the users and nested groups of a group".to_string(),
+        ),
+        // key management
+        (
+            "dev.thmsn.auth.key.list".to_string(),
+            "List Signing Keys".to_string(),
+            "Ability to view the token signing keyring".to_string(),
+        ),
+        (
+            "dev.thmsn.auth.key.promote".to_string(),
+            "Promote Signing Key".to_string(),
+            "Ability to rotate the active token signing key".to_string(),
+        ),
     ];
 
     let admin_username = args.admin_username.clone();
@@ -104,7 +238,14 @@ async fn main() -> anyhow::Result<()> {
     let agent = "seed";
 
     let app = application_repository
-        .create(agent, app_id, app_display_name, app_description)
+        .create(
+            agent,
+            app_id,
+            app_display_name,
+            app_description,
+            "public",
+            &[],
+        )
         .await?;
 
     for (grant_id, display_name, description) in &app_grants {
@@ -132,7 +273,7 @@ async fn main() -> anyhow::Result<()> {
 
     for (grant_id, _, _) in &app_grants {
         user_repository
-            .update_grant(agent, admin.user.user_id, &grant_id, true)
+            .update_grant(agent, admin.user.user_id, &grant_id, true, None, None)
             .await?;
     }
 
@@ -143,6 +143,8 @@ async fn main() -> anyhow::Result<()> {
                 created.user.user_id,
                 grant_id,
                 enabled,
+                None,
+                None,
             )
             .await?;
     }
@@ -163,6 +165,8 @@ async fn main() -> anyhow::Result<()> {
                 created.user.user_id,
                 grant_id,
                 enabled,
+                None,
+                None,
             )
             .await?;
     }